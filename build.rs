@@ -0,0 +1,20 @@
+fn main() {
+    // sets up the linker flags node addons need (e.g. `-undefined dynamic_lookup` on macOS)
+    #[cfg(feature = "node")]
+    napi_build::setup();
+
+    // generates the C header into OUT_DIR, where the ffi tests check include/sha_256.h is still in step with src/ffi.rs
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out = std::env::var("OUT_DIR").unwrap();
+        let config = cbindgen::Config::from_file(std::path::Path::new(&dir).join("cbindgen.toml")).unwrap();
+        cbindgen::generate_with_config(&dir, config).unwrap().write_to_file(std::path::Path::new(&out).join("sha_256.h"));
+    }
+
+    // generates the UniFFI scaffolding included by src/mobile.rs
+    #[cfg(feature = "uniffi")]
+    uniffi::generate_scaffolding("src/sha_256.udl").unwrap();
+}
@@ -0,0 +1 @@
+//! Nothing to see here, the tests in `tests/` check sha_256's sqlx and diesel features against SQLite.
@@ -0,0 +1,35 @@
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::Binary;
+use diesel::sqlite::SqliteConnection as DieselConnection;
+use sha_256::{Hash256, Sha256};
+use sqlx::{Connection as _, SqliteConnection};
+
+#[test]
+fn sqlx_round_trips_through_sqlite() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    runtime.block_on(async {
+        let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+        sqlx::query("CREATE TABLE files (hash BLOB NOT NULL)").execute(&mut conn).await.unwrap();
+        let hash = Hash256::new(Sha256::new().digest(b"hello"));
+        sqlx::query("INSERT INTO files (hash) VALUES (?)").bind(hash).execute(&mut conn).await.unwrap();
+        let stored: Vec<u8> = sqlx::query_scalar("SELECT hash FROM files").fetch_one(&mut conn).await.unwrap();
+        assert_eq!(stored, hash.as_bytes());
+        let loaded: Hash256 = sqlx::query_scalar("SELECT hash FROM files").fetch_one(&mut conn).await.unwrap();
+        assert_eq!(loaded, hash);
+
+        let short = sqlx::query_scalar::<_, Hash256>("SELECT x'0102'").fetch_one(&mut conn).await;
+        assert!(short.is_err());
+    });
+}
+
+#[test]
+fn diesel_round_trips_through_sqlite() {
+    let mut conn = DieselConnection::establish(":memory:").unwrap();
+    let hash = Hash256::new(Sha256::new().digest(b"hello"));
+    let loaded: Hash256 = diesel::select(hash.into_sql::<Binary>()).get_result(&mut conn).unwrap();
+    assert_eq!(loaded, hash);
+
+    let short = diesel::select(sql::<Binary>("x'0102'")).get_result::<Hash256>(&mut conn);
+    assert!(short.is_err());
+}
@@ -1,12 +1,4 @@
-use sha_256::Sha256;
-
-fn u8a_to_hex(u8a: &[u8]) -> String {
-    let mut hex = String::new();
-    for byte in u8a {
-        hex.push_str(&format!("{:02x}", byte));
-    }
-    hex
-}
+use sha_256::{Hash256, Sha256};
 
 fn main() {
     let mut sha256: Sha256 = Sha256::new();
@@ -15,7 +7,6 @@ fn main() {
     println!("Message: {}", message);
     let message_bytes: &[u8] = message.as_bytes();
     let hash: [u8; 32] = sha256.digest(message_bytes);
-    // convert the hash to a hex string
-    let hash_hex = u8a_to_hex(&hash);
-    println!("Hash: {}", hash_hex);
+    // Hash256 displays as a hex string
+    println!("Hash: {}", Hash256::from(hash));
 }
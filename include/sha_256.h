@@ -0,0 +1,76 @@
+/* C API for the sha_256 crate. Build the library with:
+ *   cargo rustc --release --features ffi --crate-type cdylib
+ * or `--crate-type staticlib` for a static library.
+ *
+ * Generated with cbindgen from src/ffi.rs, do not edit by hand.
+ */
+
+#ifndef SHA_256_H
+#define SHA_256_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/*
+ Opaque storage for a hashing context. Allocate it wherever you like and initialise it with `sha256_init`.
+ */
+typedef struct sha256_ctx {
+  uint64_t opaque[64];
+} sha256_ctx;
+
+/*
+ Opaque storage for a HMAC context. Allocate it wherever you like and initialise it with `hmac_sha256_init`.
+ */
+typedef struct hmac_sha256_ctx {
+  uint64_t opaque[128];
+} hmac_sha256_ctx;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ Initialises (or re-initialises) a hashing context.
+ */
+void sha256_init(struct sha256_ctx *ctx);
+
+/*
+ Absorbs `len` bytes from `data` into the context.
+ */
+void sha256_update(struct sha256_ctx *ctx, const uint8_t *data, size_t len);
+
+/*
+ Writes the 32 byte digest to `out`. The context is wiped and must be initialised again before reuse.
+ */
+void sha256_final(struct sha256_ctx *ctx, uint8_t *out);
+
+/*
+ Hashes `len` bytes from `data` in one go, writing the 32 byte digest to `out`.
+ */
+void sha256(const uint8_t *data, size_t len, uint8_t *out);
+
+/*
+ Initialises (or re-initialises) a HMAC context with a key.
+ */
+void hmac_sha256_init(struct hmac_sha256_ctx *ctx, const uint8_t *key, size_t key_len);
+
+/*
+ Absorbs `len` bytes from `data` into the HMAC context.
+ */
+void hmac_sha256_update(struct hmac_sha256_ctx *ctx, const uint8_t *data, size_t len);
+
+/*
+ Writes the 32 byte MAC to `out`. The context is wiped and must be initialised again before reuse.
+ */
+void hmac_sha256_final(struct hmac_sha256_ctx *ctx, uint8_t *out);
+
+/*
+ Computes the HMAC-SHA256 of `len` bytes from `data` in one go, writing the 32 byte MAC to `out`.
+ */
+void hmac_sha256(const uint8_t *key, size_t key_len, const uint8_t *data, size_t len, uint8_t *out);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SHA_256_H */
@@ -0,0 +1,196 @@
+//! Abbreviated digests, the shortest hex prefixes which still identify one digest in a set, like git's short commit ids.
+
+use alloc::collections::BTreeSet;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Bound;
+
+use crate::{hex, Hash256};
+
+/// A set of digests which can be abbreviated to, and looked up by, unambiguous hex prefixes.
+///
+/// ```
+/// use sha_256::{Abbreviations, Hash256, Sha256};
+/// let a = Hash256::new(Sha256::new().digest(b"a"));
+/// let mut abbreviations = Abbreviations::new(4);
+/// abbreviations.insert(a);
+/// let short = abbreviations.abbreviate(&a).unwrap();
+/// assert_eq!(short, "ca97");
+/// assert_eq!(abbreviations.resolve(&short), Some(a));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Abbreviations {
+    digests: BTreeSet<Hash256>,
+    min_len: usize,
+}
+
+impl Abbreviations {
+    /// Creates an empty set.
+    ///
+    /// # Arguments
+    /// * `min_len` - The fewest hex characters an abbreviation has, however few digests there are (git uses 7). At most 64.
+    pub fn new(min_len: usize) -> Self {
+        Self { digests: BTreeSet::new(), min_len: min_len.min(64) }
+    }
+
+    /// Adds a digest to the set.
+    ///
+    /// Adding a digest can make the abbreviations of the digests next to it in order longer, which is how git's short ids become ambiguous as a repository grows.
+    ///
+    /// # Arguments
+    /// * `digest` - The digest to add.
+    ///
+    /// # Returns
+    /// The digests already in the set whose abbreviations were lengthened, i.e. whose previous abbreviations are now ambiguous.
+    pub fn insert(&mut self, digest: Hash256) -> Vec<Hash256> {
+        if self.digests.contains(&digest) {
+            return Vec::new();
+        }
+        let neighbours = self.neighbours(&digest);
+        let before: Vec<(Hash256, usize)> = neighbours.iter().flatten().map(|n| (*n, self.shortest_len(n))).collect();
+        self.digests.insert(digest);
+        before.into_iter().filter(|(n, len)| self.shortest_len(n) > *len).map(|(n, _)| n).collect()
+    }
+
+    /// Returns the number of digests in the set.
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    /// Returns whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+
+    /// Returns the shortest unambiguous prefix of a digest in the set.
+    ///
+    /// # Arguments
+    /// * `digest` - A digest in the set.
+    ///
+    /// # Returns
+    /// The lowercase hex prefix, at least `min_len` characters, or `None` if the digest isn't in the set.
+    pub fn abbreviate(&self, digest: &Hash256) -> Option<String> {
+        if !self.digests.contains(digest) {
+            return None;
+        }
+        let hex = hex::encode(digest.as_bytes());
+        Some(hex[..self.shortest_len(digest)].iter().map(|&c| char::from(c)).collect())
+    }
+
+    /// Finds the digest an abbreviation refers to.
+    ///
+    /// # Arguments
+    /// * `prefix` - Hex characters, either case, as many as the whole digest.
+    ///
+    /// # Returns
+    /// The only digest starting with `prefix`, or `None` if there are none, several (see `is_ambiguous`) or `prefix` isn't hex.
+    pub fn resolve(&self, prefix: &str) -> Option<Hash256> {
+        let mut matches = self.matches(prefix)?;
+        let first = matches.next()?;
+        matches.next().is_none().then_some(first)
+    }
+
+    /// Returns whether an abbreviation matches more than one digest in the set.
+    ///
+    /// # Arguments
+    /// * `prefix` - Hex characters, either case.
+    pub fn is_ambiguous(&self, prefix: &str) -> bool {
+        self.matches(prefix).is_some_and(|matches| matches.take(2).count() == 2)
+    }
+
+    /// Returns an iterator over the digests starting with `prefix` in order, or `None` if `prefix` isn't hex or is too long.
+    fn matches<'a>(&'a self, prefix: &str) -> Option<impl Iterator<Item = Hash256> + 'a> {
+        let nibbles: Vec<u8> = prefix.chars().map(|c| c.to_digit(16).map(|d| d as u8)).collect::<Option<_>>()?;
+        if nibbles.len() > 64 {
+            return None;
+        }
+        // the smallest digest with the prefix is the prefix followed by 0's
+        let mut lowest = [0u8; 32];
+        for (i, nibble) in nibbles.iter().enumerate() {
+            lowest[i / 2] |= nibble << if i % 2 == 0 { 4 } else { 0 };
+        }
+        let len = nibbles.len();
+        Some(self.digests.range(Hash256::new(lowest)..).copied().take_while(move |digest| common_nibbles(digest, &Hash256::new(lowest)) >= len))
+    }
+
+    /// The digests either side of `digest` in order, the only ones which can share a longer prefix with it than the others do.
+    fn neighbours(&self, digest: &Hash256) -> [Option<Hash256>; 2] {
+        let below = self.digests.range(..digest).next_back().copied();
+        let above = self.digests.range((Bound::Excluded(digest), Bound::Unbounded)).next().copied();
+        [below, above]
+    }
+
+    /// One more hex character than `digest` shares with either neighbour, at least `min_len`.
+    fn shortest_len(&self, digest: &Hash256) -> usize {
+        let shared = self.neighbours(digest).iter().flatten().map(|n| common_nibbles(digest, n)).max().map_or(0, |shared| shared + 1);
+        shared.max(self.min_len).min(64)
+    }
+}
+
+impl FromIterator<Hash256> for Abbreviations {
+    /// Collects digests into a set with git's minimum abbreviation length of 7.
+    fn from_iter<I: IntoIterator<Item = Hash256>>(iter: I) -> Self {
+        let mut abbreviations = Self::new(7);
+        abbreviations.digests.extend(iter);
+        abbreviations
+    }
+}
+
+/// The number of leading hex characters two digests have in common.
+fn common_nibbles(a: &Hash256, b: &Hash256) -> usize {
+    for (i, (x, y)) in a.as_bytes().iter().zip(b.as_bytes()).enumerate() {
+        let diff = x ^ y;
+        if diff != 0 {
+            return i * 2 + if diff >> 4 == 0 { 1 } else { 0 };
+        }
+    }
+    64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(hex: &str) -> Hash256 {
+        let mut padded = String::from(hex);
+        padded.extend(core::iter::repeat_n('0', 64 - hex.len()));
+        Hash256::new(crate::hex::decode(padded.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn abbreviates_and_resolves() {
+        let mut abbreviations = Abbreviations::new(2);
+        let a = digest("abc1");
+        let b = digest("abd2");
+        let c = digest("f");
+        assert!(abbreviations.insert(a).is_empty());
+        assert!(abbreviations.insert(c).is_empty());
+        assert_eq!(abbreviations.abbreviate(&a).unwrap(), "ab");
+        assert_eq!(abbreviations.abbreviate(&c).unwrap(), "f0");
+
+        // "ab" no longer identifies a
+        assert_eq!(abbreviations.insert(b), [a]);
+        assert!(abbreviations.insert(b).is_empty());
+        assert!(abbreviations.is_ambiguous("AB"));
+        assert_eq!(abbreviations.resolve("ab"), None);
+        assert_eq!(abbreviations.abbreviate(&a).unwrap(), "abc");
+        assert_eq!(abbreviations.abbreviate(&b).unwrap(), "abd");
+        assert_eq!(abbreviations.resolve("abd"), Some(b));
+        assert_eq!(abbreviations.resolve("abd3"), None);
+        assert_eq!(abbreviations.resolve("xyz"), None);
+        assert_eq!(abbreviations.abbreviate(&digest("1")), None);
+        assert_eq!(abbreviations.len(), 3);
+    }
+
+    #[test]
+    fn every_abbreviation_resolves() {
+        let abbreviations: Abbreviations = (0..2000u32).map(|i| Hash256::new(crate::Sha256::new().digest(&i.to_le_bytes()))).collect();
+        for digest in abbreviations.digests.iter() {
+            let short = abbreviations.abbreviate(digest).unwrap();
+            assert!(short.len() >= 7);
+            assert_eq!(abbreviations.resolve(&short), Some(*digest));
+            // one character fewer is ambiguous unless it's the minimum length
+            assert!(short.len() == 7 || abbreviations.is_ambiguous(&short[..short.len() - 1]));
+        }
+    }
+}
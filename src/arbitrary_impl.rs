@@ -0,0 +1,66 @@
+//! `Arbitrary` support for fuzzing.
+//!
+//! A differential fuzz target checking the streaming API against one-shot hashing (or another implementation) is just:
+//! ```ignore
+//! fuzz_target!(|script: sha_256::HashScript| {
+//!     assert_eq!(script.run(), sha_256::Sha256::new().digest(&script.message()));
+//! });
+//! ```
+
+use std::vec::Vec;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::{Hash256, Sha256};
+
+impl<'a> Arbitrary<'a> for Hash256 {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        <[u8; 32]>::arbitrary(u).map(Hash256::new)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[u8; 32]>::size_hint(depth)
+    }
+}
+
+/// A message split into a sequence of `update` calls, so fuzzers can explore how data is chunked as well as the data itself.
+#[derive(Clone, PartialEq, Eq, Debug, Arbitrary)]
+pub struct HashScript {
+    /// The data passed to each `update` call, in order.
+    pub updates: Vec<Vec<u8>>,
+}
+
+impl HashScript {
+    /// Returns the whole message, i.e. the concatenation of every update.
+    pub fn message(&self) -> Vec<u8> {
+        self.updates.concat()
+    }
+
+    /// Hashes the message by streaming the updates through a single hasher.
+    ///
+    /// # Returns
+    /// The hash of the message.
+    pub fn run(&self) -> [u8; 32] {
+        let mut sha256 = Sha256::new();
+        for update in &self.updates {
+            sha256.update(update);
+        }
+        sha256.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_stream_the_message() {
+        let seed: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2654435761) >> 24) as u8).collect();
+        let mut u = Unstructured::new(&seed);
+        while !u.is_empty() {
+            let script = HashScript::arbitrary(&mut u).unwrap();
+            assert_eq!(script.run(), Sha256::new().digest(&script.message()));
+        }
+        assert_eq!(Hash256::arbitrary(&mut Unstructured::new(&[7u8; 32])).unwrap(), Hash256::new([7u8; 32]));
+    }
+}
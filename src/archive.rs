@@ -0,0 +1,155 @@
+//! Hashing the members of tar and zip archives without extracting them.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::string::String;
+use std::vec::Vec;
+
+use crate::{Hash256, HashingReader};
+
+/// The hash of one file inside an archive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// The member's path within the archive, as stored in the archive.
+    pub name: String,
+    /// The member's uncompressed size in bytes.
+    pub size: u64,
+    /// The hash of the member's uncompressed contents.
+    pub hash: Hash256,
+}
+
+/// Hashes every regular file in a tar, gzipped tar or zip archive, streaming each one through the hasher rather than extracting it.
+///
+/// The format is detected from the first bytes of the archive, anything which isn't zip or gzip is read as a plain tar.
+/// Directories, links and other special entries are skipped.
+///
+/// # Arguments
+/// * `archive` - The archive, seekable as the zip central directory is at the end.
+///
+/// # Returns
+/// The files in the order they appear in the archive, or an IO error (`InvalidData` if the archive is malformed).
+pub fn hash_archive<R: Read + Seek>(mut archive: R) -> io::Result<Vec<ArchiveEntry>> {
+    // a single read may return less than the whole magic
+    let mut magic = Vec::with_capacity(4);
+    archive.by_ref().take(4).read_to_end(&mut magic)?;
+    archive.seek(SeekFrom::Start(0))?;
+    match &magic[..] {
+        [b'P', b'K', 3, 4] | [b'P', b'K', 5, 6] => hash_zip(archive),
+        [0x1f, 0x8b, ..] => hash_tar(flate2::read::GzDecoder::new(archive)),
+        _ => hash_tar(archive),
+    }
+}
+
+fn hash_tar<R: Read>(archive: R) -> io::Result<Vec<ArchiveEntry>> {
+    let mut archive = tar::Archive::new(archive);
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        entries.push(hash_entry(name, entry)?);
+    }
+    Ok(entries)
+}
+
+fn hash_zip<R: Read + Seek>(archive: R) -> io::Result<Vec<ArchiveEntry>> {
+    let mut archive = zip::ZipArchive::new(archive).map_err(invalid_data)?;
+    let mut entries = Vec::new();
+    for i in 0..archive.len() {
+        let entry = archive.by_index(i).map_err(invalid_data)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let name = entry.name().into();
+        entries.push(hash_entry(name, entry)?);
+    }
+    Ok(entries)
+}
+
+fn hash_entry<R: Read>(name: String, entry: R) -> io::Result<ArchiveEntry> {
+    let mut reader = HashingReader::new(entry);
+    let size = io::copy(&mut reader, &mut io::sink())?;
+    let (_, hash) = reader.finalize();
+    Ok(ArchiveEntry { name, size, hash })
+}
+
+fn invalid_data(e: zip::result::ZipError) -> io::Error {
+    match e {
+        zip::result::ZipError::Io(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+    use std::io::{Cursor, Write};
+
+    const FILES: [(&str, &[u8]); 2] = [("a.txt", b"hello"), ("dir/b.bin", &[7; 100_000])];
+
+    fn expected() -> Vec<ArchiveEntry> {
+        FILES
+            .iter()
+            .map(|(name, data)| ArchiveEntry { name: (*name).into(), size: data.len() as u64, hash: Hash256::new(Sha256::new().digest(data)) })
+            .collect()
+    }
+
+    // returns at most one byte per read, like a slow pipe or socket
+    struct ShortReads<R>(R);
+
+    impl<R: Read> Read for ShortReads<R> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = buf.len().min(1);
+            self.0.read(&mut buf[..len])
+        }
+    }
+
+    impl<R: Seek> Seek for ShortReads<R> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.0.seek(pos)
+        }
+    }
+
+    fn tar() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_size(0);
+        builder.append_data(&mut header, "dir/", io::empty()).unwrap();
+        for (name, data) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            builder.append_data(&mut header, name, data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn hashes_tar_members() {
+        assert_eq!(hash_archive(Cursor::new(tar())).unwrap(), expected());
+
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gz.write_all(&tar()).unwrap();
+        assert_eq!(hash_archive(Cursor::new(gz.finish().unwrap())).unwrap(), expected());
+    }
+
+    #[test]
+    fn hashes_zip_members() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.add_directory("dir/", options).unwrap();
+        for (name, data) in FILES {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(data).unwrap();
+        }
+        let zip = zip.finish().unwrap().into_inner();
+        assert_eq!(hash_archive(Cursor::new(&zip)).unwrap(), expected());
+        assert_eq!(hash_archive(ShortReads(Cursor::new(&zip))).unwrap(), expected());
+
+        let mut corrupt = tar();
+        corrupt[0..4].copy_from_slice(b"PK\x03\x04");
+        assert_eq!(hash_archive(Cursor::new(corrupt)).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
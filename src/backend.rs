@@ -0,0 +1,155 @@
+//! Pluggable SHA-256 implementations.
+
+use crate::{Hash256, ReferenceSha256, Sha256};
+
+/// A streaming SHA-256 implementation.
+///
+/// `Sha256` is this crate's own, portable implementation, and `ReferenceSha256` a slow one written to match FIPS 180-4 line by line. With the `os-crypto` feature `OsSha256` routes hashing through the operating system's crypto library instead, and with `esp` `EspSha256` through an Espressif chip's SHA accelerator.
+/// Code which is generic over `Backend` can switch between them without any other changes.
+pub trait Backend: Sized {
+    /// Creates a hasher ready to absorb a message.
+    fn new() -> Self;
+
+    /// Absorbs more of the message.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the hasher, returning the hash of everything absorbed.
+    ///
+    /// # Returns
+    /// The 32 byte SHA-256 hash.
+    fn finalize(self) -> [u8; 32];
+
+    /// Hashes a message in one go.
+    ///
+    /// # Arguments
+    /// * `msg` - A byte slice representing the message to be hashed.
+    ///
+    /// # Returns
+    /// The hash of the message.
+    fn compute(msg: &[u8]) -> Hash256 {
+        let mut hasher = Self::new();
+        hasher.update(msg);
+        Hash256::new(hasher.finalize())
+    }
+}
+
+impl Backend for Sha256 {
+    #[inline]
+    fn new() -> Self {
+        Sha256::new()
+    }
+
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        Sha256::update(self, data);
+    }
+
+    #[inline]
+    fn finalize(self) -> [u8; 32] {
+        Sha256::finalize(self)
+    }
+}
+
+/// A `Backend` chosen at run time, see `SelectableSha256`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BackendKind {
+    /// `Sha256`, the default.
+    Optimized,
+    /// `ReferenceSha256`.
+    Reference,
+    /// `OsSha256`.
+    #[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+    Os,
+}
+
+/// A hasher whose implementation is picked when it's created rather than at compile time, e.g. from a command line flag to cross-check the optimised code in production.
+// boxing would need alloc, and the size is only that of the biggest hasher
+#[allow(clippy::large_enum_variant)]
+pub enum SelectableSha256 {
+    /// Hashing with `Sha256`.
+    Optimized(Sha256),
+    /// Hashing with `ReferenceSha256`.
+    Reference(ReferenceSha256),
+    /// Hashing with `OsSha256`.
+    #[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+    Os(crate::OsSha256),
+}
+
+impl SelectableSha256 {
+    /// Creates a hasher using the given implementation.
+    ///
+    /// # Arguments
+    /// * `kind` - The implementation to hash with.
+    pub fn with_kind(kind: BackendKind) -> Self {
+        match kind {
+            BackendKind::Optimized => Self::Optimized(Sha256::new()),
+            BackendKind::Reference => Self::Reference(ReferenceSha256::new()),
+            #[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+            BackendKind::Os => Self::Os(Backend::new()),
+        }
+    }
+
+    /// Returns which implementation is hashing.
+    pub fn kind(&self) -> BackendKind {
+        match self {
+            Self::Optimized(_) => BackendKind::Optimized,
+            Self::Reference(_) => BackendKind::Reference,
+            #[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+            Self::Os(_) => BackendKind::Os,
+        }
+    }
+}
+
+impl Backend for SelectableSha256 {
+    /// Uses `Sha256`.
+    fn new() -> Self {
+        Self::with_kind(BackendKind::Optimized)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Optimized(sha256) => Backend::update(sha256, data),
+            Self::Reference(sha256) => Backend::update(sha256, data),
+            #[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+            Self::Os(sha256) => Backend::update(sha256, data),
+        }
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        match self {
+            Self::Optimized(sha256) => Backend::finalize(sha256),
+            Self::Reference(sha256) => Backend::finalize(sha256),
+            #[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+            Self::Os(sha256) => Backend::finalize(sha256),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn software_backend_matches_digest() {
+        let msg = [3u8; 200];
+        assert_eq!(<Sha256 as Backend>::compute(&msg), Hash256::new(Sha256::new().digest(&msg)));
+        #[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+        assert_eq!(crate::OsSha256::compute(&msg), <Sha256 as Backend>::compute(&msg));
+    }
+
+    #[test]
+    fn selects_the_implementation() {
+        let msg = [5u8; 100];
+        for kind in [BackendKind::Optimized, BackendKind::Reference] {
+            let mut hasher = SelectableSha256::with_kind(kind);
+            assert_eq!(hasher.kind(), kind);
+            hasher.update(&msg[..30]);
+            hasher.update(&msg[30..]);
+            assert_eq!(hasher.finalize(), Sha256::new().digest(&msg));
+        }
+    }
+}
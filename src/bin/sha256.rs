@@ -0,0 +1,569 @@
+//! A `sha256sum` style command line tool.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use sha_256::{copy_and_hash, hash_files, parse_manifest, write_manifest, Error, Hash256, ManifestEntry, ManifestFormat};
+
+const USAGE: &str = "\
+Usage: sha256 [OPTION]... [FILE]...
+Print or check the SHA-256 hash of each FILE. With no FILE, or when FILE is -, read standard input.
+
+Options:
+      --archive        hash the files inside each tar, tar.gz or zip FILE, printing their paths within the archive
+  -c, --check          read checksums from each FILE (sha256sum, --tag or JSON) and check them
+  -f, --format FORMAT  print hashes as hex (default), base64, sri (sha256-<base64>) or multihash (hex, 1220 prefix, needs the multihash feature)
+      --json           print a JSON manifest of {path, size, mtime, algorithm, digest} objects, always with hex digests
+  -j, --jobs N         hash up to N files concurrently, 0 for one per core (default 1)
+  -p, --previous FILE  reuse the digests in the manifest FILE for files whose size and mtime haven't changed
+      --tee            copy standard input to standard output unchanged, printing its hash to standard error at EOF
+  -h, --help           print this help
+
+Options only used with --check:
+      --ignore-missing don't fail or report status for missing files
+      --quiet          don't print OK for each successfully verified file
+";
+
+/// How hashes are printed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Hex,
+    Base64,
+    /// Subresource Integrity, i.e. the value of an `integrity` attribute.
+    Sri,
+    /// The hex encoded multihash, i.e. the sha2-256 code (0x12) and digest length (0x20) followed by the digest.
+    #[cfg(feature = "multihash")]
+    Multihash,
+}
+
+impl Format {
+    fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "hex" => Ok(Format::Hex),
+            "base64" => Ok(Format::Base64),
+            "sri" => Ok(Format::Sri),
+            #[cfg(feature = "multihash")]
+            "multihash" => Ok(Format::Multihash),
+            #[cfg(not(feature = "multihash"))]
+            "multihash" => Err("--format multihash requires building with the multihash feature".into()),
+            _ => Err(format!("unknown format '{}'", value)),
+        }
+    }
+
+    fn encode(self, hash: &Hash256) -> String {
+        match self {
+            Format::Hex => hash.to_string(),
+            // SRI's value is the padded base64 of the digest
+            Format::Base64 => hash.to_sri().split_off("sha256-".len()),
+            Format::Sri => hash.to_sri(),
+            #[cfg(feature = "multihash")]
+            Format::Multihash => hash.to_multihash().iter().map(|byte| format!("{:02x}", byte)).collect(),
+        }
+    }
+}
+
+/// The parsed command line.
+struct Options {
+    archive: bool,
+    check: bool,
+    ignore_missing: bool,
+    quiet: bool,
+    format: Format,
+    json: bool,
+    jobs: usize,
+    previous: Option<PathBuf>,
+    tee: bool,
+    /// File names needn't be UTF-8, so they're kept as the OS gave them.
+    files: Vec<OsString>,
+}
+
+/// Parses the arguments following the program name.
+///
+/// # Returns
+/// The options, `None` if help was requested, or a message describing the first bad argument.
+fn parse_args<I: IntoIterator<Item = OsString>>(args: I) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        archive: false,
+        check: false,
+        ignore_missing: false,
+        quiet: false,
+        format: Format::Hex,
+        json: false,
+        jobs: 1,
+        previous: None,
+        tee: false,
+        files: Vec::new(),
+    };
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // options are ASCII, so anything that isn't UTF-8 is a file name or the path of --previous=
+        let Some(text) = arg.to_str() else {
+            if let Some(value) = strip_prefix(&arg, "--previous=") {
+                options.previous = Some(value.into());
+            } else if arg.as_encoded_bytes().starts_with(b"-") {
+                return Err(format!("unrecognised option '{}'", arg.to_string_lossy()));
+            } else {
+                options.files.push(arg);
+            }
+            continue;
+        };
+        let mut value = |arg: &str| args.next().ok_or_else(|| format!("{} requires a value", arg));
+        match text {
+            "-h" | "--help" => return Ok(None),
+            "--archive" => options.archive = true,
+            "-c" | "--check" => options.check = true,
+            "--ignore-missing" => options.ignore_missing = true,
+            "--quiet" => options.quiet = true,
+            "--json" => options.json = true,
+            "--tee" => options.tee = true,
+            "-f" | "--format" => options.format = Format::parse(&value(text)?.to_string_lossy())?,
+            "-j" | "--jobs" => options.jobs = parse_jobs(&value(text)?.to_string_lossy())?,
+            "-p" | "--previous" => options.previous = Some(value(text)?.into()),
+            "--" => {
+                options.files.extend(args);
+                break;
+            }
+            _ => {
+                if let Some(value) = text.strip_prefix("--format=") {
+                    options.format = Format::parse(value)?;
+                } else if let Some(value) = text.strip_prefix("--previous=") {
+                    options.previous = Some(value.into());
+                } else if let Some(value) = text.strip_prefix("--jobs=") {
+                    options.jobs = parse_jobs(value)?;
+                } else if let Some(value) = text.strip_prefix("-j").filter(|_| text.len() > 2) {
+                    options.jobs = parse_jobs(value)?;
+                } else if text.starts_with('-') && text != "-" {
+                    return Err(format!("unrecognised option '{}'", text));
+                } else {
+                    options.files.push(arg);
+                }
+            }
+        }
+    }
+    if options.tee && (options.archive || options.previous.is_some() || options.files.iter().any(|file| file != "-")) {
+        return Err("--tee only reads standard input, and can't be used with --archive or --previous".into());
+    }
+    if options.check && (options.archive || options.json || options.tee || options.previous.is_some() || options.format != Format::Hex) {
+        return Err("--check reads checksum files, and can't be used with --archive, --format, --json, --previous or --tee".into());
+    }
+    if !options.check && (options.ignore_missing || options.quiet) {
+        return Err("--ignore-missing and --quiet are only meaningful when checking checksums".into());
+    }
+    if options.files.is_empty() {
+        options.files.push("-".into());
+    }
+    Ok(Some(options))
+}
+
+fn parse_jobs(value: &str) -> Result<usize, String> {
+    value.parse().map_err(|_| format!("invalid number of jobs '{}'", value))
+}
+
+/// `str::strip_prefix` for an argument which may not be UTF-8.
+fn strip_prefix(arg: &OsStr, prefix: &str) -> Option<OsString> {
+    let rest = arg.as_encoded_bytes().strip_prefix(prefix.as_bytes())?;
+    // SAFETY: the split is right after the ASCII prefix, so the rest is a valid encoding on its own
+    Some(unsafe { OsStr::from_encoded_bytes_unchecked(rest) }.to_owned())
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args_os().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("sha256: {}\n\n{}", message, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    if options.tee {
+        return tee(&options);
+    }
+    if options.check {
+        return check(&options);
+    }
+
+    let mut failed = false;
+    let mut report = |file: &Path, e: io::Error| {
+        eprintln!("sha256: {}: {}", file.display(), e);
+        failed = true;
+    };
+    let previous = match &options.previous {
+        Some(path) => match std::fs::read_to_string(path).map_err(Error::from).and_then(|text| parse_manifest(&text)) {
+            Ok(previous) => previous,
+            Err(e) => {
+                eprintln!("sha256: {}: {}", path.display(), e);
+                return ExitCode::from(2);
+            }
+        },
+        None => Vec::new(),
+    };
+    let records = if options.archive { hash_archives(&options, &mut report) } else { hash_inputs(&options, &previous, &mut report) };
+    let Some(records) = records else { return ExitCode::from(2) };
+
+    if io::stdout().lock().write_all(render(&options, &records).as_bytes()).is_err() || failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Formats the hashes as requested by the options.
+fn render(options: &Options, records: &[ManifestEntry]) -> String {
+    let mut output = String::new();
+    match (options.json, options.format) {
+        (true, _) => write_manifest(&mut output, records, ManifestFormat::Json).unwrap(),
+        // hex output is a sha256sum checksum file, escaping paths the same way
+        (false, Format::Hex) => write_manifest(&mut output, records, ManifestFormat::Gnu).unwrap(),
+        (false, format) => {
+            for record in records {
+                record.write_line_encoded(&mut output, &format.encode(&record.hash)).unwrap();
+                output.push('\n');
+            }
+        }
+    }
+    output
+}
+
+/// Copies stdin to stdout as it is hashed, then prints the hash to stderr, so the data can be checksummed in the middle of a pipeline.
+fn tee(options: &Options) -> ExitCode {
+    let copied = copy_and_hash(&mut io::stdin().lock(), &mut io::stdout().lock()).and_then(|copied| io::stdout().flush().map(|_| copied));
+    match copied {
+        Ok((size, hash)) => {
+            let record = ManifestEntry { path: "-".into(), hash, size: Some(size), mtime: None };
+            eprint!("{}", render(options, &[record]));
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("sha256: -: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Checks the files listed in each checksum file, like `sha256sum --check`.
+///
+/// Prints `path: OK` or `path: FAILED` for each listed file and warns about mismatches, unreadable files and malformed lines at the end.
+fn check(options: &Options) -> ExitCode {
+    let mut failed = false;
+    for file in &options.files {
+        let text = if file == "-" { io::read_to_string(io::stdin().lock()) } else { std::fs::read_to_string(file) };
+        let text = match text {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("sha256: {}: {}", Path::new(file).display(), e);
+                failed = true;
+                continue;
+            }
+        };
+        let mut output = String::new();
+        let summary = check_listed(options, &text, &mut output);
+        if io::stdout().lock().write_all(output.as_bytes()).is_err() {
+            return ExitCode::FAILURE;
+        }
+        for message in summary.warnings(Path::new(file).display()) {
+            eprintln!("sha256: {}", message);
+        }
+        failed |= !summary.passed();
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// The outcome of checking the files listed in one checksum file.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct CheckSummary {
+    /// Lines which aren't checksums, only warned about as coreutils does.
+    malformed: usize,
+    /// Listed files which were read, whether or not they matched.
+    verified: usize,
+    mismatched: usize,
+    unreadable: usize,
+    /// Listed files which don't exist, skipped with --ignore-missing.
+    missing: usize,
+}
+
+impl CheckSummary {
+    /// Whether the exit status should be success, there's nothing wrong and at least one file was checked.
+    fn passed(&self) -> bool {
+        self.verified > 0 && self.mismatched == 0 && self.unreadable == 0
+    }
+
+    /// The warnings `sha256sum` prints to standard error after checking a file, given its name.
+    fn warnings(&self, file: impl fmt::Display) -> Vec<String> {
+        let plural = |n: usize, one: &str, many: &str| format!("{} {}", n, if n == 1 { one } else { many });
+        let mut warnings = Vec::new();
+        if self.verified == 0 && self.unreadable == 0 {
+            if self.missing > 0 {
+                warnings.push(format!("{}: no file was verified", file));
+            } else {
+                warnings.push(format!("{}: no properly formatted checksum lines found", file));
+            }
+            return warnings;
+        }
+        if self.malformed > 0 {
+            warnings.push(format!("WARNING: {}", plural(self.malformed, "line is improperly formatted", "lines are improperly formatted")));
+        }
+        if self.unreadable > 0 {
+            warnings.push(format!("WARNING: {}", plural(self.unreadable, "listed file could not be read", "listed files could not be read")));
+        }
+        if self.mismatched > 0 {
+            warnings.push(format!("WARNING: {}", plural(self.mismatched, "computed checksum did NOT match", "computed checksums did NOT match")));
+        }
+        warnings
+    }
+}
+
+/// Hashes the files listed in the text of a checksum file, appending a status line for each to `output`.
+fn check_listed(options: &Options, text: &str, output: &mut String) -> CheckSummary {
+    let mut summary = CheckSummary::default();
+    let mut entries = Vec::new();
+    if text.trim_start().starts_with('[') {
+        match parse_manifest(text) {
+            Ok(parsed) => entries = parsed,
+            Err(_) => summary.malformed += 1,
+        }
+    } else {
+        // each line is parsed on its own so one bad line doesn't stop the rest being checked, lines starting with # are comments
+        for line in text.lines().filter(|line| !line.trim().is_empty() && !line.starts_with('#')) {
+            match parse_manifest(line) {
+                Ok(parsed) => entries.extend(parsed),
+                Err(_) => summary.malformed += 1,
+            }
+        }
+    }
+
+    let paths: Vec<&str> = entries.iter().map(|entry| entry.path.as_str()).collect();
+    for (entry, hash) in entries.iter().zip(hash_files(&paths, options.jobs)) {
+        let status = match hash {
+            Ok(hash) if hash == entry.hash => "OK",
+            Ok(_) => {
+                summary.mismatched += 1;
+                "FAILED"
+            }
+            Err(e) if options.ignore_missing && e.kind() == io::ErrorKind::NotFound => {
+                summary.missing += 1;
+                continue;
+            }
+            Err(e) => {
+                eprintln!("sha256: {}: {}", entry.path, e);
+                summary.unreadable += 1;
+                output.push_str(&format!("{}: FAILED open or read\n", entry.path));
+                continue;
+            }
+        };
+        summary.verified += 1;
+        if !(options.quiet && status == "OK") {
+            output.push_str(&format!("{}: {}\n", entry.path, status));
+        }
+    }
+    summary
+}
+
+/// Hashes each input file, reporting the ones which couldn't be read.
+///
+/// Files listed in `previous` which haven't changed since are not read again.
+fn hash_inputs(options: &Options, previous: &[ManifestEntry], report: &mut dyn FnMut(&Path, io::Error)) -> Option<Vec<ManifestEntry>> {
+    let previous: HashMap<&str, &ManifestEntry> = previous.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+    // stdin can only be read once and isn't worth a thread, so hash it in place and any changed files in parallel afterwards
+    let mut entries = Vec::new();
+    let mut stale = Vec::new();
+    for file in &options.files {
+        // manifests hold UTF-8 paths, so a name which isn't is listed with replacement characters
+        let path = file.to_string_lossy();
+        let entry = if file == "-" {
+            copy_and_hash(&mut io::stdin().lock(), &mut io::sink()).map(|(size, hash)| ManifestEntry { path: path.into_owned(), hash, size: Some(size), mtime: None })
+        } else {
+            std::fs::metadata(file).map(|metadata| match file.to_str().and_then(|file| previous.get(file)).filter(|old| old.is_unchanged(&metadata)) {
+                Some(old) => ManifestEntry::with_metadata(path, old.hash, &metadata),
+                None => {
+                    stale.push(entries.len());
+                    ManifestEntry::with_metadata(path, Hash256::new([0; 32]), &metadata)
+                }
+            })
+        };
+        entries.push((Path::new(file), entry));
+    }
+
+    let paths: Vec<&Path> = stale.iter().map(|&i| entries[i].0).collect();
+    for (i, hash) in stale.into_iter().zip(hash_files(&paths, options.jobs)) {
+        let (_, entry) = &mut entries[i];
+        match hash {
+            Ok(hash) => entry.as_mut().unwrap().hash = hash,
+            Err(e) => *entry = Err(e),
+        }
+    }
+
+    let mut records = Vec::new();
+    for (file, entry) in entries {
+        match entry {
+            Ok(entry) => records.push(entry),
+            Err(e) => report(file, e),
+        }
+    }
+    Some(records)
+}
+
+/// Hashes every file inside each archive, reporting the archives which couldn't be read.
+#[cfg(feature = "archive")]
+fn hash_archives(options: &Options, report: &mut dyn FnMut(&Path, io::Error)) -> Option<Vec<ManifestEntry>> {
+    use std::io::{Cursor, Read};
+
+    let mut records = Vec::new();
+    for file in &options.files {
+        let entries = if file == "-" {
+            // zip needs to seek to its central directory, which stdin can't
+            let mut archive = Vec::new();
+            io::stdin().lock().read_to_end(&mut archive).and_then(|_| sha_256::hash_archive(Cursor::new(archive)))
+        } else {
+            std::fs::File::open(file).map(io::BufReader::new).and_then(sha_256::hash_archive)
+        };
+        match entries {
+            Ok(entries) => records.extend(entries.into_iter().map(|entry| ManifestEntry { path: entry.name, hash: entry.hash, size: Some(entry.size), mtime: None })),
+            Err(e) => report(Path::new(file), e),
+        }
+    }
+    Some(records)
+}
+
+#[cfg(not(feature = "archive"))]
+fn hash_archives(_: &Options, _: &mut dyn FnMut(&Path, io::Error)) -> Option<Vec<ManifestEntry>> {
+    eprintln!("sha256: --archive requires building with the archive feature");
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Option<Options>, String> {
+        parse_args(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn parses_options() {
+        for args in [&["-j", "4", "a", "b"][..], &["a", "--jobs", "4", "b"], &["-j4", "a", "b"], &["--jobs=4", "a", "b"]] {
+            let options = parse(args).unwrap().unwrap();
+            assert_eq!(options.jobs, 4);
+            assert_eq!(options.files, ["a", "b"]);
+        }
+        let options = parse(&["--", "-j"]).unwrap().unwrap();
+        assert_eq!(options.files, ["-j"]);
+        assert_eq!(parse(&[]).unwrap().unwrap().files, ["-"]);
+        assert!(parse(&["--help"]).unwrap().is_none());
+        assert!(parse(&["-j"]).is_err());
+        assert!(parse(&["-j", "x"]).is_err());
+        assert!(parse(&["--bogus"]).is_err());
+        assert_eq!(parse(&["a"]).unwrap().unwrap().format, Format::Hex);
+        assert_eq!(parse(&["-f", "sri", "a"]).unwrap().unwrap().format, Format::Sri);
+        #[cfg(feature = "multihash")]
+        assert_eq!(parse(&["--format=multihash"]).unwrap().unwrap().format, Format::Multihash);
+        #[cfg(not(feature = "multihash"))]
+        assert!(parse(&["--format=multihash"]).is_err());
+        assert!(parse(&["--format", "base32"]).is_err());
+        assert!(parse(&["--archive", "a.tar"]).unwrap().unwrap().archive);
+        assert!(parse(&["--json", "a"]).unwrap().unwrap().json);
+        assert_eq!(parse(&["-p", "SUMS.json", "a"]).unwrap().unwrap().previous.as_deref(), Some(Path::new("SUMS.json")));
+        assert!(parse(&["--tee"]).unwrap().unwrap().tee);
+        assert!(parse(&["--tee", "-f", "sri", "-"]).unwrap().unwrap().tee);
+        assert!(parse(&["--tee", "a"]).is_err());
+        assert!(parse(&["--tee", "--archive"]).is_err());
+        let options = parse(&["-c", "--quiet", "--ignore-missing", "SHA256SUMS"]).unwrap().unwrap();
+        assert!(options.check && options.quiet && options.ignore_missing);
+        assert!(parse(&["--check", "--json"]).is_err());
+        assert!(parse(&["--check", "-f", "sri"]).is_err());
+        assert!(parse(&["--quiet", "a"]).is_err());
+
+        // file names needn't be UTF-8
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+            let name = OsString::from_vec(b"\xffname".to_vec());
+            let mut previous = OsString::from("--previous=");
+            previous.push(&name);
+            let options = parse_args([name.clone(), previous]).unwrap().unwrap();
+            assert_eq!(options.files, std::slice::from_ref(&name));
+            assert_eq!(options.previous.as_deref(), Some(Path::new(&name)));
+            assert!(parse_args([OsString::from_vec(b"--\xff".to_vec())]).is_err());
+        }
+    }
+
+    #[test]
+    fn checks_listed_files() {
+        let dir = std::env::temp_dir().join(format!("sha256-check-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let good = dir.join("good");
+        let bad = dir.join("bad");
+        let missing = dir.join("missing");
+        std::fs::write(&good, b"good").unwrap();
+        std::fs::write(&bad, b"changed").unwrap();
+        let sum = |data: &[u8]| Hash256::new(sha_256::sha256(data)).to_string();
+        let text = format!(
+            "{}  {}\n# a comment\nnot a checksum\n{}  {}\n{}  {}\n",
+            sum(b"good"),
+            good.display(),
+            sum(b"bad"),
+            bad.display(),
+            sum(b"missing"),
+            missing.display()
+        );
+
+        let mut options = parse(&["--check"]).unwrap().unwrap();
+        let mut output = String::new();
+        let summary = check_listed(&options, &text, &mut output);
+        assert_eq!(output, format!("{}: OK\n{}: FAILED\n{}: FAILED open or read\n", good.display(), bad.display(), missing.display()));
+        assert_eq!(summary, CheckSummary { malformed: 1, verified: 2, mismatched: 1, unreadable: 1, missing: 0 });
+        assert!(!summary.passed());
+        assert_eq!(summary.warnings("SUMS"), [
+            "WARNING: 1 line is improperly formatted",
+            "WARNING: 1 listed file could not be read",
+            "WARNING: 1 computed checksum did NOT match"
+        ]);
+
+        options.quiet = true;
+        options.ignore_missing = true;
+        let mut output = String::new();
+        let summary = check_listed(&options, &text, &mut output);
+        assert_eq!(output, format!("{}: FAILED\n", bad.display()));
+        assert_eq!(summary, CheckSummary { malformed: 1, verified: 2, mismatched: 1, unreadable: 0, missing: 1 });
+
+        let mut output = String::new();
+        let summary = check_listed(&options, &text.replace(&format!("{}  {}\n", sum(b"bad"), bad.display()), ""), &mut output);
+        assert!(output.is_empty());
+        assert!(summary.passed());
+
+        // only missing files is a failure, as nothing was checked
+        let summary = check_listed(&options, &format!("{}  {}\n", sum(b"missing"), missing.display()), &mut String::new());
+        assert!(!summary.passed());
+        assert_eq!(summary.warnings("SUMS"), ["SUMS: no file was verified"]);
+        let summary = check_listed(&options, "not a checksum\n", &mut String::new());
+        assert!(!summary.passed());
+        assert_eq!(summary.warnings("SUMS"), ["SUMS: no properly formatted checksum lines found"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn encodes_formats() {
+        // sha256("")
+        let hash = Hash256::new(sha_256::Sha256::new().digest(b""));
+        assert_eq!(Format::Hex.encode(&hash), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(Format::Base64.encode(&hash), "47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=");
+        assert_eq!(Format::Sri.encode(&hash), "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=");
+        // file names can't forge lines in any format
+        let options = parse(&["-f", "sri"]).unwrap().unwrap();
+        let record = ManifestEntry::new("nl\nname", hash);
+        assert_eq!(render(&options, &[record]), "\\sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=  nl\\nname\n");
+        #[cfg(feature = "multihash")]
+        assert_eq!(Format::Multihash.encode(&hash), "1220e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+    }
+}
@@ -0,0 +1,217 @@
+//! Verifying chains of Bitcoin block headers, as a light client does before trusting a block's Merkle root.
+//!
+//! A header's hash is the double SHA-256 of its 80 bytes, kept here in the byte order it is computed in (and stored in the next header).
+//! Block explorers display it reversed, and it's compared with the target as a little-endian integer.
+//!
+//! Only the proof of work is checked. Difficulty retargeting, timestamps and versions are consensus rules which need the chain's history,
+//! so a chain which passes has real work behind it but may not be one full nodes would accept.
+
+use crate::{Error, Hash256, Sha256, Target};
+
+/// Hashes data twice with SHA-256, `SHA-256(SHA-256(data))`, as Bitcoin does for block headers, transactions and Merkle trees.
+///
+/// # Arguments
+/// * `data` - The data to hash.
+pub fn sha256d(data: &[u8]) -> Hash256 {
+    let mut sha256 = Sha256::new();
+    let first = sha256.digest(data);
+    Hash256::new(sha256.digest(&first))
+}
+
+/// The fields of an 80 byte block header.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlockHeader {
+    /// The block version.
+    pub version: i32,
+    /// The hash of the previous block's header.
+    pub prev_hash: Hash256,
+    /// The root of the Merkle tree of the block's transactions.
+    pub merkle_root: Hash256,
+    /// The block's timestamp, in seconds since the Unix epoch.
+    pub time: u32,
+    /// The proof-of-work target in compact form, see `Target::from_compact`.
+    pub bits: u32,
+    /// The nonce varied by miners.
+    pub nonce: u32,
+}
+
+impl BlockHeader {
+    /// Splits a serialized header into its fields.
+    ///
+    /// # Arguments
+    /// * `bytes` - The header as it is sent on the wire, with little-endian integers.
+    pub fn parse(bytes: &[u8; 80]) -> Self {
+        let u32_at = |i: usize| u32::from_le_bytes([bytes[i], bytes[i + 1], bytes[i + 2], bytes[i + 3]]);
+        let hash_at = |i: usize| Hash256::new(bytes[i..i + 32].try_into().unwrap());
+        Self { version: u32_at(0) as i32, prev_hash: hash_at(4), merkle_root: hash_at(36), time: u32_at(68), bits: u32_at(72), nonce: u32_at(76) }
+    }
+
+    /// Serializes the header back into its 80 bytes.
+    pub fn to_bytes(&self) -> [u8; 80] {
+        let mut bytes = [0u8; 80];
+        bytes[..4].copy_from_slice(&self.version.to_le_bytes());
+        bytes[4..36].copy_from_slice(self.prev_hash.as_bytes());
+        bytes[36..68].copy_from_slice(self.merkle_root.as_bytes());
+        bytes[68..72].copy_from_slice(&self.time.to_le_bytes());
+        bytes[72..76].copy_from_slice(&self.bits.to_le_bytes());
+        bytes[76..].copy_from_slice(&self.nonce.to_le_bytes());
+        bytes
+    }
+
+    /// Returns the header's hash, the double SHA-256 of its bytes.
+    pub fn hash(&self) -> Hash256 {
+        sha256d(&self.to_bytes())
+    }
+}
+
+/// Verifies block headers one after another, checking each links to the one before and meets its own target.
+///
+/// ```
+/// use sha_256::{HeaderChain, Target};
+/// # let headers: Vec<[u8; 80]> = Vec::new();
+/// let mut chain = HeaderChain::new(Target::from_compact(0x1d00ffff).unwrap());
+/// let tip = chain.verify(&headers)?;
+/// # Ok::<(), sha_256::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct HeaderChain {
+    tip: Option<Hash256>,
+    pow_limit: Target,
+    verified: u64,
+}
+
+impl HeaderChain {
+    /// Creates a verifier for a chain starting at whichever header comes first.
+    ///
+    /// # Arguments
+    /// * `pow_limit` - The easiest target the network allows (`0x1d00ffff` in compact form on mainnet), so headers can't claim an easier one.
+    pub fn new(pow_limit: Target) -> Self {
+        Self { tip: None, pow_limit, verified: 0 }
+    }
+
+    /// Creates a verifier for headers following an already trusted block, e.g. a checkpoint.
+    ///
+    /// # Arguments
+    /// * `tip` - The hash of the trusted block's header.
+    /// * `pow_limit` - The easiest target the network allows.
+    pub fn starting_at(tip: Hash256, pow_limit: Target) -> Self {
+        Self { tip: Some(tip), pow_limit, verified: 0 }
+    }
+
+    /// Returns the hash of the last header verified (or the starting point), if any.
+    pub fn tip(&self) -> Option<Hash256> {
+        self.tip
+    }
+
+    /// Returns the number of headers verified so far.
+    pub fn len(&self) -> u64 {
+        self.verified
+    }
+
+    /// Returns whether no headers have been verified yet.
+    pub fn is_empty(&self) -> bool {
+        self.verified == 0
+    }
+
+    /// Verifies the next header and makes it the tip.
+    ///
+    /// # Arguments
+    /// * `header` - The serialized header.
+    ///
+    /// # Returns
+    /// The header's hash, or `Error::InvalidHeader` if it doesn't follow the tip, its target is invalid or easier than the limit,
+    /// or its hash doesn't meet its target. The tip is unchanged on error.
+    pub fn push(&mut self, header: &[u8; 80]) -> Result<Hash256, Error> {
+        let invalid = |reason| Error::InvalidHeader { index: self.verified, reason };
+        let fields = BlockHeader::parse(header);
+        if self.tip.is_some_and(|tip| tip != fields.prev_hash) {
+            return Err(invalid("previous hash doesn't match the tip"));
+        }
+        let target = Target::from_compact(fields.bits).map_err(|_| invalid("bits is not a valid target"))?;
+        if target > self.pow_limit {
+            return Err(invalid("target is easier than the proof-of-work limit"));
+        }
+        let hash = sha256d(header);
+        // compared as a little-endian integer
+        let mut reversed = *hash.as_bytes();
+        reversed.reverse();
+        if !target.is_met_by(&Hash256::new(reversed)) {
+            return Err(invalid("hash doesn't meet the target"));
+        }
+        self.tip = Some(hash);
+        self.verified += 1;
+        Ok(hash)
+    }
+
+    /// Verifies headers in order, stopping at the first invalid one.
+    ///
+    /// # Arguments
+    /// * `headers` - The serialized headers, each following the one before.
+    ///
+    /// # Returns
+    /// The hash of the new tip, or the first `Error::InvalidHeader`. The headers before the invalid one stay verified.
+    pub fn verify(&mut self, headers: &[[u8; 80]]) -> Result<Option<Hash256>, Error> {
+        for header in headers {
+            self.push(header)?;
+        }
+        Ok(self.tip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(hex: &str) -> [u8; 80] {
+        core::array::from_fn(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+    }
+
+    // the mainnet genesis block and blocks 1 and 2
+    const HEADERS: [&str; 3] = [
+        "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c",
+        "010000006fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000982051fd1e4ba744bbbe680e1fee14677ba1a3c3540bf7b1cdb606e857233e0e61bc6649ffff001d01e36299",
+        "010000004860eb18bf1b1620e37e9490fc8a427514416fd75159ab86688e9a8300000000d5fdcc541e25de1c7a5addedf24858b8bb665c9f36ef744ee42c316022c90f9bb0bc6649ffff001d08d2bd61",
+    ];
+
+    #[test]
+    fn verifies_mainnet_headers() {
+        let headers = HEADERS.map(header);
+        let limit = Target::from_compact(0x1d00ffff).unwrap();
+        let mut chain = HeaderChain::new(limit);
+        let tip = chain.verify(&headers).unwrap().unwrap();
+        assert_eq!(chain.len(), 3);
+        // block 2's hash as explorers show it, 000000006a625f06636b8bb6ac7b960a8d03705d1ace08b1a19da3fdcc99ddbd
+        assert_eq!(tip.as_bytes()[..4], [0xbd, 0xdd, 0x99, 0xcc]);
+        assert_eq!(tip.as_bytes()[28..], [0, 0, 0, 0]);
+
+        let genesis = BlockHeader::parse(&headers[0]);
+        assert_eq!((genesis.version, genesis.time, genesis.bits, genesis.nonce), (1, 1231006505, 0x1d00ffff, 2083236893));
+        assert_eq!(genesis.to_bytes(), headers[0]);
+        assert_eq!(genesis.hash(), BlockHeader::parse(&headers[1]).prev_hash);
+
+        // continuing from a checkpoint
+        let mut chain = HeaderChain::starting_at(genesis.hash(), limit);
+        assert_eq!(chain.verify(&headers[1..]).unwrap(), Some(tip));
+    }
+
+    #[test]
+    fn rejects_invalid_headers() {
+        let headers = HEADERS.map(header);
+        let limit = Target::from_compact(0x1d00ffff).unwrap();
+
+        // out of order
+        let mut chain = HeaderChain::new(limit);
+        assert!(matches!(chain.verify(&[headers[0], headers[2]]), Err(Error::InvalidHeader { index: 1, .. })));
+        assert_eq!(chain.tip(), Some(BlockHeader::parse(&headers[1]).prev_hash));
+
+        // a different nonce no longer meets the target
+        let mut fields = BlockHeader::parse(&headers[0]);
+        fields.nonce += 1;
+        assert!(matches!(HeaderChain::new(limit).push(&fields.to_bytes()), Err(Error::InvalidHeader { index: 0, reason: "hash doesn't meet the target" })));
+
+        // an easier target than the network allows
+        let mut chain = HeaderChain::new(Target::from_compact(0x1c00ffff).unwrap());
+        assert!(matches!(chain.push(&headers[0]), Err(Error::InvalidHeader { index: 0, reason: "target is easier than the proof-of-work limit" })));
+        assert!(chain.is_empty());
+    }
+}
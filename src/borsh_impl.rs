@@ -0,0 +1,32 @@
+//! borsh serialization for `Hash256`, encoded as its raw 32 bytes (the same as `[u8; 32]`).
+
+use borsh::io::{Read, Result, Write};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::Hash256;
+
+impl BorshSerialize for Hash256 {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(self.as_bytes())
+    }
+}
+
+impl BorshDeserialize for Hash256 {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> Result<Self> {
+        <[u8; 32]>::deserialize_reader(reader).map(Hash256::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encoded_as_raw_bytes() {
+        let hash = Hash256::new(crate::Sha256::new().digest(b"hello"));
+        let encoded = borsh::to_vec(&hash).unwrap();
+        assert_eq!(encoded, hash.as_bytes());
+        assert_eq!(borsh::from_slice::<Hash256>(&encoded).unwrap(), hash);
+        assert!(borsh::from_slice::<Hash256>(&encoded[..31]).is_err());
+    }
+}
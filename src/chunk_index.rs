@@ -0,0 +1,276 @@
+//! An index from chunk digests to where the chunk's data can be found.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{Error, Hash256};
+
+/// Where a chunk's data is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChunkLocation<'a> {
+    /// The file containing the chunk.
+    pub file: &'a str,
+    /// The position of the chunk's first byte in the file.
+    pub offset: u64,
+    /// The length of the chunk in bytes.
+    pub len: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Entry {
+    file: u32,
+    offset: u64,
+    len: u64,
+}
+
+/// Maps the digest of each chunk of data to the first place it was seen, for deduplicating storage or finding reusable blocks.
+///
+/// File names are stored once however many chunks they contain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChunkIndex {
+    files: Vec<String>,
+    // each file's position in `files`, so recording a chunk doesn't scan them
+    names: BTreeMap<String, u32>,
+    chunks: BTreeMap<Hash256, Entry>,
+}
+
+impl ChunkIndex {
+    const MAGIC: &'static [u8; 8] = b"SHA256CI";
+
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self { files: Vec::new(), names: BTreeMap::new(), chunks: BTreeMap::new() }
+    }
+
+    /// Returns the number of distinct chunks in the index.
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Returns whether the index has no chunks.
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// Records where a chunk is stored, unless a chunk with the same digest is already indexed.
+    ///
+    /// # Arguments
+    /// * `hash` - The digest of the chunk.
+    /// * `file` - The file containing the chunk.
+    /// * `offset` - The position of the chunk in the file.
+    /// * `len` - The length of the chunk.
+    ///
+    /// # Returns
+    /// True if the chunk is new, false if it's a duplicate and the existing location was kept.
+    pub fn insert(&mut self, hash: Hash256, file: &str, offset: u64, len: u64) -> bool {
+        if self.chunks.contains_key(&hash) {
+            return false;
+        }
+        let file = match self.names.get(file) {
+            Some(&i) => i,
+            None => {
+                let i = self.files.len() as u32;
+                self.files.push(file.into());
+                self.names.insert(file.into(), i);
+                i
+            }
+        };
+        self.chunks.insert(hash, Entry { file, offset, len });
+        true
+    }
+
+    /// Looks up where a chunk is stored.
+    ///
+    /// # Arguments
+    /// * `hash` - The digest of the chunk.
+    ///
+    /// # Returns
+    /// The chunk's location, or `None` if it isn't indexed.
+    pub fn get(&self, hash: &Hash256) -> Option<ChunkLocation<'_>> {
+        self.chunks.get(hash).map(|entry| self.location(entry))
+    }
+
+    /// Iterates over every chunk in the index, in order of digest.
+    pub fn iter(&self) -> impl Iterator<Item = (&Hash256, ChunkLocation<'_>)> {
+        self.chunks.iter().map(|(hash, entry)| (hash, self.location(entry)))
+    }
+
+    fn location(&self, entry: &Entry) -> ChunkLocation<'_> {
+        ChunkLocation { file: &self.files[entry.file as usize], offset: entry.offset, len: entry.len }
+    }
+
+    /// Encodes the index for storage.
+    ///
+    /// The encoding is `SHA256CI`, the number of files as a big-endian `u32`, each file name as a big-endian `u32` length and UTF-8 bytes,
+    /// then the number of chunks as a big-endian `u64` and each chunk as its 32 byte digest then big-endian `u32` file index, `u64` offset and `u64` length.
+    ///
+    /// # Returns
+    /// The encoded index.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(20 + self.files.iter().map(|file| 4 + file.len()).sum::<usize>() + self.chunks.len() * 52);
+        bytes.extend_from_slice(Self::MAGIC);
+        bytes.extend_from_slice(&(self.files.len() as u32).to_be_bytes());
+        for file in &self.files {
+            bytes.extend_from_slice(&(file.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(file.as_bytes());
+        }
+        bytes.extend_from_slice(&(self.chunks.len() as u64).to_be_bytes());
+        for (hash, entry) in &self.chunks {
+            bytes.extend_from_slice(hash.as_bytes());
+            bytes.extend_from_slice(&entry.file.to_be_bytes());
+            bytes.extend_from_slice(&entry.offset.to_be_bytes());
+            bytes.extend_from_slice(&entry.len.to_be_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes an index encoded with `to_bytes`.
+    ///
+    /// # Arguments
+    /// * `bytes` - The encoded index.
+    ///
+    /// # Returns
+    /// The index, or `Error::Parse` if `bytes` is not a valid encoding.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let mut reader = Reader(bytes);
+        if reader.take(8)? != Self::MAGIC {
+            return Err(Error::Parse { reason: "not a chunk index" });
+        }
+        let file_count = reader.u32()?;
+        let mut files = Vec::new();
+        let mut names = BTreeMap::new();
+        for i in 0..file_count {
+            let len = reader.u32()? as usize;
+            let file = core::str::from_utf8(reader.take(len)?).map_err(|_| Error::Parse { reason: "file name is not UTF-8" })?;
+            files.push(file.into());
+            names.entry(file.into()).or_insert(i);
+        }
+        let chunk_count = reader.u64()?;
+        let mut chunks = BTreeMap::new();
+        for _ in 0..chunk_count {
+            let hash = Hash256::new(reader.take(32)?.try_into().unwrap());
+            let entry = Entry { file: reader.u32()?, offset: reader.u64()?, len: reader.u64()? };
+            if entry.file >= file_count {
+                return Err(Error::Parse { reason: "chunk refers to a file which isn't listed" });
+            }
+            chunks.insert(hash, entry);
+        }
+        if !reader.0.is_empty() {
+            return Err(Error::Parse { reason: "trailing bytes after chunk index" });
+        }
+        Ok(Self { files, names, chunks })
+    }
+
+    /// Splits a stream into fixed size blocks and indexes each one, e.g. for rsync-style block matching.
+    ///
+    /// # Arguments
+    /// * `file` - The name the blocks are recorded under.
+    /// * `reader` - The contents of the file, read from its start.
+    /// * `block_size` - The length of each block, the last block may be shorter. Must not be 0.
+    ///
+    /// # Returns
+    /// The number of blocks which were new to the index, or the first IO error encountered.
+    #[cfg(feature = "std")]
+    pub fn index_blocks<R: std::io::Read>(&mut self, file: &str, mut reader: R, block_size: usize) -> std::io::Result<u64> {
+        assert!(block_size > 0, "block size must be non-zero");
+        let mut block = alloc::vec![0u8; block_size];
+        let (mut offset, mut new) = (0u64, 0u64);
+        loop {
+            // fill the whole block, reads may return less
+            let mut len = 0;
+            while len < block_size {
+                match reader.read(&mut block[len..]) {
+                    Ok(0) => break,
+                    Ok(n) => len += n,
+                    Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            if len == 0 {
+                return Ok(new);
+            }
+            let hash = Hash256::new(crate::Sha256::new().digest(&block[..len]));
+            new += self.insert(hash, file, offset, len as u64) as u64;
+            offset += len as u64;
+        }
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < n {
+            return Err(Error::Parse { reason: "chunk index is truncated" });
+        }
+        let (taken, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+
+    #[test]
+    fn indexes_and_round_trips() {
+        let hash = |data: &[u8]| Hash256::new(Sha256::new().digest(data));
+        let mut index = ChunkIndex::new();
+        assert!(index.insert(hash(b"a"), "one.bin", 0, 1));
+        assert!(index.insert(hash(b"b"), "two.bin", 10, 1));
+        assert!(!index.insert(hash(b"a"), "two.bin", 11, 1));
+        assert_eq!(index.len(), 2);
+        assert_eq!(index.get(&hash(b"a")), Some(ChunkLocation { file: "one.bin", offset: 0, len: 1 }));
+        assert_eq!(index.get(&hash(b"c")), None);
+
+        let bytes = index.to_bytes();
+        assert_eq!(ChunkIndex::from_bytes(&bytes).unwrap(), index);
+        assert!(ChunkIndex::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_file = bytes.clone();
+        // the file index of the last chunk
+        let at = bad_file.len() - 20;
+        bad_file[at..at + 4].copy_from_slice(&7u32.to_be_bytes());
+        assert!(ChunkIndex::from_bytes(&bad_file).is_err());
+    }
+
+    #[test]
+    fn stores_each_file_once() {
+        let mut index = ChunkIndex::new();
+        for i in 0..1000u32 {
+            let file = if i % 2 == 0 { "even" } else { "odd" };
+            index.insert(Hash256::new(Sha256::new().digest(&i.to_be_bytes())), file, i as u64, 4);
+        }
+        assert_eq!(index.files, ["even", "odd"]);
+        assert_eq!(index.get(&Hash256::new(Sha256::new().digest(&7u32.to_be_bytes()))).unwrap().file, "odd");
+        let decoded = ChunkIndex::from_bytes(&index.to_bytes()).unwrap();
+        assert_eq!(decoded, index);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn indexes_blocks() {
+        extern crate std;
+
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i / 4096) as u8).collect();
+        let mut index = ChunkIndex::new();
+        assert_eq!(index.index_blocks("data", &data[..], 4096).unwrap(), 3);
+        assert_eq!(index.get(&Hash256::new(Sha256::new().digest(&data[8192..]))), Some(ChunkLocation { file: "data", offset: 8192, len: 1808 }));
+        // a file sharing a block with one already indexed
+        let mut other = data[..4096].to_vec();
+        other.extend_from_slice(&[9; 100]);
+        assert_eq!(index.index_blocks("other", &other[..], 4096).unwrap(), 1);
+        assert_eq!(index.get(&Hash256::new(Sha256::new().digest(&[9; 100]))), Some(ChunkLocation { file: "other", offset: 4096, len: 100 }));
+        assert_eq!(index.index_blocks("same", std::io::Cursor::new(&data), 4096).unwrap(), 0);
+    }
+}
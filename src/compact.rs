@@ -0,0 +1,95 @@
+//! A compact round function for microcontrollers, used in place of the unrolled one on Cortex-M, AVR and MSP430 (or anywhere with the `compact` feature).
+//!
+//! The desktop code unrolls 8 schedule steps and 8 rounds at a time, which on a Cortex-M0 mostly spills the working variables to the stack
+//! and costs several KB of flash for little gain. Here each loop does one step, so the body is small enough to stay in registers
+//! (the M0 only has 8 low registers for most instructions) and in a small instruction cache, and the choice and majority functions
+//! use the forms with fewer operations, which matters as Thumb can't fold a shift into another instruction the way ARM mode can.
+//!
+//! On 8/16-bit cores every `u32` operation is several instructions, so the functions here are never inlined: `Sha256` calls them
+//! from a handful of places and inlining each copy took `Sha256::digest` alone past 32KB of AVR code.
+
+use crate::primitives::{big_sigma0, big_sigma1, small_sigma0, small_sigma1};
+use crate::{Sha256, K};
+
+/// Whether `Sha256` uses this module instead of its unrolled loops.
+pub(crate) const ENABLED: bool = cfg!(any(feature = "compact", all(target_arch = "arm", target_os = "none"), target_arch = "avr", target_arch = "msp430"));
+
+/// Ch in 3 operations instead of 4, `z ^ (x & (y ^ z))`.
+#[inline(always)]
+fn ch(x: u32, y: u32, z: u32) -> u32 {
+    z ^ (x & (y ^ z))
+}
+
+/// Maj in 4 operations instead of 5, `(x & y) | (z & (x | y))`.
+#[inline(always)]
+fn maj(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (z & (x | y))
+}
+
+/// Copies a 64 byte block into the first 16 words of the message schedule.
+#[inline(never)]
+pub(crate) fn set_block(w: &mut [u32; 64], block: &[u8]) {
+    for (w, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    }
+}
+
+/// Extends the first 16 words of the message schedule to all 64.
+#[inline(never)]
+pub(crate) fn expand_schedule(w: &mut [u32; 64]) {
+    for i in 16..64 {
+        w[i] = w[i - 16].wrapping_add(small_sigma0(w[i - 15])).wrapping_add(w[i - 7]).wrapping_add(small_sigma1(w[i - 2]));
+    }
+}
+
+/// Runs the 64 rounds over the expanded message schedule, adding the result into the hash values.
+///
+/// Takes the whole hasher, not its state as an array, so the call site stays small: the hash values are separate fields and gathering them costs dozens of instructions on 8-bit cores.
+#[inline(never)]
+pub(crate) fn compress_rounds(sha256: &mut Sha256) {
+    let (mut a, mut b, mut c, mut d) = (sha256.h0, sha256.h1, sha256.h2, sha256.h3);
+    let (mut e, mut f, mut g, mut h) = (sha256.h4, sha256.h5, sha256.h6, sha256.h7);
+    for (k, w) in K.iter().zip(&sha256.w) {
+        let temp1 = h.wrapping_add(big_sigma1(e)).wrapping_add(ch(e, f, g)).wrapping_add(*k).wrapping_add(*w);
+        let temp2 = big_sigma0(a).wrapping_add(maj(a, b, c));
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+    sha256.h0 = sha256.h0.wrapping_add(a);
+    sha256.h1 = sha256.h1.wrapping_add(b);
+    sha256.h2 = sha256.h2.wrapping_add(c);
+    sha256.h3 = sha256.h3.wrapping_add(d);
+    sha256.h4 = sha256.h4.wrapping_add(e);
+    sha256.h5 = sha256.h5.wrapping_add(f);
+    sha256.h6 = sha256.h6.wrapping_add(g);
+    sha256.h7 = sha256.h7.wrapping_add(h);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_the_unrolled_rounds() {
+        let x = 0x6a09e667;
+        for (y, z) in [(0, !0), (0xbb67ae85, 0x3c6ef372)] {
+            assert_eq!(ch(x, y, z), crate::primitives::ch(x, y, z));
+            assert_eq!(maj(x, y, z), crate::primitives::maj(x, y, z));
+        }
+        let block: [u8; 64] = core::array::from_fn(|i| (i * 37) as u8);
+        let mut sha256 = Sha256::new();
+        sha256.compress_block(&block);
+        let mut compact = Sha256::new();
+        set_block(&mut compact.w, &block);
+        expand_schedule(&mut compact.w);
+        compress_rounds(&mut compact);
+        assert_eq!(compact.w, sha256.w);
+        assert_eq!(compact.hash(), sha256.hash());
+    }
+}
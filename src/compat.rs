@@ -0,0 +1,44 @@
+//! Drop-in replacement for the `sha2` crate's API.
+//!
+//! Switch implementations by changing
+//! ```ignore
+//! use sha2::{Digest, Sha256};
+//! ```
+//! to
+//! ```
+//! use sha_256::compat::{Digest, Sha256};
+//!
+//! let mut hasher = Sha256::new();
+//! hasher.update(b"hello");
+//! let hash = hasher.finalize();
+//! assert_eq!(format!("{:x}", hash), "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+//! ```
+//! The types are built from the same `digest` crate wrappers as `sha2`'s, so `Sha256::new()`, `update()`, `finalize()`, `Sha256::digest()`,
+//! `hmac::Hmac<Sha256>` etc. all behave identically.
+
+pub use digest::{self, Digest};
+
+use digest::core_api::CoreWrapper;
+
+use crate::Sha256Core;
+
+/// SHA-256 hasher, equivalent to `sha2::Sha256`.
+pub type Sha256 = CoreWrapper<Sha256Core>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_sha2() {
+        let msg = [42u8; 300];
+        for len in [0, 1, 55, 56, 64, 65, 300] {
+            let mut ours = Sha256::new();
+            let mut theirs = sha2::Sha256::new();
+            ours.update(&msg[..len]);
+            theirs.update(&msg[..len]);
+            assert_eq!(ours.finalize(), theirs.finalize());
+            assert_eq!(Sha256::digest(&msg[..len]), sha2::Sha256::digest(&msg[..len]));
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Finding files with identical contents.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use crate::{hash_files, Hash256};
+
+/// Files which all have the same contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// The hash of the contents.
+    pub hash: Hash256,
+    /// The size of each file in bytes.
+    pub size: u64,
+    /// The files, in the order they were given.
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Returns how many bytes would be freed by keeping only one of the files.
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// The outcome of `find_duplicates`.
+#[derive(Debug, Default)]
+pub struct DuplicateReport {
+    /// Every set of two or more identical files, the most wasted space first.
+    pub groups: Vec<DuplicateGroup>,
+    /// The files which couldn't be read, these are left out of `groups`.
+    pub errors: Vec<(PathBuf, io::Error)>,
+}
+
+impl DuplicateReport {
+    /// Returns how many bytes would be freed by keeping only one file from each group.
+    pub fn wasted(&self) -> u64 {
+        self.groups.iter().map(DuplicateGroup::wasted).sum()
+    }
+}
+
+/// Groups files by their contents.
+///
+/// Files are only hashed if another file has the same size, so most files in a typical tree are never read.
+/// A path listed more than once is treated as one file.
+///
+/// # Arguments
+/// * `paths` - The files to compare, directories and other non-files are reported as errors.
+/// * `jobs` - The maximum number of files to hash concurrently, see `hash_files`.
+///
+/// # Returns
+/// The groups of duplicates and the files which couldn't be read.
+pub fn find_duplicates<P: AsRef<Path>>(paths: &[P], jobs: usize) -> DuplicateReport {
+    let mut report = DuplicateReport::default();
+
+    // the position of each path's first occurrence, also used to keep the caller's order within each group
+    let mut order: HashMap<&Path, usize> = HashMap::new();
+    let mut by_size: HashMap<u64, Vec<&Path>> = HashMap::new();
+    for (i, path) in paths.iter().enumerate() {
+        let path = path.as_ref();
+        if order.contains_key(path) {
+            continue;
+        }
+        order.insert(path, i);
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.is_file() => by_size.entry(metadata.len()).or_default().push(path),
+            Ok(_) => report.errors.push((path.into(), io::Error::new(io::ErrorKind::InvalidInput, "not a file"))),
+            Err(e) => report.errors.push((path.into(), e)),
+        }
+    }
+
+    let mut candidates: Vec<(u64, &Path)> = Vec::new();
+    for (size, paths) in by_size {
+        if paths.len() > 1 {
+            candidates.extend(paths.into_iter().map(|path| (size, path)));
+        }
+    }
+    let hashes = hash_files(&candidates.iter().map(|(_, path)| *path).collect::<Vec<_>>(), jobs);
+
+    let mut by_hash: HashMap<(u64, Hash256), Vec<&Path>> = HashMap::new();
+    for ((size, path), hash) in candidates.iter().zip(hashes) {
+        match hash {
+            Ok(hash) => by_hash.entry((*size, hash)).or_default().push(path),
+            Err(e) => report.errors.push((path.into(), e)),
+        }
+    }
+
+    report.groups = by_hash
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|((size, hash), mut paths)| {
+            paths.sort_by_key(|path| order[path]);
+            DuplicateGroup { hash, size, paths: paths.into_iter().map(PathBuf::from).collect() }
+        })
+        .collect();
+    report.groups.sort_unstable_by(|a, b| b.wasted().cmp(&a.wasted()).then(a.hash.cmp(&b.hash)));
+    report.errors.sort_by_key(|(path, _)| order[path.as_path()]);
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+    use std::fs;
+
+    #[test]
+    fn groups_identical_files() {
+        let root = std::env::temp_dir().join(std::format!("sha_256-dedup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        // b and c are the same size as a but only b has the same contents
+        let files = [("a", "hello"), ("b", "hello"), ("c", "world"), ("d", "big file"), ("e", "big file"), ("f", "big file"), ("g", "unique")];
+        for (name, contents) in files {
+            fs::write(root.join(name), contents).unwrap();
+        }
+        let mut paths: Vec<PathBuf> = files.iter().map(|(name, _)| root.join(name)).collect();
+        paths.push(root.join("missing"));
+        paths.push(root.join("a"));
+
+        let report = find_duplicates(&paths, 2);
+        let hash = |contents: &str| Hash256::new(Sha256::new().digest(contents.as_bytes()));
+        assert_eq!(
+            report.groups,
+            [
+                DuplicateGroup { hash: hash("big file"), size: 8, paths: std::vec![root.join("d"), root.join("e"), root.join("f")] },
+                DuplicateGroup { hash: hash("hello"), size: 5, paths: std::vec![root.join("a"), root.join("b")] },
+            ]
+        );
+        assert_eq!(report.wasted(), 16 + 5);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, root.join("missing"));
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,34 @@
+//! diesel `ToSql`/`FromSql` for `Hash256`, mapping digests to `Binary` (`BYTEA`/`BLOB`) columns as their raw 32 bytes.
+//!
+//! `AsExpression` and `FromSqlRow` are derived on `Hash256` itself, so digests can be used directly in queries and `Queryable` structs.
+
+use std::vec::Vec;
+
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Binary;
+
+use crate::Hash256;
+
+impl<DB: Backend> ToSql<Binary, DB> for Hash256
+where
+    [u8]: ToSql<Binary, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        <[u8] as ToSql<Binary, DB>>::to_sql(&self.as_bytes()[..], out)
+    }
+}
+
+impl<DB: Backend> FromSql<Binary, DB> for Hash256
+where
+    Vec<u8>: FromSql<Binary, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let bytes = <Vec<u8> as FromSql<Binary, DB>>::from_sql(bytes)?;
+        let len = bytes.len();
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| std::format!("expected a 32 byte SHA-256 digest, got {} bytes", len))?;
+        Ok(Hash256::new(bytes))
+    }
+}
+
@@ -0,0 +1,292 @@
+//! Implementations of the RustCrypto `digest` traits, so `Sha256` can be used in generic code parameterised over `D: Digest`.
+//! Enabled by the `digest` feature, or its alias `digest-traits`.
+//!
+//! `hmac::SimpleHmac<sha_256::Sha256>` works directly. `hmac::Hmac` needs a block level core (its `CoreProxy` bound is sealed to the `digest` crate's `CoreWrapper`),
+//! so use `hmac::Hmac<CoreWrapper<sha_256::Sha256Core>>` for it.
+
+use core::fmt;
+
+use digest::block_buffer::Eager;
+use digest::core_api::{
+    AlgorithmName, Block, BlockSizeUser, Buffer, BufferKindUser, FixedOutputCore, OutputSizeUser, UpdateCore,
+};
+use digest::typenum::{U128, U28, U32, U48, U64};
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, Reset, Update};
+
+use crate::{Sha224, Sha256, Sha384, Sha512, Sha512_224, Sha512_256};
+
+impl HashMarker for Sha256 {}
+
+impl BlockSizeUser for Sha256 {
+    type BlockSize = U64;
+}
+
+impl OutputSizeUser for Sha256 {
+    type OutputSize = U32;
+}
+
+impl Update for Sha256 {
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        Sha256::update(self, data);
+    }
+}
+
+impl FixedOutput for Sha256 {
+    #[inline]
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&Sha256::finalize(self));
+    }
+}
+
+impl FixedOutputReset for Sha256 {
+    #[inline]
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize_reset());
+    }
+}
+
+impl Reset for Sha256 {
+    #[inline]
+    fn reset(&mut self) {
+        Sha256::reset(self);
+    }
+}
+
+impl HashMarker for Sha224 {}
+
+impl BlockSizeUser for Sha224 {
+    type BlockSize = U64;
+}
+
+impl OutputSizeUser for Sha224 {
+    type OutputSize = U28;
+}
+
+impl Update for Sha224 {
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        Sha224::update(self, data);
+    }
+}
+
+impl FixedOutput for Sha224 {
+    #[inline]
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&Sha224::finalize(self));
+    }
+}
+
+impl FixedOutputReset for Sha224 {
+    #[inline]
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize_reset());
+    }
+}
+
+impl Reset for Sha224 {
+    #[inline]
+    fn reset(&mut self) {
+        Sha224::reset(self);
+    }
+}
+
+impl HashMarker for Sha512 {}
+
+impl BlockSizeUser for Sha512 {
+    type BlockSize = U128;
+}
+
+impl OutputSizeUser for Sha512 {
+    type OutputSize = U64;
+}
+
+impl Update for Sha512 {
+    #[inline]
+    fn update(&mut self, data: &[u8]) {
+        Sha512::update(self, data);
+    }
+}
+
+impl FixedOutput for Sha512 {
+    #[inline]
+    fn finalize_into(self, out: &mut Output<Self>) {
+        out.copy_from_slice(&Sha512::finalize(self));
+    }
+}
+
+impl FixedOutputReset for Sha512 {
+    #[inline]
+    fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+        out.copy_from_slice(&self.finalize_reset());
+    }
+}
+
+impl Reset for Sha512 {
+    #[inline]
+    fn reset(&mut self) {
+        Sha512::reset(self);
+    }
+}
+
+// the truncated SHA-512 variants only differ in their output size
+macro_rules! impl_digest_truncated_sha512 {
+    ($($t:ty => $output_size:ty),*) => {
+        $(
+            impl HashMarker for $t {}
+
+            impl BlockSizeUser for $t {
+                type BlockSize = U128;
+            }
+
+            impl OutputSizeUser for $t {
+                type OutputSize = $output_size;
+            }
+
+            impl Update for $t {
+                #[inline]
+                fn update(&mut self, data: &[u8]) {
+                    <$t>::update(self, data);
+                }
+            }
+
+            impl FixedOutput for $t {
+                #[inline]
+                fn finalize_into(self, out: &mut Output<Self>) {
+                    out.copy_from_slice(&<$t>::finalize(self));
+                }
+            }
+
+            impl FixedOutputReset for $t {
+                #[inline]
+                fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                    out.copy_from_slice(&self.finalize_reset());
+                }
+            }
+
+            impl Reset for $t {
+                #[inline]
+                fn reset(&mut self) {
+                    <$t>::reset(self);
+                }
+            }
+        )*
+    };
+}
+
+impl_digest_truncated_sha512!(Sha384 => U48, Sha512_256 => U32, Sha512_224 => U28);
+
+/// The block level SHA-256 core, buffering is left to the `digest` crate's `BlockBuffer`.
+///
+/// Wrap it in `digest::core_api::CoreWrapper` for constructions which need access to the core, e.g. `hmac::Hmac`.
+#[derive(Clone, Default)]
+pub struct Sha256Core {
+    state: Sha256,
+    block_len: u64, // how many blocks have been processed
+}
+
+impl HashMarker for Sha256Core {}
+
+impl BlockSizeUser for Sha256Core {
+    type BlockSize = U64;
+}
+
+impl BufferKindUser for Sha256Core {
+    type BufferKind = Eager;
+}
+
+impl OutputSizeUser for Sha256Core {
+    type OutputSize = U32;
+}
+
+impl UpdateCore for Sha256Core {
+    #[inline]
+    fn update_blocks(&mut self, blocks: &[Block<Self>]) {
+        self.block_len = self.block_len.wrapping_add(blocks.len() as u64);
+        for block in blocks {
+            self.state.compress_block(block);
+        }
+    }
+}
+
+impl FixedOutputCore for Sha256Core {
+    #[inline]
+    fn finalize_fixed_core(&mut self, buffer: &mut Buffer<Self>, out: &mut Output<Self>) {
+        let bit_len = 8 * (buffer.get_pos() as u64 + 64 * self.block_len);
+        let state = &mut self.state;
+        buffer.len64_padding_be(bit_len, |block| state.compress_block(block));
+        out.copy_from_slice(&state.hash());
+    }
+}
+
+impl Reset for Sha256Core {
+    #[inline]
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+impl AlgorithmName for Sha256Core {
+    fn write_alg_name(f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sha256")
+    }
+}
+
+impl fmt::Debug for Sha256Core {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Sha256Core { ... }")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{hmac_sha256, Sha224, Sha256, Sha256Core, Sha384, Sha512, Sha512_224, Sha512_256};
+    use digest::core_api::CoreWrapper;
+    use digest::Digest;
+    use hmac::Mac;
+
+    #[test]
+    fn digest_trait_matches_inherent() {
+        let hash = <Sha256 as Digest>::digest(b"hello");
+        assert_eq!(hash.as_slice(), &Sha256::new().digest(b"hello"));
+
+        let mut hasher = <Sha256 as Digest>::new();
+        Digest::update(&mut hasher, b"hel");
+        Digest::update(&mut hasher, b"lo");
+        assert_eq!(hasher.finalize_reset().as_slice(), &Sha256::new().digest(b"hello"));
+        assert_eq!(Digest::finalize(hasher).as_slice(), &Sha256::new().digest(b""));
+    }
+
+    #[test]
+    fn works_in_generic_code() {
+        fn generic<D: Digest + digest::FixedOutputReset + digest::Reset>(msg: &[u8]) -> digest::Output<D> {
+            let mut hasher = D::new();
+            Digest::update(&mut hasher, b"discarded");
+            digest::Reset::reset(&mut hasher);
+            digest::Update::update(&mut hasher, msg);
+            digest::FixedOutputReset::finalize_fixed_reset(&mut hasher)
+        }
+        assert_eq!(generic::<Sha256>(b"hello"), generic::<sha2::Sha256>(b"hello"));
+        assert_eq!(generic::<Sha224>(b"hello"), generic::<sha2::Sha224>(b"hello"));
+        assert_eq!(generic::<Sha384>(b"hello"), generic::<sha2::Sha384>(b"hello"));
+        assert_eq!(generic::<Sha512>(b"hello"), generic::<sha2::Sha512>(b"hello"));
+        assert_eq!(generic::<Sha512_256>(b"hello"), generic::<sha2::Sha512_256>(b"hello"));
+        assert_eq!(generic::<Sha512_224>(b"hello"), generic::<sha2::Sha512_224>(b"hello"));
+    }
+
+    #[test]
+    fn works_with_hmac_crate() {
+        for len in [0, 1, 55, 56, 63, 64, 65, 200] {
+            let msg = [7u8; 200];
+            let expected = hmac_sha256(b"key", &msg[..len]);
+
+            let mut mac = hmac::SimpleHmac::<Sha256>::new_from_slice(b"key").unwrap();
+            mac.update(&msg[..len]);
+            assert_eq!(mac.finalize().into_bytes().as_slice(), &expected);
+
+            let mut mac = hmac::Hmac::<CoreWrapper<Sha256Core>>::new_from_slice(b"key").unwrap();
+            mac.update(&msg[..len]);
+            assert_eq!(mac.finalize().into_bytes().as_slice(), &expected);
+        }
+    }
+}
@@ -0,0 +1,562 @@
+//! Deterministic hashing of directory trees.
+//!
+//! `hash_directory` walks the tree and sorts every entry by its path relative to the root,
+//! with components joined by `/` on every platform and compared bytewise. Then, for each entry in that order:
+//! * `absorb_field` of `b"dir"`, `b"file"`, `b"symlink"` or `b"stream"`,
+//! * `absorb_field` of the relative path,
+//! * if modes are included, `absorb_field` of the permission bits as a big-endian `u32`,
+//! * for files and streams, `absorb_field` of the SHA-256 of the contents, for symlinks, `absorb_field` of the link target.
+//!
+//! The root itself is not included, so identical trees hash the same wherever they are. Empty directories are included.
+//! Paths which aren't valid Unicode are hashed as their raw bytes on Unix, elsewhere invalid sequences are replaced with U+FFFD.
+//!
+//! What else is walked is set by `DirHashOptions`, and is the same on every platform unless noted:
+//! * Symlinks are skipped, hashed as their target path, or followed (`SymlinkPolicy`). Followed links are hashed as whatever
+//!   they point to under the link's own path, so a tree hashes the same as a copy with the links replaced by their targets.
+//!   A broken link or a link to a directory containing it is an error. On Windows, junctions count as symlinks.
+//! * Hidden entries are included unless turned off. A name starting with `.` is hidden everywhere, on Windows so is
+//!   anything with the hidden attribute. A hidden directory is skipped along with everything in it.
+//! * Special files, i.e. FIFOs, sockets and device nodes, are always skipped, as reading them could block forever
+//!   (a pipe with no writer) or never end (`/dev/zero`). This includes followed links to them.
+//! * Alternate data streams are skipped unless turned on, and only exist on Windows (NTFS). Each named stream of a file
+//!   is an entry of its own, with the path `<file path>:<stream name>`. Streams of directories are never included.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::string::String;
+use std::vec::Vec;
+
+use crate::{hash_files, Hash256, ManifestEntry, Sha256};
+
+/// What to do with symlinks found while walking a tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Leave them out, as if they weren't there.
+    #[default]
+    Skip,
+    /// Hash the path each link points to, without reading what it points to.
+    RecordTarget,
+    /// Hash what each link points to, walking into linked directories.
+    Follow,
+}
+
+/// Controls what `hash_directory` includes besides the names and contents of files.
+#[derive(Clone, Copy, Debug)]
+pub struct DirHashOptions {
+    modes: bool,
+    symlinks: SymlinkPolicy,
+    hidden: bool,
+    streams: bool,
+    jobs: usize,
+}
+
+impl Default for DirHashOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DirHashOptions {
+    /// Hashes names and contents only, includes hidden entries, skips symlinks and alternate data streams and reads one file at a time.
+    pub fn new() -> Self {
+        Self { modes: false, symlinks: SymlinkPolicy::Skip, hidden: true, streams: false, jobs: 1 }
+    }
+
+    /// Whether permission bits are hashed, so e.g. making a script executable changes the digest.
+    ///
+    /// On Unix these are the lower 12 bits of the mode. Elsewhere only the read only flag is available, so a file or directory is 0o444 if read only and 0o644 otherwise.
+    ///
+    /// # Arguments
+    /// * `include` - True to hash modes, false (the default) to ignore them.
+    pub fn include_modes(mut self, include: bool) -> Self {
+        self.modes = include;
+        self
+    }
+
+    /// Whether symlinks are hashed as their target path, rather than skipped. Short for `symlinks(SymlinkPolicy::RecordTarget)`.
+    ///
+    /// # Arguments
+    /// * `include` - True to hash symlinks, false (the default) to skip them.
+    pub fn include_symlink_targets(mut self, include: bool) -> Self {
+        self.symlinks = if include { SymlinkPolicy::RecordTarget } else { SymlinkPolicy::Skip };
+        self
+    }
+
+    /// How symlinks are treated, see the `dir` module docs.
+    ///
+    /// # Arguments
+    /// * `policy` - Skip (the default), record their targets, or follow them.
+    pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+        self.symlinks = policy;
+        self
+    }
+
+    /// Whether hidden files and directories are included. Names starting with `.` are hidden on every platform, plus anything with the hidden attribute on Windows.
+    ///
+    /// # Arguments
+    /// * `include` - True (the default) to include them, false to skip them and everything in hidden directories.
+    pub fn include_hidden(mut self, include: bool) -> Self {
+        self.hidden = include;
+        self
+    }
+
+    /// Whether the named alternate data streams of files are hashed as entries of their own. Only NTFS has them, so this does nothing on other platforms.
+    ///
+    /// Off by default, so a tree hashes the same on Windows as a copy on a filesystem without streams.
+    ///
+    /// # Arguments
+    /// * `include` - True to hash streams, false (the default) to ignore them.
+    pub fn include_alternate_streams(mut self, include: bool) -> Self {
+        self.streams = include;
+        self
+    }
+
+    /// How many files are read concurrently, see `hash_files`. The digest does not depend on this.
+    ///
+    /// # Arguments
+    /// * `jobs` - The maximum number of files to hash at once, 0 for one per core.
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs;
+        self
+    }
+}
+
+/// The type of a walked entry.
+pub(crate) enum EntryKind {
+    Dir,
+    File,
+    Symlink(PathBuf),
+    /// An alternate data stream of a file, opened by appending `:<name>` to the file's path.
+    Stream,
+}
+
+/// An entry found while walking a tree.
+pub(crate) struct WalkEntry {
+    /// The path to open the entry with.
+    pub(crate) path: PathBuf,
+    /// The path relative to the root, `/` separated.
+    pub(crate) relative: Vec<u8>,
+    pub(crate) kind: EntryKind,
+    pub(crate) metadata: Metadata,
+}
+
+/// Lists every entry below `root` that `options` asks for, sorted by relative path.
+pub(crate) fn walk(root: &Path, options: &DirHashOptions) -> io::Result<Vec<WalkEntry>> {
+    let mut entries = Vec::new();
+    let follow = options.symlinks == SymlinkPolicy::Follow;
+    // the real paths of each queued directory and those above it, so following a link back up the tree is caught rather than looping forever
+    let ancestors = if follow { std::vec![fs::canonicalize(root)?] } else { Vec::new() };
+    let mut dirs = std::vec![(root.to_path_buf(), Vec::new(), ancestors)];
+    while let Some((dir, relative, ancestors)) = dirs.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let mut metadata = fs::symlink_metadata(&path)?;
+            if !options.hidden && is_hidden(&entry.file_name(), &metadata) {
+                continue;
+            }
+            let mut child = relative.clone();
+            if !child.is_empty() {
+                child.push(b'/');
+            }
+            child.extend_from_slice(&name_bytes(&entry.file_name()));
+            if metadata.is_symlink() {
+                match options.symlinks {
+                    SymlinkPolicy::Skip => continue,
+                    SymlinkPolicy::RecordTarget => {
+                        let kind = EntryKind::Symlink(fs::read_link(&path)?);
+                        entries.push(WalkEntry { path, relative: child, kind, metadata });
+                        continue;
+                    }
+                    // from here on the link is treated as whatever it points to
+                    SymlinkPolicy::Follow => metadata = fs::metadata(&path)?,
+                }
+            }
+            let kind = if metadata.is_dir() {
+                let mut below = Vec::new();
+                if follow {
+                    let real = fs::canonicalize(&path)?;
+                    if ancestors.contains(&real) {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, std::format!("symlink loop at {}", path.display())));
+                    }
+                    below = ancestors.clone();
+                    below.push(real);
+                }
+                dirs.push((path.clone(), child.clone(), below));
+                EntryKind::Dir
+            } else if !metadata.is_file() {
+                // a FIFO, socket or device, see the module docs
+                continue;
+            } else {
+                if options.streams {
+                    for name in streams::list(&path)? {
+                        let mut stream_path = path.clone().into_os_string();
+                        stream_path.push(":");
+                        stream_path.push(&name);
+                        let mut stream_relative = child.clone();
+                        stream_relative.push(b':');
+                        stream_relative.extend_from_slice(name.as_bytes());
+                        entries.push(WalkEntry { path: stream_path.into(), relative: stream_relative, kind: EntryKind::Stream, metadata: metadata.clone() });
+                    }
+                }
+                EntryKind::File
+            };
+            entries.push(WalkEntry { path, relative: child, kind, metadata });
+        }
+    }
+    entries.sort_unstable_by(|a, b| a.relative.cmp(&b.relative));
+    Ok(entries)
+}
+
+#[cfg(windows)]
+fn is_hidden(name: &OsStr, metadata: &Metadata) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    name_bytes(name).starts_with(b".") || metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0
+}
+
+#[cfg(not(windows))]
+fn is_hidden(name: &OsStr, _: &Metadata) -> bool {
+    name_bytes(name).starts_with(b".")
+}
+
+#[cfg(windows)]
+mod streams {
+    use core::ffi::c_void;
+    use std::io;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::Path;
+    use std::string::String;
+    use std::vec::Vec;
+
+    const INVALID_HANDLE_VALUE: *mut c_void = -1isize as *mut c_void;
+    const ERROR_HANDLE_EOF: i32 = 38;
+
+    /// `WIN32_FIND_STREAM_DATA`, the name is at most `MAX_PATH + 36` characters.
+    #[repr(C)]
+    struct FindStreamData {
+        size: i64,
+        name: [u16; 296],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn FindFirstStreamW(path: *const u16, level: i32, data: *mut FindStreamData, flags: u32) -> *mut c_void;
+        fn FindNextStreamW(find: *mut c_void, data: *mut FindStreamData) -> i32;
+        fn FindClose(find: *mut c_void) -> i32;
+    }
+
+    /// Returns the names of a file's alternate data streams, without the `:` and `:$DATA` around them.
+    pub(super) fn list(path: &Path) -> io::Result<Vec<String>> {
+        let wide: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+        let mut data = FindStreamData { size: 0, name: [0; 296] };
+        // SAFETY: the path is nul terminated and data has the layout of WIN32_FIND_STREAM_DATA (level 0 is FindStreamInfoStandard)
+        let find = unsafe { FindFirstStreamW(wide.as_ptr(), 0, &mut data, 0) };
+        if find == INVALID_HANDLE_VALUE {
+            let error = io::Error::last_os_error();
+            // no streams at all
+            return if error.raw_os_error() == Some(ERROR_HANDLE_EOF) { Ok(Vec::new()) } else { Err(error) };
+        }
+        let mut names = Vec::new();
+        let error = loop {
+            let len = data.name.iter().position(|&c| c == 0).unwrap_or(data.name.len());
+            let name = String::from_utf16_lossy(&data.name[..len]);
+            // the unnamed "::$DATA" stream is the file's contents
+            if let Some(name) = name.strip_prefix(':').and_then(|name| name.strip_suffix(":$DATA")).filter(|name| !name.is_empty()) {
+                names.push(name.into());
+            }
+            // SAFETY: as for FindFirstStreamW, find is open until FindClose below
+            if unsafe { FindNextStreamW(find, &mut data) } == 0 {
+                break io::Error::last_os_error();
+            }
+        };
+        // SAFETY: find is a valid search handle and isn't used again
+        unsafe { FindClose(find) };
+        if error.raw_os_error() == Some(ERROR_HANDLE_EOF) {
+            Ok(names)
+        } else {
+            Err(error)
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod streams {
+    use std::io;
+    use std::path::Path;
+    use std::string::String;
+    use std::vec::Vec;
+
+    /// Only NTFS has alternate data streams.
+    pub(super) fn list(_: &Path) -> io::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(unix)]
+fn name_bytes(name: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    name.as_bytes().to_vec()
+}
+
+#[cfg(not(unix))]
+fn name_bytes(name: &OsStr) -> Vec<u8> {
+    name.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(unix)]
+fn mode(metadata: &Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn mode(metadata: &Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
+fn target_bytes(target: &Path) -> Vec<u8> {
+    let bytes = name_bytes(target.as_os_str());
+    // windows accepts either separator, normalise to the one used for paths
+    if cfg!(windows) {
+        bytes.into_iter().map(|b| if b == b'\\' { b'/' } else { b }).collect()
+    } else {
+        bytes
+    }
+}
+
+/// Computes a single digest of a directory tree, which is the same for identical trees on any machine.
+///
+/// See the `dir` module docs for the exact encoding.
+///
+/// # Arguments
+/// * `path` - The root of the tree.
+/// * `options` - What to include besides names and contents, and what to do with symlinks, hidden entries and alternate data streams.
+///
+/// # Returns
+/// The digest, or the first IO error encountered.
+pub fn hash_directory<P: AsRef<Path>>(path: P, options: DirHashOptions) -> io::Result<Hash256> {
+    let entries = walk(path.as_ref(), &options)?;
+    let files: Vec<&Path> = entries.iter().filter(|entry| matches!(entry.kind, EntryKind::File | EntryKind::Stream)).map(|entry| entry.path.as_path()).collect();
+    let mut hashes = hash_files(&files, options.jobs).into_iter();
+
+    let mut sha256 = Sha256::new();
+    for entry in &entries {
+        let tag: &[u8] = match entry.kind {
+            EntryKind::Dir => b"dir",
+            EntryKind::File => b"file",
+            EntryKind::Symlink(_) => b"symlink",
+            EntryKind::Stream => b"stream",
+        };
+        sha256.absorb_field(tag);
+        sha256.absorb_field(&entry.relative);
+        if options.modes {
+            sha256.absorb_field(&mode(&entry.metadata).to_be_bytes());
+        }
+        match &entry.kind {
+            EntryKind::Dir => {}
+            EntryKind::File | EntryKind::Stream => sha256.absorb_field(hashes.next().unwrap()?.as_bytes()),
+            EntryKind::Symlink(target) => sha256.absorb_field(&target_bytes(target)),
+        }
+    }
+    Ok(Hash256::new(sha256.finalize()))
+}
+
+/// Lists every file in a directory tree with its hash, size and modification time, e.g. for `write_manifest`.
+///
+/// Files listed in `previous` which are unchanged according to `ManifestEntry::is_unchanged` keep their hash without being read,
+/// so regenerating a manifest of a large tree only reads the files which are new or modified. Pass an empty `previous` for a full rescan.
+///
+/// # Arguments
+/// * `path` - The root of the tree.
+/// * `options` - Which entries are walked and how many files are read at once. Modes are not stored in manifests, and symlinks and
+///   alternate data streams are never listed, though files reached by following symlinks are.
+/// * `previous` - The manifest from the last scan of the tree, or `&[]`.
+///
+/// # Returns
+/// The entries with paths relative to `path` (`/` separated), sorted by path, or the first IO error encountered.
+pub fn manifest_directory<P: AsRef<Path>>(path: P, options: DirHashOptions, previous: &[ManifestEntry]) -> io::Result<Vec<ManifestEntry>> {
+    let previous: HashMap<&str, &ManifestEntry> = previous.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+    let mut manifest = Vec::new();
+    let mut stale = Vec::new();
+    for entry in walk(path.as_ref(), &options)? {
+        // only files are listed, checkers read through links so a link's target path would never match, and a stream's size and mtime are its file's
+        if !matches!(entry.kind, EntryKind::File) {
+            continue;
+        }
+        let path = String::from_utf8_lossy(&entry.relative).into_owned();
+        let hash = match previous.get(path.as_str()).filter(|old| old.is_unchanged(&entry.metadata)) {
+            Some(old) => old.hash,
+            None => {
+                stale.push((manifest.len(), entry.path));
+                Hash256::new([0; 32])
+            }
+        };
+        manifest.push(ManifestEntry::with_metadata(path, hash, &entry.metadata));
+    }
+
+    let paths: Vec<&Path> = stale.iter().map(|(_, path)| path.as_path()).collect();
+    for ((i, _), hash) in stale.iter().zip(hash_files(&paths, options.jobs)) {
+        manifest[*i].hash = hash?;
+    }
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a fresh tree in the temp dir.
+    fn tree(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(std::format!("sha_256-dir-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("sub/empty")).unwrap();
+        fs::write(root.join("a.txt"), "hello").unwrap();
+        fs::write(root.join("sub/b.txt"), "world").unwrap();
+        root
+    }
+
+    #[test]
+    fn hashes_the_documented_encoding() {
+        let root = tree("encoding");
+        let mut expected = Sha256::new();
+        for (tag, path, contents) in [("file", "a.txt", Some("hello")), ("dir", "sub", None), ("file", "sub/b.txt", Some("world")), ("dir", "sub/empty", None)] {
+            expected.absorb_field(tag.as_bytes());
+            expected.absorb_field(path.as_bytes());
+            if let Some(contents) = contents {
+                expected.absorb_field(&Sha256::new().digest(contents.as_bytes()));
+            }
+        }
+        let hash = hash_directory(&root, DirHashOptions::new()).unwrap();
+        assert_eq!(hash, Hash256::new(expected.finalize()));
+        assert_eq!(hash_directory(&root, DirHashOptions::new().jobs(0)).unwrap(), hash);
+
+        // the location of the tree doesn't matter, its contents do
+        let other = tree("encoding-other");
+        assert_eq!(hash_directory(&other, DirHashOptions::new()).unwrap(), hash);
+        fs::create_dir(other.join("sub/empty/deeper")).unwrap();
+        assert_ne!(hash_directory(&other, DirHashOptions::new()).unwrap(), hash);
+
+        fs::remove_dir_all(root).unwrap();
+        fs::remove_dir_all(other).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn includes_modes_and_symlinks_on_request() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = tree("options");
+        let plain = hash_directory(&root, DirHashOptions::new()).unwrap();
+        let modes = hash_directory(&root, DirHashOptions::new().include_modes(true)).unwrap();
+        assert_ne!(plain, modes);
+        fs::set_permissions(root.join("a.txt"), fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(hash_directory(&root, DirHashOptions::new()).unwrap(), plain);
+        assert_ne!(hash_directory(&root, DirHashOptions::new().include_modes(true)).unwrap(), modes);
+
+        std::os::unix::fs::symlink("sub/b.txt", root.join("link")).unwrap();
+        assert_eq!(hash_directory(&root, DirHashOptions::new()).unwrap(), plain);
+        let with_link = hash_directory(&root, DirHashOptions::new().include_symlink_targets(true)).unwrap();
+        assert_ne!(with_link, plain);
+        fs::remove_file(root.join("link")).unwrap();
+        std::os::unix::fs::symlink("a.txt", root.join("link")).unwrap();
+        assert_ne!(hash_directory(&root, DirHashOptions::new().include_symlink_targets(true)).unwrap(), with_link);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn follows_symlinks_and_skips_hidden_entries() {
+        let root = tree("follow");
+        let plain = hash_directory(&root, DirHashOptions::new()).unwrap();
+
+        // hidden entries count by default, and a hidden directory is skipped with its contents
+        fs::write(root.join(".env"), "secret").unwrap();
+        fs::create_dir(root.join(".git")).unwrap();
+        fs::write(root.join(".git/HEAD"), "ref").unwrap();
+        assert_ne!(hash_directory(&root, DirHashOptions::new()).unwrap(), plain);
+        assert_eq!(hash_directory(&root, DirHashOptions::new().include_hidden(false)).unwrap(), plain);
+        fs::remove_file(root.join(".env")).unwrap();
+        fs::remove_dir_all(root.join(".git")).unwrap();
+
+        // a followed link hashes the same as a copy of what it points to
+        let copy = tree("follow-copy");
+        fs::write(copy.join("c.txt"), "hello").unwrap();
+        fs::create_dir_all(copy.join("d/empty")).unwrap();
+        fs::write(copy.join("d/b.txt"), "world").unwrap();
+        std::os::unix::fs::symlink("a.txt", root.join("c.txt")).unwrap();
+        std::os::unix::fs::symlink(root.join("sub"), root.join("d")).unwrap();
+        let follow = DirHashOptions::new().symlinks(SymlinkPolicy::Follow);
+        assert_eq!(hash_directory(&root, follow).unwrap(), hash_directory(&copy, DirHashOptions::new()).unwrap());
+        assert_eq!(hash_directory(&root, DirHashOptions::new()).unwrap(), plain);
+        let paths: Vec<String> = manifest_directory(&root, follow, &[]).unwrap().into_iter().map(|entry| entry.path).collect();
+        assert_eq!(paths, ["a.txt", "c.txt", "d/b.txt", "sub/b.txt"]);
+
+        // a link back up the tree, and a broken link
+        std::os::unix::fs::symlink("..", root.join("sub/up")).unwrap();
+        assert_eq!(hash_directory(&root, follow).unwrap_err().kind(), io::ErrorKind::InvalidData);
+        fs::remove_file(root.join("sub/up")).unwrap();
+        std::os::unix::fs::symlink("missing", root.join("broken")).unwrap();
+        assert_eq!(hash_directory(&root, follow).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+        fs::remove_dir_all(root).unwrap();
+        fs::remove_dir_all(copy).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn skips_special_files() {
+        let root = tree("special");
+        let plain = hash_directory(&root, DirHashOptions::new()).unwrap();
+        // a FIFO nothing writes to would block the read forever
+        let _ = std::process::Command::new("mkfifo").arg(root.join("fifo")).status();
+        let _socket = std::os::unix::net::UnixListener::bind(root.join("socket")).unwrap();
+        std::os::unix::fs::symlink("/dev/zero", root.join("zero")).unwrap();
+        assert_eq!(hash_directory(&root, DirHashOptions::new()).unwrap(), plain);
+        assert_eq!(hash_directory(&root, DirHashOptions::new().symlinks(SymlinkPolicy::Follow)).unwrap(), plain);
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn includes_alternate_streams_on_request() {
+        let root = tree("streams");
+        let plain = hash_directory(&root, DirHashOptions::new()).unwrap();
+        // not every filesystem has streams
+        if fs::write(root.join("a.txt:extra"), "more").is_ok() {
+            assert_eq!(hash_directory(&root, DirHashOptions::new()).unwrap(), plain);
+            let entries = walk(&root, &DirHashOptions::new().include_alternate_streams(true)).unwrap();
+            let paths: Vec<&[u8]> = entries.iter().map(|entry| entry.relative.as_slice()).collect();
+            assert_eq!(paths, [&b"a.txt"[..], b"a.txt:extra", b"sub", b"sub/b.txt", b"sub/empty"]);
+            assert_ne!(hash_directory(&root, DirHashOptions::new().include_alternate_streams(true)).unwrap(), plain);
+        }
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn manifest_only_rehashes_changed_files() {
+        let root = tree("manifest");
+        let manifest = manifest_directory(&root, DirHashOptions::new(), &[]).unwrap();
+        let paths: Vec<&str> = manifest.iter().map(|entry| entry.path.as_str()).collect();
+        assert_eq!(paths, ["a.txt", "sub/b.txt"]);
+        assert_eq!(manifest[0].hash, Hash256::new(Sha256::new().digest(b"hello")));
+        assert_eq!(manifest[0].size, Some(5));
+
+        // a stale hash with a matching size and mtime is kept, proving the file wasn't read
+        let mut previous = manifest.clone();
+        previous[0].hash = Hash256::new([1; 32]);
+        previous[1].size = Some(0);
+        let updated = manifest_directory(&root, DirHashOptions::new(), &previous).unwrap();
+        assert_eq!(updated[0].hash, Hash256::new([1; 32]));
+        assert_eq!(updated[1], manifest[1]);
+        fs::remove_dir_all(root).unwrap();
+    }
+}
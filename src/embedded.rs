@@ -0,0 +1,140 @@
+//! `embedded-io` (and `embedded-io-async`) adapters which hash data as it passes through, the firmware counterparts of `HashingReader`/`HashingWriter`.
+
+use embedded_io::{ErrorType, Read, Write};
+
+use crate::{Hash256, Sha256};
+
+/// A reader which hashes every byte read through it.
+pub struct EmbeddedHashingReader<R> {
+    inner: R,
+    sha256: Sha256,
+}
+
+impl<R> EmbeddedHashingReader<R> {
+    /// Wraps a reader.
+    ///
+    /// # Arguments
+    /// * `inner` - The reader to be hashed.
+    pub fn new(inner: R) -> Self {
+        Self { inner, sha256: Sha256::new() }
+    }
+
+    /// Returns a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped reader. Bytes read directly from it are not hashed.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped reader and the hash of the bytes read so far.
+    pub fn finalize(self) -> (R, Hash256) {
+        (self.inner, Hash256::new(self.sha256.finalize()))
+    }
+}
+
+impl<R: ErrorType> ErrorType for EmbeddedHashingReader<R> {
+    type Error = R::Error;
+}
+
+impl<R: Read> Read for EmbeddedHashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.read(buf)?;
+        self.sha256.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<R: embedded_io_async::Read> embedded_io_async::Read for EmbeddedHashingReader<R> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.read(buf).await?;
+        self.sha256.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// A writer which hashes every byte written through it.
+pub struct EmbeddedHashingWriter<W> {
+    inner: W,
+    sha256: Sha256,
+}
+
+impl<W> EmbeddedHashingWriter<W> {
+    /// Wraps a writer.
+    ///
+    /// # Arguments
+    /// * `inner` - The writer to be hashed.
+    pub fn new(inner: W) -> Self {
+        Self { inner, sha256: Sha256::new() }
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped writer. Bytes written directly to it are not hashed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped writer and the hash of the bytes written so far.
+    pub fn finalize(self) -> (W, Hash256) {
+        (self.inner, Hash256::new(self.sha256.finalize()))
+    }
+}
+
+impl<W: ErrorType> ErrorType for EmbeddedHashingWriter<W> {
+    type Error = W::Error;
+}
+
+impl<W: Write> Write for EmbeddedHashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        // only hash what the inner writer accepted, the rest will be passed to write again
+        let n = self.inner.write(buf)?;
+        self.sha256.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(feature = "embedded-io-async")]
+impl<W: embedded_io_async::Write> embedded_io_async::Write for EmbeddedHashingWriter<W> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let n = self.inner.write(buf).await?;
+        self.sha256.update(&buf[..n]);
+        Ok(n)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adapters_hash_what_passes_through() {
+        let msg = [9u8; 100];
+        let expected = Hash256::new(Sha256::new().digest(&msg));
+
+        let mut reader = EmbeddedHashingReader::new(&msg[..]);
+        let mut buf = [0u8; 100];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(reader.finalize().1, expected);
+
+        let mut out = [0u8; 100];
+        let mut writer = EmbeddedHashingWriter::new(&mut out[..]);
+        writer.write_all(&msg).unwrap();
+        assert_eq!(writer.finalize().1, expected);
+        assert_eq!(out, msg);
+    }
+}
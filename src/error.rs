@@ -0,0 +1,89 @@
+//! The error type shared by the fallible APIs of this crate.
+
+use core::fmt;
+
+use crate::Hash256;
+
+/// Why an operation failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Reading or writing failed.
+    #[cfg(feature = "std")]
+    Io(std::io::Error),
+    /// The message is longer than SHA-256 can hash (2^64 - 1 bits), e.g. a saved state claiming more than `Sha256::MAX_LEN` bytes absorbed.
+    LengthOverflow,
+    /// Text could not be parsed, e.g. a hex digest with the wrong length or a non-hex character.
+    Parse {
+        /// What was wrong with the input.
+        reason: &'static str,
+    },
+    /// The data was hashed in full but its hash was not the expected one.
+    Mismatch {
+        /// The hash the data should have had.
+        expected: Hash256,
+        /// The hash of the data actually read.
+        actual: Hash256,
+        /// The number of bytes hashed.
+        bytes_read: u64,
+    },
+    /// The requested hashing backend is not available on this system.
+    BackendUnavailable,
+    /// A known-answer test run by `self_test()` produced the wrong digest.
+    SelfTest {
+        /// The name of the failing vector.
+        name: &'static str,
+        /// The digest the vector should have had.
+        expected: Hash256,
+        /// The digest actually produced.
+        actual: Hash256,
+    },
+    /// A block header failed verification by `HeaderChain`.
+    InvalidHeader {
+        /// The position of the header in the chain, counting from the first header the verifier was given.
+        index: u64,
+        /// What was wrong with it.
+        reason: &'static str,
+    },
+    /// The HMAC trailer of a signed manifest doesn't match its contents, so it was altered or signed with a different key.
+    Unauthenticated,
+    /// The buffer passed to be written into is too short for the output.
+    BufferTooSmall {
+        /// The number of bytes the output needs.
+        needed: usize,
+    },
+}
+
+#[cfg(feature = "std")]
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::LengthOverflow => f.write_str("message is too long for SHA-256"),
+            Error::Parse { reason } => write!(f, "parse error: {}", reason),
+            Error::Mismatch { bytes_read, .. } => write!(f, "hash mismatch after reading {} bytes", bytes_read),
+            Error::BackendUnavailable => f.write_str("hashing backend is not available"),
+            Error::SelfTest { name, .. } => write!(f, "self test failed: {}", name),
+            Error::InvalidHeader { index, reason } => write!(f, "invalid header {}: {}", index, reason),
+            Error::Unauthenticated => f.write_str("manifest HMAC doesn't match"),
+            Error::BufferTooSmall { needed } => write!(f, "output buffer is shorter than {} bytes", needed),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
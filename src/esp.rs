@@ -0,0 +1,186 @@
+//! A `Backend` using the SHA accelerator of Espressif chips (ESP32, ESP32-S3 and the other chips esp-hal supports) through esp-hal.
+//!
+//! Hashing goes through esp-hal's `ShaBackend` work queue, which must be running, e.g.
+//! ```ignore
+//! let mut sha = esp_hal::sha::ShaBackend::new(peripherals.SHA);
+//! let _backend = sha.start();
+//! let hash = sha_256::EspSha256::compute(b"hello");
+//! ```
+//! Each hasher keeps its own context, which esp-hal swaps in and out of the peripheral, so any number can be in progress at once.
+//!
+//! Data passed to `update` goes to the peripheral in one work item however large it is, copied in by the CPU a block at a time.
+//!
+//! On the RISC-V chips (ESP32-C2, C3, C6, H2, ...) `EspSha256Dma` instead feeds large buffers to the peripheral over a GDMA channel,
+//! up to 63 blocks per operation. It owns the peripheral and the channel, so it can't share them with `EspSha256` or other drivers:
+//! ```ignore
+//! let mut sha256 = sha_256::EspSha256Dma::new(peripherals.SHA, peripherals.DMA_CH0);
+//! sha256.update(&firmware_image);
+//! let hash = sha256.finalize_reset();
+//! ```
+
+#[cfg(target_arch = "riscv32")]
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use esp_hal::sha::Sha256Context;
+#[cfg(target_arch = "riscv32")]
+use esp_hal::{
+    dma::{AhbGdmaChannel, AhbGdmaTxChannel, BurstConfig, Channel, DmaDescriptor, DmaPeripheral, InterruptAccess, RegisterAccess, TxRegisterAccess},
+    peripherals::SHA,
+    sha::Sha,
+    Blocking,
+};
+
+use crate::Backend;
+#[cfg(target_arch = "riscv32")]
+use crate::staging::Staging;
+
+/// SHA-256 computed by the SHA accelerator of an Espressif chip.
+pub struct EspSha256 {
+    context: Sha256Context,
+}
+
+impl Backend for EspSha256 {
+    fn new() -> Self {
+        Self { context: Sha256Context::new() }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        // the work item is never cancelled, so it always completes
+        self.context.update(data).wait_blocking();
+    }
+
+    fn finalize(mut self) -> [u8; 32] {
+        let mut hash = [0u8; 32];
+        self.context.finalize(&mut hash).wait_blocking();
+        hash
+    }
+}
+
+impl Default for EspSha256 {
+    fn default() -> Self {
+        Backend::new()
+    }
+}
+
+// the most one descriptor (4095 bytes) and SHA_DMA_BLOCK_NUM (6 bits) can cover
+#[cfg(target_arch = "riscv32")]
+const DMA_BLOCKS: usize = 63;
+
+// the SHA_MODE value for SHA-256, and SHA's peripheral selector on the AHB GDMA
+#[cfg(target_arch = "riscv32")]
+const MODE_SHA256: u8 = 2;
+#[cfg(target_arch = "riscv32")]
+const GDMA_SHA: u8 = 7;
+
+/// SHA-256 computed by the SHA accelerator of an Espressif RISC-V chip, with the message fed to it over DMA.
+///
+/// Data is staged in a 4032 byte buffer inside the hasher, so it may come from flash, and the hasher must live in internal RAM.
+#[cfg(target_arch = "riscv32")]
+pub struct EspSha256Dma<'d> {
+    // keeps the peripheral's clock running; the registers are reached through `SHA::regs`
+    _sha: Sha<'d>,
+    // keeps the GDMA clock running; `Channel` keeps the registers private, so `channel` is a second handle to program them
+    _dma: Channel<Blocking, AhbGdmaChannel<'d>>,
+    channel: AhbGdmaTxChannel<'d>,
+    descriptor: DmaDescriptor,
+    staging: Staging<{ DMA_BLOCKS * 64 }>,
+    started: bool,
+}
+
+#[cfg(target_arch = "riscv32")]
+impl<'d> EspSha256Dma<'d> {
+    /// Takes the SHA peripheral and a GDMA channel to feed it.
+    ///
+    /// # Arguments
+    /// * `sha` - The SHA peripheral.
+    /// * `channel` - Any GDMA channel, e.g. `peripherals.DMA_CH0`.
+    ///
+    /// # Panics
+    /// If the channel can't serve the SHA peripheral, which is the case on chips with an AXI DMA for SHA such as the ESP32-P4.
+    pub fn new(sha: SHA<'d>, channel: impl Into<AhbGdmaChannel<'d>>) -> Self {
+        let channel: AhbGdmaChannel<'d> = channel.into();
+        // SAFETY: the handle only points at the channel's static description and has no `Drop`; `Channel::new`
+        // duplicates it the same way to split it into halves
+        let dma = Channel::new(unsafe { core::ptr::read(&channel) });
+        let channel = AhbGdmaTxChannel::from(channel);
+        channel.runtime_ensure_compatible(DmaPeripheral::SHA);
+        Self {
+            _sha: Sha::new(sha),
+            _dma: dma,
+            channel,
+            descriptor: DmaDescriptor::EMPTY,
+            staging: Staging::new(),
+            started: false,
+        }
+    }
+
+    /// Absorbs more of the message. Every 4032 bytes are hashed in one DMA transfer, which this waits for.
+    ///
+    /// # Arguments
+    /// * `data` - The next part of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        let Self { channel, descriptor, staging, started, .. } = self;
+        staging.update(data, |blocks| Self::transfer(channel, descriptor, started, blocks));
+    }
+
+    /// Pads the message and returns its hash, leaving the hasher ready for the next message.
+    ///
+    /// # Returns
+    /// The 32 byte hash.
+    pub fn finalize_reset(&mut self) -> [u8; 32] {
+        let Self { channel, descriptor, staging, started, .. } = self;
+        staging.finish(|blocks| Self::transfer(channel, descriptor, started, blocks));
+        *started = false;
+        let mut hash = [0u8; 32];
+        for (out, word) in hash.chunks_exact_mut(4).zip(SHA::regs().h_mem_iter()) {
+            // the hash registers hold the digest bytes in memory order
+            out.copy_from_slice(&word.read().bits().to_ne_bytes());
+        }
+        hash
+    }
+
+    /// Hashes a message in one go, leaving the hasher ready for the next message.
+    ///
+    /// # Arguments
+    /// * `msg` - The message.
+    ///
+    /// # Returns
+    /// The 32 byte hash.
+    pub fn digest(&mut self, msg: &[u8]) -> [u8; 32] {
+        self.update(msg);
+        self.finalize_reset()
+    }
+
+    // hashes whole blocks from the staging buffer, continuing from the hash the peripheral holds unless this is the first run
+    fn transfer(channel: &AhbGdmaTxChannel<'d>, descriptor: &mut DmaDescriptor, started: &mut bool, blocks: &[u8]) {
+        *descriptor = DmaDescriptor::EMPTY;
+        descriptor.buffer = blocks.as_ptr() as *mut u8;
+        descriptor.set_size(blocks.len());
+        descriptor.set_length(blocks.len());
+        descriptor.reset_for_tx(true);
+
+        channel.set_burst_mode(BurstConfig::default());
+        channel.set_descr_burst_mode(true);
+        channel.set_check_owner(None);
+        channel.set_auto_write_back(false);
+        compiler_fence(Ordering::SeqCst);
+        channel.clear_all();
+        channel.reset();
+        channel.set_link_addr(descriptor as *const DmaDescriptor as u32);
+        channel.set_peripheral(GDMA_SHA);
+        channel.start();
+
+        let regs = SHA::regs();
+        regs.mode().write(|w| unsafe { w.mode().bits(MODE_SHA256) });
+        regs.dma_block_num().write(|w| unsafe { w.dma_block_num().bits((blocks.len() / 64) as u8) });
+        if *started {
+            regs.dma_continue().write(|w| w.dma_continue().set_bit());
+        } else {
+            regs.dma_start().write(|w| w.dma_start().set_bit());
+            *started = true;
+        }
+        while regs.busy().read().state().bit_is_set() {}
+        compiler_fence(Ordering::SeqCst);
+        channel.stop();
+    }
+}
@@ -0,0 +1,182 @@
+//! C API, see `include/sha_256.h`.
+//!
+//! Contexts are allocated by the caller (on the stack or wherever they like) as opaque, suitably sized and aligned structs.
+//! Build the library with `cargo rustc --release --features ffi --crate-type cdylib` (or `staticlib`).
+
+#![allow(non_camel_case_types)]
+
+use core::mem::{align_of, size_of};
+use core::ptr;
+use core::slice;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use crate::{HmacSha256, Sha256};
+
+/// Opaque storage for a hashing context. Allocate it wherever you like and initialise it with `sha256_init`.
+#[repr(C)]
+pub struct sha256_ctx {
+    opaque: [u64; 64],
+}
+
+/// Opaque storage for a HMAC context. Allocate it wherever you like and initialise it with `hmac_sha256_init`.
+#[repr(C)]
+pub struct hmac_sha256_ctx {
+    opaque: [u64; 128],
+}
+
+// the header promises a fixed size, so fail the build if the Rust types ever outgrow it
+const _: () = assert!(size_of::<Sha256>() <= size_of::<sha256_ctx>());
+const _: () = assert!(align_of::<Sha256>() <= align_of::<sha256_ctx>());
+const _: () = assert!(size_of::<HmacSha256>() <= size_of::<hmac_sha256_ctx>());
+const _: () = assert!(align_of::<HmacSha256>() <= align_of::<hmac_sha256_ctx>());
+
+/// Converts a C buffer into a slice, allowing a null pointer for empty buffers.
+///
+/// # Safety
+/// `data` must be null or valid for reads of `len` bytes.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> &'a [u8] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(data, len)
+    }
+}
+
+/// Overwrites a whole context with `zeroed`, writing it in one go as parts of the context may never have been initialised.
+///
+/// # Safety
+/// `ctx` must be valid for writes.
+unsafe fn wipe<T>(ctx: *mut T, zeroed: T) {
+    ptr::write_volatile(ctx, zeroed);
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Initialises (or re-initialises) a hashing context.
+///
+/// # Safety
+/// `ctx` must be a valid pointer to a `sha256_ctx`.
+#[no_mangle]
+pub unsafe extern "C" fn sha256_init(ctx: *mut sha256_ctx) {
+    ptr::write(ctx as *mut Sha256, Sha256::new());
+}
+
+/// Absorbs `len` bytes from `data` into the context.
+///
+/// # Safety
+/// `ctx` must have been initialised with `sha256_init`. `data` must be valid for reads of `len` bytes, or may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn sha256_update(ctx: *mut sha256_ctx, data: *const u8, len: usize) {
+    (*(ctx as *mut Sha256)).update(bytes(data, len));
+}
+
+/// Writes the 32 byte digest to `out`. The context is wiped and must be initialised again before reuse.
+///
+/// # Safety
+/// `ctx` must have been initialised with `sha256_init`. `out` must be valid for writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sha256_final(ctx: *mut sha256_ctx, out: *mut u8) {
+    let hash = ptr::read(ctx as *const Sha256).finalize();
+    // the context holds buffered message bytes, which shouldn't be left in the caller's memory
+    wipe(ctx, sha256_ctx { opaque: [0; 64] });
+    ptr::copy_nonoverlapping(hash.as_ptr(), out, hash.len());
+}
+
+/// Hashes `len` bytes from `data` in one go, writing the 32 byte digest to `out`.
+///
+/// # Safety
+/// `data` must be valid for reads of `len` bytes, or may be null if `len` is 0. `out` must be valid for writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sha256(data: *const u8, len: usize, out: *mut u8) {
+    let hash = Sha256::new().digest(bytes(data, len));
+    ptr::copy_nonoverlapping(hash.as_ptr(), out, hash.len());
+}
+
+/// Initialises (or re-initialises) a HMAC context with a key.
+///
+/// # Safety
+/// `ctx` must be a valid pointer to a `hmac_sha256_ctx`. `key` must be valid for reads of `key_len` bytes, or may be null if `key_len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn hmac_sha256_init(ctx: *mut hmac_sha256_ctx, key: *const u8, key_len: usize) {
+    ptr::write(ctx as *mut HmacSha256, HmacSha256::new(bytes(key, key_len)));
+}
+
+/// Absorbs `len` bytes from `data` into the HMAC context.
+///
+/// # Safety
+/// `ctx` must have been initialised with `hmac_sha256_init`. `data` must be valid for reads of `len` bytes, or may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn hmac_sha256_update(ctx: *mut hmac_sha256_ctx, data: *const u8, len: usize) {
+    (*(ctx as *mut HmacSha256)).update(bytes(data, len));
+}
+
+/// Writes the 32 byte MAC to `out`. The context is wiped and must be initialised again before reuse.
+///
+/// # Safety
+/// `ctx` must have been initialised with `hmac_sha256_init`. `out` must be valid for writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hmac_sha256_final(ctx: *mut hmac_sha256_ctx, out: *mut u8) {
+    let mac = ptr::read(ctx as *const HmacSha256).finalize();
+    // the context holds the keyed pad states as well as buffered message bytes
+    wipe(ctx, hmac_sha256_ctx { opaque: [0; 128] });
+    ptr::copy_nonoverlapping(mac.as_ptr(), out, mac.len());
+}
+
+/// Computes the HMAC-SHA256 of `len` bytes from `data` in one go, writing the 32 byte MAC to `out`.
+///
+/// # Safety
+/// `key` and `data` must be valid for reads of `key_len` and `len` bytes respectively, or may be null if their length is 0. `out` must be valid for writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn hmac_sha256(key: *const u8, key_len: usize, data: *const u8, len: usize, out: *mut u8) {
+    let mac = crate::hmac_sha256(bytes(key, key_len), bytes(data, len));
+    ptr::copy_nonoverlapping(mac.as_ptr(), out, mac.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::mem::MaybeUninit;
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let msg = b"hello world";
+        let mut expected = [0u8; 32];
+        let mut actual = [0u8; 32];
+        unsafe {
+            sha256(msg.as_ptr(), msg.len(), expected.as_mut_ptr());
+            let mut ctx = MaybeUninit::<sha256_ctx>::uninit();
+            sha256_init(ctx.as_mut_ptr());
+            sha256_update(ctx.as_mut_ptr(), msg.as_ptr(), 5);
+            sha256_update(ctx.as_mut_ptr(), ptr::null(), 0);
+            sha256_update(ctx.as_mut_ptr(), msg[5..].as_ptr(), msg.len() - 5);
+            sha256_final(ctx.as_mut_ptr(), actual.as_mut_ptr());
+            assert_eq!(ctx.assume_init_ref().opaque, [0; 64]);
+        }
+        assert_eq!(actual, expected);
+        assert_eq!(actual, Sha256::new().digest(msg));
+    }
+
+    #[test]
+    fn hmac_streaming_matches_one_shot() {
+        let key = b"key";
+        let msg = b"hello world";
+        let mut expected = [0u8; 32];
+        let mut actual = [0u8; 32];
+        unsafe {
+            hmac_sha256(key.as_ptr(), key.len(), msg.as_ptr(), msg.len(), expected.as_mut_ptr());
+            let mut ctx = MaybeUninit::<hmac_sha256_ctx>::uninit();
+            hmac_sha256_init(ctx.as_mut_ptr(), key.as_ptr(), key.len());
+            hmac_sha256_update(ctx.as_mut_ptr(), msg.as_ptr(), msg.len());
+            hmac_sha256_final(ctx.as_mut_ptr(), actual.as_mut_ptr());
+            assert_eq!(ctx.assume_init_ref().opaque, [0; 128]);
+        }
+        assert_eq!(actual, expected);
+        assert_eq!(actual, crate::hmac_sha256(key, msg));
+    }
+
+    #[test]
+    fn header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/sha_256.h"));
+        let checked_in = include_str!("../include/sha_256.h");
+        assert!(generated == checked_in, "include/sha_256.h is out of date, copy {}/sha_256.h over it", env!("OUT_DIR"));
+    }
+}
@@ -0,0 +1,250 @@
+use core::fmt;
+
+/// A SHA-256 digest.
+///
+/// Digests order by their bytes (the same as ordering their hex strings), so they can be sorted deterministically and used as `BTreeMap`/`HashMap` keys.
+/// They display as 64 lowercase hex characters, `{:X}` gives uppercase and `{:#x}` adds a `0x` prefix.
+///
+/// ```
+/// let hash = sha_256::Hash256::from(sha_256::sha256(b"hello"));
+/// assert_eq!(hash.to_string(), "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+/// assert_eq!(format!("{:.8X}", hash), "2CF24DBA");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "diesel", derive(diesel::expression::AsExpression, diesel::deserialize::FromSqlRow))]
+#[cfg_attr(feature = "diesel", diesel(sql_type = diesel::sql_types::Binary))]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(feature = "rkyv", rkyv(compare(PartialEq), derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)))]
+pub struct Hash256([u8; 32]);
+
+impl Hash256 {
+    /// Wraps the raw bytes of a digest.
+    ///
+    /// # Arguments
+    /// * `bytes` - The 32 bytes of the digest.
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the raw bytes of the digest.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Checks whether the digest is in a set, in constant time.
+    ///
+    /// Every entry of `set` is compared in full, without stopping at the first match, so the time taken does not reveal whether (or where) the digest was found.
+    /// For allow/deny-list checks on digests derived from secrets.
+    ///
+    /// # Arguments
+    /// * `set` - The digests to be searched.
+    ///
+    /// # Returns
+    /// `true` if any entry of `set` equals this digest.
+    pub fn ct_contains(&self, set: &[Hash256]) -> bool {
+        let mut found = 0u8;
+        for candidate in set {
+            let mut diff = 0u8;
+            for (a, b) in self.0.iter().zip(candidate.0.iter()) {
+                diff |= a ^ b;
+            }
+            // 1 if diff is 0, otherwise 0, without branching
+            found |= (((diff as u16).wrapping_sub(1) >> 8) as u8) & 1;
+        }
+        // stop the compiler reasoning about found and exiting the loop early
+        core::hint::black_box(found) == 1
+    }
+
+    /// Compares the digest with a hex string, ignoring case, e.g. against a checksum from a web page.
+    ///
+    /// Not constant time, like `==`. Use `ct_contains` when the digest is derived from a secret.
+    ///
+    /// # Arguments
+    /// * `hex` - The expected digest, 64 hex characters in either case.
+    ///
+    /// # Returns
+    /// `true` if `hex` is the digest, `false` if it differs or isn't 64 characters long.
+    pub fn eq_hex(&self, hex: &str) -> bool {
+        crate::hex::encode(&self.0).eq_ignore_ascii_case(hex.as_bytes())
+    }
+}
+
+// comparisons with raw bytes, so tests and checks needn't wrap literals in Hash256::new. Like the derived PartialEq these aren't constant time
+
+impl PartialEq<[u8; 32]> for Hash256 {
+    fn eq(&self, other: &[u8; 32]) -> bool {
+        &self.0 == other
+    }
+}
+
+impl PartialEq<Hash256> for [u8; 32] {
+    fn eq(&self, other: &Hash256) -> bool {
+        self == &other.0
+    }
+}
+
+impl PartialEq<&[u8]> for Hash256 {
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.0[..] == **other
+    }
+}
+
+impl PartialEq<Hash256> for &[u8] {
+    fn eq(&self, other: &Hash256) -> bool {
+        **self == other.0[..]
+    }
+}
+
+impl From<[u8; 32]> for Hash256 {
+    fn from(bytes: [u8; 32]) -> Self {
+        Hash256(bytes)
+    }
+}
+
+impl From<Hash256> for [u8; 32] {
+    fn from(hash: Hash256) -> Self {
+        hash.0
+    }
+}
+
+impl AsRef<[u8]> for Hash256 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8; 32]> for Hash256 {
+    fn as_ref(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+// the hex forms honour the precision as a number of characters, for abbreviated digests, and width, fill and `#` like the integer impls
+impl fmt::LowerHex for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_hex(f, crate::hex::encode(&self.0))
+    }
+}
+
+impl fmt::UpperHex for Hash256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut hex = crate::hex::encode(&self.0);
+        hex.make_ascii_uppercase();
+        write_hex(f, hex)
+    }
+}
+
+fn write_hex(f: &mut fmt::Formatter<'_>, hex: [u8; 64]) -> fmt::Result {
+    let len = f.precision().unwrap_or(64).min(64);
+    // hex digits are always ASCII
+    f.pad_integral(true, "0x", core::str::from_utf8(&hex[..len]).unwrap())
+}
+
+// `Hash256` derives `Archive`, its archived form is the same 32 bytes with an alignment of 1 so digests can be read in place from a memory mapped archive
+#[cfg(feature = "rkyv")]
+impl ArchivedHash256 {
+    /// Returns the raw bytes of the archived digest.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+#[cfg(feature = "rkyv")]
+impl From<&ArchivedHash256> for Hash256 {
+    fn from(archived: &ArchivedHash256) -> Self {
+        Hash256(archived.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::collections::{BTreeSet, HashSet};
+
+    #[test]
+    fn usable_as_keys() {
+        let mut low = [0u8; 32];
+        low[31] = 0xff;
+        let mut high = [0u8; 32];
+        high[0] = 1;
+        assert!(Hash256::new(low) < Hash256::new(high));
+
+        let sorted: BTreeSet<_> = [Hash256::new(high), Hash256::new(low)].into_iter().collect();
+        assert_eq!(sorted.into_iter().next(), Some(Hash256::new(low)));
+        let set: HashSet<_> = [Hash256::new(low), Hash256::new(low)].into_iter().collect();
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn ct_contains_finds_members() {
+        let set: std::vec::Vec<Hash256> = (0..5u8).map(|i| Hash256::new([i; 32])).collect();
+        assert!(Hash256::new([3; 32]).ct_contains(&set));
+        assert!(Hash256::new([0; 32]).ct_contains(&set));
+        let mut near = [4u8; 32];
+        near[31] = 5;
+        assert!(!Hash256::new(near).ct_contains(&set));
+        assert!(!Hash256::new([0; 32]).ct_contains(&[]));
+    }
+
+    #[test]
+    fn compares_with_bytes_and_hex() {
+        let digest = crate::Sha256::new().digest(b"");
+        let hash = Hash256::new(digest);
+        assert_eq!(hash, digest);
+        assert_eq!(digest, hash);
+        assert_eq!(hash, &digest[..]);
+        assert_eq!(&digest[..], hash);
+        assert_ne!(hash, &digest[..31]);
+        assert_ne!(hash, [0; 32]);
+
+        assert!(hash.eq_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"));
+        assert!(hash.eq_hex("E3B0C44298FC1C149AFBF4C8996FB92427AE41E4649B934CA495991B7852B855"));
+        assert!(!hash.eq_hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b856"));
+        assert!(!hash.eq_hex("e3b0c442"));
+        assert!(!hash.eq_hex(""));
+    }
+
+    #[test]
+    fn formats_as_hex() {
+        use std::format;
+
+        let hash = Hash256::from(crate::Sha256::new().digest(b""));
+        let hex = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+        assert_eq!(format!("{}", hash), hex);
+        assert_eq!(format!("{:x}", hash), hex);
+        assert_eq!(format!("{:X}", hash), hex.to_ascii_uppercase());
+        assert_eq!(format!("{:#x}", hash), format!("0x{}", hex));
+        assert_eq!(format!("{:.7}", hash), "e3b0c44");
+        assert_eq!(format!("{:>10.4}", hash), "      e3b0");
+        assert_eq!(format!("{:.100}", hash), hex);
+
+        let bytes: [u8; 32] = hash.into();
+        assert_eq!(Hash256::from(bytes), hash);
+        assert_eq!(AsRef::<[u8]>::as_ref(&hash), &bytes[..]);
+        assert_eq!(AsRef::<[u8; 32]>::as_ref(&hash), &bytes);
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn archives_in_place() {
+        use rkyv::rancor::Error;
+        use std::vec::Vec;
+
+        let hashes: Vec<Hash256> = (0..4u8).map(|i| Hash256::new(crate::Sha256::new().digest(&[i]))).collect();
+        let bytes = rkyv::to_bytes::<Error>(&hashes).unwrap();
+        let archived = rkyv::access::<rkyv::Archived<Vec<Hash256>>, Error>(&bytes).unwrap();
+        assert_eq!(archived.len(), 4);
+        assert_eq!(archived[2], hashes[2]);
+        assert_eq!(archived[3].as_bytes(), hashes[3].as_bytes());
+        assert_eq!(Hash256::from(&archived[1]), hashes[1]);
+        assert_eq!(rkyv::deserialize::<Vec<Hash256>, Error>(archived).unwrap(), hashes);
+    }
+}
@@ -0,0 +1,278 @@
+//! Canonical encodings for absorbing structured data into a hasher.
+//!
+//! Concatenating the raw bytes of several values is ambiguous, e.g. `("ab", "c")` and `("a", "bc")` hash the same.
+//! Each `Hashable` impl feeds an encoding into the hasher which cannot collide with the encoding of a different value of the same type:
+//! * integers are written as fixed width big-endian bytes. `usize`/`isize` are always widened to 64 bits so the encoding does not depend on the platform.
+//! * `bool` is a single `0` or `1` byte, `char` is its scalar value as a `u32`.
+//! * strings, slices, arrays and collections are prefixed with their length (number of elements) as a big-endian `u64`.
+//! * `Option` is a `0` byte for `None`, or a `1` byte followed by the value for `Some`.
+//! * tuples are the concatenation of their fields.
+//!
+//! `HashMap`/`HashSet` are deliberately not supported as their iteration order is not deterministic. Use `BTreeMap`/`BTreeSet` instead.
+
+use crate::Sha256;
+
+/// A value which can be absorbed into a `Sha256` hasher using a canonical, unambiguous encoding.
+pub trait Hashable {
+    /// Feeds the canonical encoding of this value into the hasher.
+    ///
+    /// # Arguments
+    /// * `sha256` - The hasher to absorb the value into.
+    fn hash_into(&self, sha256: &mut Sha256);
+
+    /// Feeds the elements of a slice into the hasher, without the length prefix.
+    ///
+    /// Types can override this to absorb many elements at once, e.g. `u8` passes the whole slice to `update` rather than one byte at a time.
+    ///
+    /// # Arguments
+    /// * `data` - The elements to be absorbed.
+    /// * `sha256` - The hasher to absorb the elements into.
+    fn hash_slice_into(data: &[Self], sha256: &mut Sha256)
+    where
+        Self: Sized,
+    {
+        for item in data {
+            item.hash_into(sha256);
+        }
+    }
+}
+
+/// Writes the length prefix used for variable length values.
+#[inline(always)]
+fn hash_len(len: usize, sha256: &mut Sha256) {
+    sha256.update(&(len as u64).to_be_bytes());
+}
+
+macro_rules! impl_hashable_int {
+    ($($t:ty),*) => {
+        $(
+            impl Hashable for $t {
+                #[inline]
+                fn hash_into(&self, sha256: &mut Sha256) {
+                    sha256.update(&self.to_be_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_hashable_int!(u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl Hashable for u8 {
+    #[inline]
+    fn hash_into(&self, sha256: &mut Sha256) {
+        sha256.update(&[*self]);
+    }
+
+    #[inline]
+    fn hash_slice_into(data: &[Self], sha256: &mut Sha256) {
+        sha256.update(data);
+    }
+}
+
+impl Hashable for usize {
+    #[inline]
+    fn hash_into(&self, sha256: &mut Sha256) {
+        (*self as u64).hash_into(sha256);
+    }
+}
+
+impl Hashable for isize {
+    #[inline]
+    fn hash_into(&self, sha256: &mut Sha256) {
+        (*self as i64).hash_into(sha256);
+    }
+}
+
+impl Hashable for bool {
+    #[inline]
+    fn hash_into(&self, sha256: &mut Sha256) {
+        (*self as u8).hash_into(sha256);
+    }
+}
+
+impl Hashable for char {
+    #[inline]
+    fn hash_into(&self, sha256: &mut Sha256) {
+        (*self as u32).hash_into(sha256);
+    }
+}
+
+impl Hashable for str {
+    fn hash_into(&self, sha256: &mut Sha256) {
+        hash_len(self.len(), sha256);
+        sha256.update(self.as_bytes());
+    }
+}
+
+impl<T: Hashable> Hashable for [T] {
+    fn hash_into(&self, sha256: &mut Sha256) {
+        hash_len(self.len(), sha256);
+        T::hash_slice_into(self, sha256);
+    }
+}
+
+// arrays are encoded the same as slices so `[T; N]`, `&[T]` and `Vec<T>` agree
+impl<T: Hashable, const N: usize> Hashable for [T; N] {
+    fn hash_into(&self, sha256: &mut Sha256) {
+        self[..].hash_into(sha256);
+    }
+}
+
+impl<T: Hashable> Hashable for Option<T> {
+    fn hash_into(&self, sha256: &mut Sha256) {
+        match self {
+            None => 0u8.hash_into(sha256),
+            Some(value) => {
+                1u8.hash_into(sha256);
+                value.hash_into(sha256);
+            }
+        }
+    }
+}
+
+impl<T: Hashable + ?Sized> Hashable for &T {
+    #[inline]
+    fn hash_into(&self, sha256: &mut Sha256) {
+        (**self).hash_into(sha256);
+    }
+}
+
+impl<T: Hashable + ?Sized> Hashable for &mut T {
+    #[inline]
+    fn hash_into(&self, sha256: &mut Sha256) {
+        (**self).hash_into(sha256);
+    }
+}
+
+impl Hashable for () {
+    #[inline]
+    fn hash_into(&self, _sha256: &mut Sha256) {}
+}
+
+macro_rules! impl_hashable_tuple {
+    ($($name:ident)+) => {
+        impl<$($name: Hashable),+> Hashable for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn hash_into(&self, sha256: &mut Sha256) {
+                let ($($name,)+) = self;
+                $($name.hash_into(sha256);)+
+            }
+        }
+    };
+}
+
+impl_hashable_tuple!(A);
+impl_hashable_tuple!(A B);
+impl_hashable_tuple!(A B C);
+impl_hashable_tuple!(A B C D);
+impl_hashable_tuple!(A B C D E);
+impl_hashable_tuple!(A B C D E F);
+impl_hashable_tuple!(A B C D E F G);
+impl_hashable_tuple!(A B C D E F G H);
+
+#[cfg(feature = "alloc")]
+mod alloc_impls {
+    use super::{hash_len, Hashable};
+    use crate::Sha256;
+    use alloc::boxed::Box;
+    use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    impl Hashable for String {
+        #[inline]
+        fn hash_into(&self, sha256: &mut Sha256) {
+            self.as_str().hash_into(sha256);
+        }
+    }
+
+    impl<T: Hashable> Hashable for Vec<T> {
+        #[inline]
+        fn hash_into(&self, sha256: &mut Sha256) {
+            self.as_slice().hash_into(sha256);
+        }
+    }
+
+    impl<T: Hashable + ?Sized> Hashable for Box<T> {
+        #[inline]
+        fn hash_into(&self, sha256: &mut Sha256) {
+            (**self).hash_into(sha256);
+        }
+    }
+
+    impl<T: Hashable> Hashable for VecDeque<T> {
+        fn hash_into(&self, sha256: &mut Sha256) {
+            hash_len(self.len(), sha256);
+            let (front, back) = self.as_slices();
+            T::hash_slice_into(front, sha256);
+            T::hash_slice_into(back, sha256);
+        }
+    }
+
+    impl<T: Hashable> Hashable for BTreeSet<T> {
+        fn hash_into(&self, sha256: &mut Sha256) {
+            hash_len(self.len(), sha256);
+            for item in self {
+                item.hash_into(sha256);
+            }
+        }
+    }
+
+    impl<K: Hashable, V: Hashable> Hashable for BTreeMap<K, V> {
+        fn hash_into(&self, sha256: &mut Sha256) {
+            hash_len(self.len(), sha256);
+            for (key, value) in self {
+                key.hash_into(sha256);
+                value.hash_into(sha256);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::{vec, vec::Vec};
+
+    fn hash<T: Hashable + ?Sized>(value: &T) -> [u8; 32] {
+        let mut sha256 = Sha256::new();
+        sha256.absorb(value);
+        sha256.finalize()
+    }
+
+    #[test]
+    fn strings_are_length_prefixed() {
+        assert_ne!(hash(&("ab", "c")), hash(&("a", "bc")));
+        let mut sha256 = Sha256::new();
+        let expected = sha256.digest(&[0, 0, 0, 0, 0, 0, 0, 2, b'a', b'b']);
+        assert_eq!(hash("ab"), expected);
+        #[cfg(feature = "alloc")]
+        assert_eq!(hash(&std::string::String::from("ab")), expected);
+    }
+
+    #[test]
+    fn integers_are_big_endian() {
+        let mut sha256 = Sha256::new();
+        assert_eq!(hash(&0x01020304u32), sha256.digest(&[1, 2, 3, 4]));
+        assert_eq!(hash(&1usize), hash(&1u64));
+        assert_eq!(hash(&-1isize), hash(&-1i64));
+    }
+
+    #[test]
+    fn slices_arrays_and_vecs_agree() {
+        let vec: Vec<u16> = vec![1, 2, 3];
+        assert_eq!(hash(&[1u16, 2, 3]), hash(vec.as_slice()));
+        #[cfg(feature = "alloc")]
+        assert_eq!(hash(&vec), hash(&[1u16, 2, 3]));
+        assert_ne!(hash(&[[1u8], [2u8]]), hash(&[[1u8, 2u8]]));
+    }
+
+    #[test]
+    fn options_are_tagged() {
+        assert_ne!(hash(&None::<u8>), hash(&Some(0u8)));
+        assert_ne!(hash(&Some(None::<u8>)), hash(&None::<Option<u8>>));
+    }
+}
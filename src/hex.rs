@@ -0,0 +1,171 @@
+//! Lowercase hex encoding without allocation.
+
+use core::fmt;
+use core::str;
+
+use crate::Sha256;
+
+const DIGITS: &[u8; 16] = b"0123456789abcdef";
+
+/// Encodes a digest as 64 lowercase hex characters.
+///
+/// # Arguments
+/// * `bytes` - The digest to be encoded.
+///
+/// # Returns
+/// The ASCII hex characters, two per byte.
+pub(crate) fn encode(bytes: &[u8; 32]) -> [u8; 64] {
+    let mut hex = [0u8; 64];
+    for (i, byte) in bytes.iter().enumerate() {
+        hex[i * 2] = DIGITS[(byte >> 4) as usize];
+        hex[i * 2 + 1] = DIGITS[(byte & 0xf) as usize];
+    }
+    hex
+}
+
+/// A hasher which writes its digest as lowercase hex straight into a sink when finalized, without allocating a `String`.
+///
+/// ```
+/// use sha_256::HexDigestWriter;
+/// let mut hasher = HexDigestWriter::new(String::new());
+/// hasher.update(b"hello");
+/// let hex = hasher.finalize_fmt().unwrap();
+/// assert_eq!(hex, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+/// ```
+pub struct HexDigestWriter<W> {
+    sha256: Sha256,
+    sink: W,
+}
+
+impl<W> HexDigestWriter<W> {
+    /// Creates a hasher which will write its digest into `sink`.
+    ///
+    /// # Arguments
+    /// * `sink` - Where the hex digest is written, e.g. a `fmt::Formatter`, `String`, `File` or `Stdout`.
+    pub fn new(sink: W) -> Self {
+        Self { sha256: Sha256::new(), sink }
+    }
+
+    /// Absorbs more of the message.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.sha256.update(data);
+    }
+
+    fn finalize_hex(self) -> ([u8; 64], W) {
+        (encode(&self.sha256.finalize()), self.sink)
+    }
+}
+
+impl<W: fmt::Write> HexDigestWriter<W> {
+    /// Writes the 64 character hex digest of the message into a `fmt::Write` sink.
+    ///
+    /// # Returns
+    /// The sink, or the error it returned.
+    pub fn finalize_fmt(self) -> Result<W, fmt::Error> {
+        let (hex, mut sink) = self.finalize_hex();
+        // hex digits are always ASCII
+        sink.write_str(str::from_utf8(&hex).unwrap())?;
+        Ok(sink)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> HexDigestWriter<W> {
+    /// Writes the 64 character hex digest of the message into an `io::Write` sink.
+    ///
+    /// # Returns
+    /// The sink, or the error it returned.
+    pub fn finalize_io(self) -> std::io::Result<W> {
+        let (hex, mut sink) = self.finalize_hex();
+        sink.write_all(&hex)?;
+        Ok(sink)
+    }
+}
+
+/// Data written to the adapter is hashed, e.g. as the destination of `io::copy`.
+#[cfg(feature = "std")]
+impl<W> std::io::Write for HexDigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Decodes 64 hex characters (either case) into a digest.
+///
+/// # Arguments
+/// * `hex` - The ASCII hex characters.
+///
+/// # Returns
+/// The digest, or `None` if `hex` is not exactly 64 hex characters.
+#[cfg(any(feature = "alloc", feature = "serde"))]
+pub(crate) fn decode(hex: &[u8]) -> Option<[u8; 32]> {
+    if hex.len() != 64 {
+        return None;
+    }
+    let mut bytes = [0u8; 32];
+    for (byte, pair) in bytes.iter_mut().zip(hex.chunks_exact(2)) {
+        *byte = (nibble(pair[0])? << 4) | nibble(pair[1])?;
+    }
+    Some(bytes)
+}
+
+#[cfg(any(feature = "alloc", feature = "serde"))]
+fn nibble(c: u8) -> Option<u8> {
+    match c {
+        b'0'..=b'9' => Some(c - b'0'),
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'A'..=b'F' => Some(c - b'A' + 10),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    struct Buf {
+        bytes: [u8; 64],
+        len: usize,
+    }
+
+    impl fmt::Write for Buf {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            let end = self.len + s.len();
+            self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?.copy_from_slice(s.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_hex_into_sinks() {
+        let expected = b"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        // a fixed size buffer, no allocation involved
+        let mut hasher = HexDigestWriter::new(Buf { bytes: [0; 64], len: 0 });
+        hasher.update(b"hello");
+        let buf = hasher.finalize_fmt().unwrap();
+        assert_eq!(&buf.bytes, expected);
+
+        #[cfg(feature = "std")]
+        {
+            let mut hasher = HexDigestWriter::new(std::vec::Vec::new());
+            std::io::Write::write_all(&mut hasher, b"hello").unwrap();
+            assert_eq!(hasher.finalize_io().unwrap(), expected);
+        }
+
+        // the sink's errors are passed on
+        let mut hasher = HexDigestWriter::new(Buf { bytes: [0; 64], len: 1 });
+        hasher.update(b"hello");
+        assert!(hasher.finalize_fmt().is_err());
+    }
+}
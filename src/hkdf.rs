@@ -0,0 +1,142 @@
+//! HKDF-SHA256, the HMAC based key derivation function of RFC 5869.
+//!
+//! `extract` concentrates the entropy of the input keying material, e.g. a Diffie-Hellman shared secret, into a pseudorandom key,
+//! and `expand` stretches that into as many output bytes as needed, bound to a context string so different uses get independent keys.
+//!
+//! ```
+//! let prk = sha_256::hkdf::extract(b"salt", b"shared secret");
+//! let mut keys = [0u8; 64];
+//! sha_256::hkdf::expand_into(&prk, b"handshake keys", &mut keys);
+//! let (client_key, server_key) = keys.split_at(32);
+//! # assert_ne!(client_key, server_key);
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::wipe::zeroize;
+use crate::{HmacKey, HmacSha256};
+
+/// The most output `expand` can produce, 255 blocks of 32 bytes.
+pub const MAX_OUTPUT_LEN: usize = 255 * 32;
+
+/// The extract step, `PRK = HMAC-SHA256(salt, IKM)`.
+///
+/// # Arguments
+/// * `salt` - A non-secret random value, ideally 32 bytes. An empty salt is the same as 32 0's, as the RFC specifies.
+/// * `ikm` - The input keying material.
+///
+/// # Returns
+/// The 32 byte pseudorandom key, to pass to `expand`.
+pub fn extract(salt: &[u8], ikm: &[u8]) -> [u8; 32] {
+    // HMAC pads the key with 0's anyway, so an empty salt needs no special case
+    let mut hmac = HmacSha256::new(salt);
+    hmac.update(ikm);
+    hmac.finalize()
+}
+
+/// The expand step, filling `okm` with output keying material derived from the pseudorandom key and `info`.
+///
+/// # Arguments
+/// * `prk` - A pseudorandom key of at least 32 bytes, usually the output of `extract`.
+/// * `info` - Context binding the output to its use, e.g. a protocol and purpose label, can be empty.
+/// * `okm` - Where the output is written, up to `MAX_OUTPUT_LEN` bytes. The first bytes are the same whatever its length.
+///
+/// # Panics
+/// If `okm` is longer than `MAX_OUTPUT_LEN`.
+pub fn expand_into(prk: &[u8], info: &[u8], okm: &mut [u8]) {
+    assert!(okm.len() <= MAX_OUTPUT_LEN, "HKDF-SHA256 can't output more than {} bytes", MAX_OUTPUT_LEN);
+    let key = HmacKey::new(prk);
+    // T(i) = HMAC(PRK, T(i - 1) | info | i), where T(0) is empty
+    let mut t = [0u8; 32];
+    for (i, out) in okm.chunks_mut(32).enumerate() {
+        let mut hmac = HmacSha256::with_key(&key);
+        if i > 0 {
+            hmac.update(&t);
+        }
+        hmac.update(info);
+        hmac.update(&[i as u8 + 1]);
+        t = hmac.finalize();
+        out.copy_from_slice(&t[..out.len()]);
+    }
+    zeroize(&mut t);
+}
+
+/// The expand step, returning `out_len` bytes of output keying material derived from the pseudorandom key and `info`.
+///
+/// # Arguments
+/// * `prk` - A pseudorandom key of at least 32 bytes, usually the output of `extract`.
+/// * `info` - Context binding the output to its use, e.g. a protocol and purpose label, can be empty.
+/// * `out_len` - The number of bytes to output, up to `MAX_OUTPUT_LEN`.
+///
+/// # Panics
+/// If `out_len` is more than `MAX_OUTPUT_LEN`.
+#[cfg(feature = "alloc")]
+pub fn expand(prk: &[u8], info: &[u8], out_len: usize) -> Vec<u8> {
+    let mut okm = alloc::vec![0u8; out_len];
+    expand_into(prk, info, &mut okm);
+    okm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unhex(hex: &str) -> [u8; 82] {
+        let mut bytes = [0u8; 82];
+        for (i, byte) in bytes.iter_mut().enumerate().take(hex.len() / 2) {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        bytes
+    }
+
+    #[test]
+    fn rfc_5869() {
+        // test cases 1 to 3, the SHA-256 ones, as [IKM, salt, info] and the expected (PRK, OKM), most inputs are runs of consecutive bytes
+        let seq: [u8; 256] = core::array::from_fn(|i| i as u8);
+        let cases: [[&[u8]; 3]; 3] = [[&[0x0b; 22], &seq[..0x0d], &seq[0xf0..0xfa]], [&seq[..0x50], &seq[0x60..0xb0], &seq[0xb0..]], [&[0x0b; 22], b"", b""]];
+        let expected = [
+            (
+                "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865",
+            ),
+            (
+                "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+                "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71cc30c58179ec3e87c14c01d5c1f3434f1d87",
+            ),
+            (
+                "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+                "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8",
+            ),
+        ];
+        for (i, ([ikm, salt, info], (prk, okm))) in cases.iter().zip(expected).enumerate() {
+            let actual_prk = extract(salt, ikm);
+            assert_eq!(actual_prk[..], unhex(prk)[..32], "case {}", i + 1);
+            let len = okm.len() / 2;
+            let mut actual_okm = [0u8; 82];
+            expand_into(&actual_prk, info, &mut actual_okm[..len]);
+            assert_eq!(actual_okm, unhex(okm), "case {}", i + 1);
+            #[cfg(feature = "alloc")]
+            assert_eq!(expand(&actual_prk, info, len), actual_okm[..len], "case {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn shorter_output_is_a_prefix() {
+        let prk = extract(b"salt", b"ikm");
+        let mut long = [0u8; MAX_OUTPUT_LEN];
+        expand_into(&prk, b"info", &mut long);
+        for len in [0, 1, 31, 32, 33, 100] {
+            let mut short = [0u8; 100];
+            expand_into(&prk, b"info", &mut short[..len]);
+            assert_eq!(short[..len], long[..len], "len {}", len);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_much_output() {
+        let mut okm = [0u8; MAX_OUTPUT_LEN + 1];
+        expand_into(&[0; 32], b"", &mut okm);
+    }
+}
@@ -0,0 +1,536 @@
+//! HMAC-SHA256 as specified in RFC 2104.
+
+use core::fmt;
+
+use crate::wipe::{zeroize, zeroize_hasher, zeroize_keyed_hasher};
+use crate::Sha256;
+
+/// A secret HMAC key, overwritten with 0's when dropped.
+///
+/// The key is processed once, as RFC 2104 specifies: keys longer than the 64 byte block size are hashed first and shorter keys padded with 0's,
+/// then the hash states after absorbing `key ^ ipad` and `key ^ opad` are kept. `HmacSha256::with_key` starts from those, so MACing each message
+/// under the key only hashes the message and the inner digest. The key itself is not kept.
+///
+/// It isn't `Clone` and its `Debug` output is redacted, so the key isn't copied or logged by accident.
+pub struct HmacKey {
+    inner: [u32; 8], // hash values after absorbing key ^ ipad
+    outer: [u32; 8], // hash values after absorbing key ^ opad
+    #[cfg(feature = "std")]
+    locked: bool,
+}
+
+impl HmacKey {
+    /// Creates a key from its bytes.
+    ///
+    /// # Arguments
+    /// * `key` - The secret key, of any length. The caller is responsible for wiping its own copy.
+    pub fn new(key: &[u8]) -> Self {
+        let mut hmac_key = Self {
+            inner: [0; 8],
+            outer: [0; 8],
+            #[cfg(feature = "std")]
+            locked: false,
+        };
+        hmac_key.set(key);
+        hmac_key
+    }
+
+    /// Creates a key in memory which is locked into RAM (`mlock` on Unix, `VirtualLock` on Windows), so it is never written to swap.
+    ///
+    /// The key lives in a `LockedHmacKey`, which it can't be moved out of. Locking is usually limited per process (see `ulimit -l`).
+    ///
+    /// # Arguments
+    /// * `key` - The secret key, of any length.
+    ///
+    /// # Returns
+    /// The key, or the OS error if the memory could not be locked, in which case the key was never copied.
+    #[cfg(feature = "std")]
+    pub fn new_locked(key: &[u8]) -> std::io::Result<LockedHmacKey> {
+        LockedHmacKey::new(key)
+    }
+
+    /// Returns whether the key's memory is locked into RAM, see `new_locked`.
+    pub fn is_locked(&self) -> bool {
+        #[cfg(feature = "std")]
+        return self.locked;
+        #[cfg(not(feature = "std"))]
+        false
+    }
+
+    fn set(&mut self, key: &[u8]) {
+        let mut sha256 = Sha256::new();
+        let mut pad = [0u8; 64];
+        if key.len() > 64 {
+            // digest leaves the hasher reset, ready for the padded key
+            let mut hashed = [0u8; 32];
+            sha256.digest_into(key, &mut hashed);
+            pad[..32].copy_from_slice(&hashed);
+            zeroize(&mut hashed);
+        } else {
+            pad[..key.len()].copy_from_slice(key);
+        }
+
+        for p in pad.iter_mut() {
+            *p ^= 0x36;
+        }
+        sha256.update(&pad);
+        self.inner = sha256.state();
+        // undo the inner pad while applying the outer one
+        for p in pad.iter_mut() {
+            *p ^= 0x36 ^ 0x5c;
+        }
+        sha256.reset();
+        sha256.update(&pad);
+        self.outer = sha256.state();
+
+        // the pad and the message schedule derived from it are as secret as the key
+        zeroize(&mut pad);
+        zeroize_hasher(&mut sha256);
+    }
+}
+
+impl Drop for HmacKey {
+    fn drop(&mut self) {
+        zeroize(&mut self.inner);
+        zeroize(&mut self.outer);
+    }
+}
+
+impl fmt::Debug for HmacKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HmacKey").field("locked", &self.is_locked()).finish_non_exhaustive()
+    }
+}
+
+/// An `HmacKey` locked into RAM, see `HmacKey::new_locked`. Derefs to the `HmacKey`.
+///
+/// The key is allocated on its own, page aligned, so it never moves and unlocking it can't unlock the page of any other locked key
+/// (page locks aren't counted, one `munlock` undoes every `mlock` of the page). The key is wiped before the memory is unlocked and freed.
+#[cfg(feature = "std")]
+pub struct LockedHmacKey {
+    key: core::ptr::NonNull<HmacKey>,
+}
+
+// SAFETY: the key is owned exclusively, like a `Box<HmacKey>`, and `HmacKey` is `Send` and `Sync`
+#[cfg(feature = "std")]
+unsafe impl Send for LockedHmacKey {}
+#[cfg(feature = "std")]
+unsafe impl Sync for LockedHmacKey {}
+
+#[cfg(feature = "std")]
+impl LockedHmacKey {
+    // at least the page size on every supported platform, and Windows' allocation granularity
+    const ALIGN: usize = 64 * 1024;
+
+    fn layout() -> std::alloc::Layout {
+        std::alloc::Layout::from_size_align(core::mem::size_of::<HmacKey>().max(Self::ALIGN), Self::ALIGN).unwrap()
+    }
+
+    fn new(key: &[u8]) -> std::io::Result<Self> {
+        let layout = Self::layout();
+        // SAFETY: the layout has a non-zero size
+        let ptr = unsafe { std::alloc::alloc(layout) } as *mut HmacKey;
+        let Some(ptr) = core::ptr::NonNull::new(ptr) else {
+            std::alloc::handle_alloc_error(layout);
+        };
+        if let Err(e) = lock::lock(ptr.as_ptr() as *const u8, core::mem::size_of::<HmacKey>()) {
+            // SAFETY: allocated above with the same layout and nothing was written to it
+            unsafe { std::alloc::dealloc(ptr.as_ptr() as *mut u8, layout) };
+            return Err(e);
+        }
+        // SAFETY: the allocation is big enough and aligned for an `HmacKey`, and not yet initialized
+        unsafe { ptr.as_ptr().write(HmacKey { inner: [0; 8], outer: [0; 8], locked: true }) };
+        let mut locked = Self { key: ptr };
+        // SAFETY: initialized above and owned by `locked`
+        unsafe { locked.key.as_mut() }.set(key);
+        Ok(locked)
+    }
+}
+
+#[cfg(feature = "std")]
+impl core::ops::Deref for LockedHmacKey {
+    type Target = HmacKey;
+
+    fn deref(&self) -> &HmacKey {
+        // SAFETY: initialized in `new` and only freed in `drop`
+        unsafe { self.key.as_ref() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for LockedHmacKey {
+    fn drop(&mut self) {
+        let ptr = self.key.as_ptr();
+        // SAFETY: the key is initialized and dropped once, which wipes it while it's still locked, then the allocation from `new` is freed
+        unsafe {
+            core::ptr::drop_in_place(ptr);
+            // nothing useful can be done if this fails, the memory is freed either way
+            let _ = lock::unlock(ptr as *const u8, core::mem::size_of::<HmacKey>());
+            std::alloc::dealloc(ptr as *mut u8, Self::layout());
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for LockedHmacKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+mod lock {
+    use core::ffi::{c_int, c_void};
+    use std::io;
+
+    extern "C" {
+        fn mlock(addr: *const c_void, len: usize) -> c_int;
+        fn munlock(addr: *const c_void, len: usize) -> c_int;
+    }
+
+    pub(super) fn lock(ptr: *const u8, len: usize) -> io::Result<()> {
+        // SAFETY: only changes whether the pages are swappable, the memory itself isn't touched
+        if unsafe { mlock(ptr as *const c_void, len) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub(super) fn unlock(ptr: *const u8, len: usize) -> io::Result<()> {
+        // SAFETY: as for lock
+        if unsafe { munlock(ptr as *const c_void, len) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+mod lock {
+    use core::ffi::c_void;
+    use std::io;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn VirtualLock(addr: *const c_void, len: usize) -> i32;
+        fn VirtualUnlock(addr: *const c_void, len: usize) -> i32;
+    }
+
+    pub(super) fn lock(ptr: *const u8, len: usize) -> io::Result<()> {
+        // SAFETY: only changes whether the pages can be paged out, the memory itself isn't touched
+        if unsafe { VirtualLock(ptr as *const c_void, len) } != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+
+    pub(super) fn unlock(ptr: *const u8, len: usize) -> io::Result<()> {
+        // SAFETY: as for lock
+        if unsafe { VirtualUnlock(ptr as *const c_void, len) } != 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(all(feature = "std", not(any(unix, windows))))]
+mod lock {
+    use std::io;
+
+    pub(super) fn lock(_: *const u8, _: usize) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    pub(super) fn unlock(_: *const u8, _: usize) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The HMAC-SHA256 keyed message authentication code.
+///
+/// Its states start from the keyed ones, which are as secret as the key, so they are wiped when it's dropped.
+#[derive(Clone)]
+pub struct HmacSha256 {
+    inner: Sha256, // absorbs (key ^ ipad) || message
+    outer: Sha256, // absorbs (key ^ opad) || inner hash
+}
+
+impl Drop for HmacSha256 {
+    fn drop(&mut self) {
+        zeroize_keyed_hasher(&mut self.inner);
+        zeroize_keyed_hasher(&mut self.outer);
+    }
+}
+
+impl HmacSha256 {
+    /// Creates a new HMAC instance for the given key.
+    ///
+    /// # Arguments
+    /// * `key` - The secret key. Keys longer than the 64 byte block size are hashed first, shorter keys are padded with 0's.
+    pub fn new(key: &[u8]) -> Self {
+        Self::with_key(&HmacKey::new(key))
+    }
+
+    /// Creates a new HMAC instance for a key held in an `HmacKey`.
+    ///
+    /// The key was already processed when the `HmacKey` was created, so this is much cheaper than `new` when MACing many messages under one key.
+    ///
+    /// # Arguments
+    /// * `key` - The secret key.
+    pub fn with_key(key: &HmacKey) -> Self {
+        Self { inner: Sha256::from_state(key.inner, 64), outer: Sha256::from_state(key.outer, 64) }
+    }
+
+    /// Absorbs more of the message.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// Computes the authentication code of the message absorbed so far.
+    ///
+    /// # Returns
+    /// The 32 byte MAC.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let inner = self.inner.finalize_with(|_| {});
+        self.outer.update(&inner);
+        self.outer.finalize_with(|_| {})
+    }
+
+    /// Checks the message against an expected MAC in constant time.
+    ///
+    /// # Arguments
+    /// * `tag` - The expected MAC.
+    ///
+    /// # Returns
+    /// `true` if the MAC of the message matches `tag`.
+    pub fn verify(self, tag: &[u8]) -> bool {
+        let mac = self.finalize();
+        if tag.len() != mac.len() {
+            return false;
+        }
+        // accumulate differences rather than exiting early to avoid leaking the position of the first mismatch
+        let mut diff = 0u8;
+        for (a, b) in mac.iter().zip(tag.iter()) {
+            diff |= a ^ b;
+        }
+        core::hint::black_box(diff) == 0
+    }
+}
+
+/// Computes the HMAC-SHA256 of a message in one go.
+///
+/// # Arguments
+/// * `key` - The secret key.
+/// * `msg` - A byte slice representing the message to be authenticated.
+///
+/// # Returns
+/// The 32 byte MAC.
+pub fn hmac_sha256(key: &[u8], msg: &[u8]) -> [u8; 32] {
+    let mut hmac = HmacSha256::new(key);
+    hmac.update(msg);
+    hmac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_4231_case_2() {
+        let mac = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+        assert_eq!(mac, [
+            0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75, 0xc7,
+            0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58, 0xb9, 0x64, 0xec, 0x38, 0x43,
+        ]);
+    }
+
+    #[test]
+    fn long_key_is_hashed() {
+        // RFC 4231 test case 6
+        let key = [0xaau8; 131];
+        let mac = hmac_sha256(&key, b"Test Using Larger Than Block-Size Key - Hash Key First");
+        assert_eq!(mac, [
+            0x60, 0xe4, 0x31, 0x59, 0x1e, 0xe0, 0xb6, 0x7f, 0x0d, 0x8a, 0x26, 0xaa, 0xcb, 0xf5, 0xb7, 0x7f,
+            0x8e, 0x0b, 0xc6, 0x21, 0x37, 0x28, 0xc5, 0x14, 0x05, 0x46, 0x04, 0x0f, 0x0e, 0xe3, 0x7f, 0x54,
+        ]);
+    }
+
+    #[test]
+    fn rfc_4231() {
+        fn unhex(hex: &str) -> [u8; 32] {
+            let mut bytes = [0u8; 32];
+            for (i, byte) in bytes.iter_mut().enumerate().take(hex.len() / 2) {
+                *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+            }
+            bytes
+        }
+        let key_4: [u8; 25] = core::array::from_fn(|i| i as u8 + 1);
+        let long_msg: &[u8] = b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm.";
+        // test cases 1 to 7, case 5 only gives the first 128 bits of the tag
+        let cases: [(&[u8], &[u8], &str); 7] = [
+            (&[0x0b; 20], b"Hi There", "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"),
+            (b"Jefe", b"what do ya want for nothing?", "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"),
+            (&[0xaa; 20], &[0xdd; 50], "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe"),
+            (&key_4, &[0xcd; 50], "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b"),
+            (&[0x0c; 20], b"Test With Truncation", "a3b6167473100ee06e0c796c2955552b"),
+            (&[0xaa; 131], b"Test Using Larger Than Block-Size Key - Hash Key First", "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"),
+            (&[0xaa; 131], long_msg, "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2"),
+        ];
+        for (i, (key, msg, tag)) in cases.iter().enumerate() {
+            let tag_len = tag.len() / 2;
+            let expected = unhex(tag);
+            let mac = hmac_sha256(key, msg);
+            assert_eq!(mac[..tag_len], expected[..tag_len], "case {}", i + 1);
+            // and through the streaming API, one byte at a time
+            let mut hmac = HmacSha256::with_key(&HmacKey::new(key));
+            for byte in msg.iter() {
+                hmac.update(core::slice::from_ref(byte));
+            }
+            assert_eq!(hmac.finalize(), mac, "case {}", i + 1);
+        }
+    }
+
+    #[test]
+    fn update_matches_one_shot() {
+        extern crate std;
+        use std::vec::Vec;
+
+        // random keys and messages fed in randomly sized pieces, compared with the one-shot MAC
+        let mut rng = crate::tests::Rng::new(7);
+        for _ in 0..2_000 {
+            let key: Vec<u8> = (0..rng.next() % 150).map(|_| rng.next() as u8).collect();
+            let msg: Vec<u8> = (0..rng.next() % 600).map(|_| rng.next() as u8).collect();
+            let expected = hmac_sha256(&key, &msg);
+
+            let mut hmac = HmacSha256::new(&key);
+            let mut rem = &msg[..];
+            while !rem.is_empty() {
+                let n = ((rng.next() % 130) as usize).min(rem.len());
+                hmac.update(&rem[..n]);
+                rem = &rem[n..];
+            }
+            assert_eq!(hmac.finalize(), expected, "key of len {}, message of len {}", key.len(), msg.len());
+        }
+    }
+
+    #[test]
+    fn verify_checks_tag() {
+        let mac = hmac_sha256(b"key", b"msg");
+        let mut hmac = HmacSha256::new(b"key");
+        hmac.update(b"msg");
+        assert!(hmac.verify(&mac));
+        let mut hmac = HmacSha256::new(b"key");
+        hmac.update(b"msg");
+        assert!(!hmac.verify(&mac[..31]));
+    }
+
+    #[test]
+    fn key_is_wiped() {
+        extern crate std;
+
+        let key = [7u8; 100];
+        let hmac_key = HmacKey::new(&key);
+        let mut hmac = HmacSha256::with_key(&hmac_key);
+        hmac.update(b"msg");
+        assert_eq!(hmac.finalize(), hmac_sha256(&key, b"msg"));
+        assert!(!hmac_key.is_locked());
+        assert_eq!(std::format!("{:?}", hmac_key), "HmacKey { locked: false, .. }");
+
+        let mut hmac_key = core::mem::ManuallyDrop::new(hmac_key);
+        // SAFETY: the key is never used again, drop only wipes the states in place
+        unsafe { core::mem::ManuallyDrop::drop(&mut hmac_key) };
+        assert_eq!((hmac_key.inner, hmac_key.outer), ([0; 8], [0; 8]));
+    }
+
+    #[test]
+    fn hmac_states_are_wiped() {
+        let mut hmac = core::mem::ManuallyDrop::new(HmacSha256::new(b"key"));
+        hmac.update(b"msg");
+        // SAFETY: the instance is never used again, drop only wipes the states in place
+        unsafe { core::mem::ManuallyDrop::drop(&mut hmac) };
+        for sha256 in [&hmac.inner, &hmac.outer] {
+            assert_eq!((sha256.state(), sha256.buf, sha256.w), ([0; 8], [0; 64], [0; 64]));
+        }
+    }
+
+    #[test]
+    fn key_is_reusable() {
+        extern crate std;
+
+        let msg = [3u8; 150];
+        for len in [0, 1, 32, 63, 64, 65, 131] {
+            let key: std::vec::Vec<u8> = (0..len as u8).collect();
+            let hmac_key = HmacKey::new(&key);
+            for msg_len in [0, 55, 64, 150] {
+                let mut hmac = HmacSha256::with_key(&hmac_key);
+                hmac.update(&msg[..msg_len]);
+                assert_eq!(hmac.finalize(), hmac_sha256(&key, &msg[..msg_len]), "key {} msg {}", len, msg_len);
+            }
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn locked_key() {
+        // locking can be refused by the sandbox or the memlock limit, but must not change the MAC
+        if let (Ok(first), Ok(second)) = (HmacKey::new_locked(b"key"), HmacKey::new_locked(b"other key")) {
+            assert!(first.is_locked());
+            let mut hmac = HmacSha256::with_key(&first);
+            hmac.update(b"msg");
+            assert_eq!(hmac.finalize(), hmac_sha256(b"key", b"msg"));
+            // each key has its own page, so unlocking one leaves the other locked
+            assert_ne!(&*first as *const HmacKey as usize / LockedHmacKey::ALIGN, &*second as *const HmacKey as usize / LockedHmacKey::ALIGN);
+            drop(first);
+            assert!(second.is_locked());
+            assert_eq!(hmac_sha256(b"other key", b"msg"), {
+                let mut hmac = HmacSha256::with_key(&second);
+                hmac.update(b"msg");
+                hmac.finalize()
+            });
+        }
+    }
+
+    #[test]
+    fn empty_key() {
+        let mac = hmac_sha256(b"", b"");
+        assert_eq!(mac, [
+            0xb6, 0x13, 0x67, 0x9a, 0x08, 0x14, 0xd9, 0xec, 0x77, 0x2f, 0x95, 0xd7, 0x78, 0xc3, 0x5f, 0xc5,
+            0xff, 0x16, 0x97, 0xc4, 0x93, 0x71, 0x56, 0x53, 0xc6, 0xc7, 0x12, 0x14, 0x42, 0x92, 0xc5, 0xad,
+        ]);
+        // keys are padded with 0's, so this is the same key
+        assert_eq!(hmac_sha256(&[0; 64], b""), mac);
+    }
+
+    #[cfg(feature = "wycheproof")]
+    #[test]
+    fn wycheproof() {
+        use wycheproof::mac::{TestName, TestSet};
+
+        let set = TestSet::load(TestName::HmacSha256).unwrap();
+        let mut long_keys = 0;
+        for group in &set.test_groups {
+            // truncated tags are compared against the start of the MAC
+            let tag_len = group.tag_size / 8;
+            for test in &group.tests {
+                let mac = hmac_sha256(&test.key, &test.msg);
+                let matches = test.tag.len() == tag_len && mac[..tag_len] == test.tag[..];
+                assert_eq!(matches, !test.result.must_fail(), "tcId {}: {}", test.tc_id, test.comment);
+                if tag_len == 32 {
+                    let mut hmac = HmacSha256::new(&test.key);
+                    hmac.update(&test.msg);
+                    assert_eq!(hmac.verify(&test.tag), matches, "tcId {}", test.tc_id);
+                }
+                long_keys += (test.key.len() > 64) as usize;
+            }
+        }
+        // the set has no empty keys, see empty_key
+        assert!(long_keys > 0);
+    }
+}
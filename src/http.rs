@@ -0,0 +1,135 @@
+//! Hashing of `http_body::Body` payloads, e.g. to verify request/response integrity in web services.
+
+use core::fmt;
+use core::future::poll_fn;
+use core::pin::pin;
+
+use bytes::{Buf, Bytes, BytesMut};
+use http_body::Body;
+
+use crate::{Hash256, Sha256};
+
+/// Why hashing a body failed.
+///
+/// Kept apart from the crate's `Error` because it carries the body's own error type, e.g. `hyper::Error`, unchanged for the caller to match on,
+/// which `Error` could only hold boxed and type erased.
+#[derive(Debug)]
+pub enum BodyError<E> {
+    /// The body itself returned an error.
+    Body(E),
+    /// The body was larger than the allowed maximum size.
+    TooLarge {
+        /// The maximum size in bytes.
+        limit: u64,
+    },
+}
+
+impl<E: fmt::Display> fmt::Display for BodyError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BodyError::Body(e) => write!(f, "body error: {}", e),
+            BodyError::TooLarge { limit } => write!(f, "body is larger than {} bytes", limit),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for BodyError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BodyError::Body(e) => Some(e),
+            BodyError::TooLarge { .. } => None,
+        }
+    }
+}
+
+/// Streams a body through the hasher without holding on to the data.
+///
+/// # Arguments
+/// * `body` - The body to be hashed. Trailers are ignored.
+/// * `max_size` - If set, fail with `BodyError::TooLarge` once the body exceeds this many bytes.
+///
+/// # Returns
+/// The hash of the body's data frames.
+pub async fn hash_body<B: Body>(body: B, max_size: Option<u64>) -> Result<Hash256, BodyError<B::Error>> {
+    let mut sha256 = Sha256::new();
+    read_body(body, max_size, |chunk| sha256.update(chunk)).await?;
+    Ok(Hash256::new(sha256.finalize()))
+}
+
+/// Collects a body into memory while hashing it, for when the payload is needed as well as its hash.
+///
+/// # Arguments
+/// * `body` - The body to be collected. Trailers are ignored.
+/// * `max_size` - If set, fail with `BodyError::TooLarge` once the body exceeds this many bytes.
+///
+/// # Returns
+/// The body's data and its hash.
+pub async fn collect_and_hash<B: Body>(body: B, max_size: Option<u64>) -> Result<(Bytes, Hash256), BodyError<B::Error>> {
+    let mut sha256 = Sha256::new();
+    let mut data = BytesMut::new();
+    read_body(body, max_size, |chunk| {
+        sha256.update(chunk);
+        data.extend_from_slice(chunk);
+    })
+    .await?;
+    Ok((data.freeze(), Hash256::new(sha256.finalize())))
+}
+
+/// Feeds every chunk of the body's data frames to `f`, enforcing the size limit.
+async fn read_body<B: Body>(body: B, max_size: Option<u64>, mut f: impl FnMut(&[u8])) -> Result<(), BodyError<B::Error>> {
+    let limit = max_size.unwrap_or(u64::MAX);
+    // fail fast if the body already declares that it is too large
+    if body.size_hint().lower() > limit {
+        return Err(BodyError::TooLarge { limit });
+    }
+
+    let mut body = pin!(body);
+    let mut len: u64 = 0;
+    while let Some(frame) = poll_fn(|cx| body.as_mut().poll_frame(cx)).await {
+        let frame = frame.map_err(BodyError::Body)?;
+        if let Ok(mut data) = frame.into_data() {
+            len = len.saturating_add(data.remaining() as u64);
+            if len > limit {
+                return Err(BodyError::TooLarge { limit });
+            }
+            while data.has_remaining() {
+                let chunk = data.chunk();
+                let n = chunk.len();
+                f(chunk);
+                data.advance(n);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::block_on;
+    use http_body_util::{BodyExt, Full, StreamBody};
+    use std::vec::Vec;
+
+    #[test]
+    fn hashes_all_frames() {
+        let chunks: Vec<Result<http_body::Frame<Bytes>, core::convert::Infallible>> =
+            [&b"hel"[..], b"lo"].iter().map(|c| Ok(http_body::Frame::data(Bytes::from_static(c)))).collect();
+        let body = StreamBody::new(futures_util::stream::iter(chunks));
+        let expected = Hash256::new(Sha256::new().digest(b"hello"));
+        assert_eq!(block_on(hash_body(body, None)).unwrap(), expected);
+
+        let (data, hash) = block_on(collect_and_hash(Full::new(Bytes::from_static(b"hello")), Some(5))).unwrap();
+        assert_eq!(&data[..], b"hello");
+        assert_eq!(hash, expected);
+    }
+
+    #[test]
+    fn enforces_max_size() {
+        let result = block_on(hash_body(Full::new(Bytes::from_static(b"hello")), Some(4)));
+        assert!(matches!(result, Err(BodyError::TooLarge { limit: 4 })));
+        // a body which does not declare its size is caught while streaming
+        let body = Full::new(Bytes::from_static(b"hello")).map_frame(|f| f);
+        let result = block_on(hash_body(body.boxed_unsync(), Some(4)));
+        assert!(matches!(result, Err(BodyError::TooLarge { limit: 4 })));
+    }
+}
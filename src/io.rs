@@ -0,0 +1,788 @@
+//! `std::io` adapters which hash data as it passes through ("tee" into a hasher).
+
+use std::fs::{self, File, Metadata};
+use std::io::{self, BufRead, IoSlice, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::vec;
+use std::vec::Vec;
+
+use crate::{Error, Hash256, Sha256};
+
+/// A reader which hashes every byte read through it.
+pub struct HashingReader<R> {
+    inner: R,
+    sha256: Sha256,
+}
+
+impl<R> HashingReader<R> {
+    /// Wraps a reader.
+    ///
+    /// # Arguments
+    /// * `inner` - The reader to be hashed.
+    pub fn new(inner: R) -> Self {
+        Self { inner, sha256: Sha256::new() }
+    }
+
+    /// Returns a reference to the wrapped reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped reader. Bytes read directly from it are not hashed.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped reader and the hash of the bytes read so far.
+    pub fn finalize(self) -> (R, Hash256) {
+        (self.inner, Hash256::new(self.sha256.finalize()))
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.sha256.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+/// A writer which hashes every byte written through it.
+pub struct HashingWriter<W> {
+    inner: W,
+    sha256: Sha256,
+}
+
+impl<W> HashingWriter<W> {
+    /// Wraps a writer.
+    ///
+    /// # Arguments
+    /// * `inner` - The writer to be hashed.
+    pub fn new(inner: W) -> Self {
+        Self { inner, sha256: Sha256::new() }
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped writer. Bytes written directly to it are not hashed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the wrapped writer and the hash of the bytes written so far.
+    pub fn finalize(self) -> (W, Hash256) {
+        (self.inner, Hash256::new(self.sha256.finalize()))
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // only hash what the inner writer accepted, the rest will be passed to write again
+        let n = self.inner.write(buf)?;
+        self.sha256.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl Sha256 {
+    /// Absorbs the concatenation of a list of buffers, e.g. the scatter/gather buffers of a network stack.
+    ///
+    /// The same as calling `update` on each in turn. The vectored counterpart of `update`, as `write_vectored` is of `write`.
+    ///
+    /// # Arguments
+    /// * `bufs` - The next pieces of the message, in order.
+    pub fn update_vectored(&mut self, bufs: &[IoSlice<'_>]) {
+        for buf in bufs {
+            self.update(buf);
+        }
+    }
+}
+
+/// Lets the hasher be used as the destination of `io::copy`, `write!` etc.
+impl Write for Sha256 {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        // the default only writes the first non-empty buffer, the hasher can always take them all
+        self.update_vectored(bufs);
+        Ok(bufs.iter().map(|buf| buf.len()).sum())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Hashes everything from a reader until EOF.
+///
+/// # Arguments
+/// * `reader` - The source of the message.
+///
+/// # Returns
+/// The hash of the bytes read, or the first IO error encountered.
+pub fn hash_reader<R: Read>(mut reader: R) -> io::Result<Hash256> {
+    let mut sha256 = Sha256::new();
+    io::copy(&mut reader, &mut sha256)?;
+    Ok(Hash256::new(sha256.finalize()))
+}
+
+/// Hashes everything from a buffered reader until EOF, straight out of the reader's own buffer.
+///
+/// Prefer this over `hash_reader` for sources which are already buffered (`BufReader`, `StdinLock`, byte slices, ...) as it avoids copying into a second buffer.
+///
+/// # Arguments
+/// * `reader` - The source of the message.
+///
+/// # Returns
+/// The hash of the bytes read, or the first IO error encountered.
+pub fn hash_bufread<R: BufRead>(mut reader: R) -> io::Result<Hash256> {
+    let mut sha256 = Sha256::new();
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            break;
+        }
+        let n = buf.len();
+        sha256.update(buf);
+        reader.consume(n);
+    }
+    Ok(Hash256::new(sha256.finalize()))
+}
+
+/// Copies everything from a reader to a writer until EOF, hashing the data on the way through.
+///
+/// # Arguments
+/// * `reader` - The source of the data.
+/// * `writer` - The destination of the data.
+///
+/// # Returns
+/// The number of bytes copied and their hash, or the first IO error encountered.
+pub fn copy_and_hash<R: Read + ?Sized, W: Write + ?Sized>(reader: &mut R, writer: &mut W) -> io::Result<(u64, Hash256)> {
+    let mut writer = HashingWriter::new(writer);
+    let n = io::copy(reader, &mut writer)?;
+    Ok((n, writer.finalize().1))
+}
+
+/// Reads a stream to EOF and checks it against an expected hash, without buffering the data.
+///
+/// # Arguments
+/// * `reader` - The source of the message, e.g. a download.
+/// * `expected` - The hash the message should have.
+///
+/// # Returns
+/// `Ok` if the hash matches, otherwise `Error::Io` or an `Error::Mismatch` with the actual hash and number of bytes read.
+pub fn verify_reader<R: Read>(mut reader: R, expected: Hash256) -> Result<(), Error> {
+    let mut sha256 = Sha256::new();
+    // io::copy goes through a fixed size buffer, so memory use does not depend on the stream's length
+    let bytes_read = io::copy(&mut reader, &mut sha256)?;
+    let actual = Hash256::new(sha256.finalize());
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(Error::Mismatch { expected, actual, bytes_read })
+    }
+}
+
+/// Hashes the contents of a file.
+///
+/// With the `sparse` feature on Unix, holes in sparse files are found with `SEEK_HOLE`/`SEEK_DATA` and hashed as zeros without reading them.
+///
+/// # Arguments
+/// * `path` - The file to be hashed.
+///
+/// # Returns
+/// The hash of the file's contents, or the first IO error encountered.
+pub fn hash_file<P: AsRef<Path>>(path: P) -> io::Result<Hash256> {
+    let file = File::open(path)?;
+    #[cfg(all(unix, feature = "sparse"))]
+    return sparse::hash_file(file);
+    // larger than BufReader's default 8KiB to cut down on read syscalls for big files
+    #[cfg(not(all(unix, feature = "sparse")))]
+    hash_bufread(io::BufReader::with_capacity(64 * 1024, file))
+}
+
+/// How `hash_file_with` reads a file. The digest never depends on these, only how fast it's computed.
+///
+/// Every setting is picked from the file's size and the filesystem unless set. The best values depend on the storage: NVMe drives
+/// like big reads with several in flight, spinning disks a steady sequential stream, and network filesystems big reads to hide their latency.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FileHashOptions {
+    chunk_size: usize,
+    read_ahead: Option<usize>,
+    #[cfg(feature = "direct-io")]
+    direct: bool,
+}
+
+impl FileHashOptions {
+    /// Picks every setting automatically.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How many bytes are read at a time.
+    ///
+    /// Picked automatically by default: 64KiB for files under 1MiB, 256KiB under 64MiB and 1MiB above that, or the filesystem's
+    /// preferred IO size (`st_blksize` on Unix, large on most network filesystems) if that's bigger, but never more than the file needs.
+    ///
+    /// # Arguments
+    /// * `chunk_size` - The size of each read in bytes, 0 to pick automatically.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// How many chunks are read ahead on a background thread while the current one is hashed, so the disk and CPU work at the same time.
+    ///
+    /// Picked automatically by default: 4 for files of 16MiB or more, none for smaller files where starting a thread costs more than it saves.
+    /// For `hash_file_uring_with` this is the number of reads in flight instead.
+    ///
+    /// # Arguments
+    /// * `chunks` - The number of chunks, 0 to read and hash on the calling thread.
+    pub fn read_ahead(mut self, chunks: usize) -> Self {
+        self.read_ahead = Some(chunks);
+        self
+    }
+
+    /// Whether to bypass the OS page cache, so hashing a large file once doesn't evict data other programs are using.
+    ///
+    /// A hint: on Linux the file is opened with `O_DIRECT` (chunks are rounded up to 4KiB for its alignment rules), falling back to a normal
+    /// open on filesystems without it, such as tmpfs. On macOS `F_NOCACHE` is set. Elsewhere it does nothing. Off by default.
+    ///
+    /// # Arguments
+    /// * `direct` - True to bypass the cache.
+    #[cfg(feature = "direct-io")]
+    pub fn direct(mut self, direct: bool) -> Self {
+        self.direct = direct;
+        self
+    }
+
+    /// The chunk size to use for a file, `chunk_size` unless that's 0.
+    pub(crate) fn resolve_chunk_size(&self, metadata: &Metadata) -> usize {
+        if self.chunk_size > 0 {
+            return self.chunk_size;
+        }
+        let len = metadata.len();
+        let by_size = match len {
+            0..0x10_0000 => 64 * 1024,
+            0x10_0000..0x400_0000 => 256 * 1024,
+            _ => 1024 * 1024,
+        };
+        let preferred = preferred_io_size(metadata).min(8 * 1024 * 1024);
+        // a small file doesn't need a buffer bigger than itself, rounded up to whole pages
+        by_size.max(preferred).min(len.max(1).next_multiple_of(4096).try_into().unwrap_or(usize::MAX))
+    }
+
+    /// The read ahead depth to use for a file.
+    pub(crate) fn resolve_read_ahead(&self, metadata: &Metadata) -> usize {
+        self.read_ahead_or(if metadata.len() >= 16 * 1024 * 1024 { 4 } else { 0 })
+    }
+
+    /// The read ahead depth if set, otherwise `default`.
+    pub(crate) fn read_ahead_or(&self, default: usize) -> usize {
+        self.read_ahead.unwrap_or(default)
+    }
+}
+
+#[cfg(unix)]
+fn preferred_io_size(metadata: &Metadata) -> usize {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blksize().try_into().unwrap_or(0)
+}
+
+#[cfg(not(unix))]
+fn preferred_io_size(_: &Metadata) -> usize {
+    0
+}
+
+/// Hashes the contents of a file, reading it in chunks of a given size with an optional read ahead thread.
+///
+/// Unlike `hash_file` the `sparse` feature doesn't apply, holes are read like the rest of the file.
+///
+/// ```no_run
+/// use sha_256::{hash_file_with, FileHashOptions};
+/// // big sequential reads with plenty queued, e.g. for a network filesystem
+/// let hash = hash_file_with("disk.img", FileHashOptions::new().chunk_size(4 << 20).read_ahead(8))?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+///
+/// # Arguments
+/// * `path` - The file to be hashed.
+/// * `options` - How the file is read.
+///
+/// # Returns
+/// The hash of the file's contents, or the first IO error encountered.
+pub fn hash_file_with<P: AsRef<Path>>(path: P, options: FileHashOptions) -> io::Result<Hash256> {
+    #[cfg(feature = "direct-io")]
+    let (mut file, align) = if options.direct { direct::open(path.as_ref())? } else { (File::open(path)?, 1) };
+    #[cfg(not(feature = "direct-io"))]
+    let (mut file, align) = (File::open(path)?, 1);
+    let metadata = file.metadata()?;
+    let chunk_size = options.resolve_chunk_size(&metadata).next_multiple_of(align);
+    let read_ahead = options.resolve_read_ahead(&metadata);
+    if read_ahead == 0 {
+        let mut sha256 = Sha256::new();
+        let mut buf = AlignedBuf::new(chunk_size, align);
+        loop {
+            match read_full(&mut file, buf.as_mut_slice())? {
+                0 => return Ok(Hash256::new(sha256.finalize())),
+                n => sha256.update(&buf.as_mut_slice()[..n]),
+            }
+        }
+    }
+
+    // full buffers go to the hashing thread and come back empty to be refilled, so only read_ahead + 1 are ever allocated
+    let (full_tx, full_rx) = mpsc::sync_channel::<io::Result<(AlignedBuf, usize)>>(read_ahead);
+    let (empty_tx, empty_rx) = mpsc::channel();
+    for _ in 0..=read_ahead {
+        empty_tx.send(AlignedBuf::new(chunk_size, align)).unwrap();
+    }
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            while let Ok(mut buf) = empty_rx.recv() {
+                let result = read_full(&mut file, buf.as_mut_slice());
+                let last = !matches!(result, Ok(n) if n > 0);
+                // the hasher only stops listening after the last chunk
+                if full_tx.send(result.map(|n| (buf, n))).is_err() || last {
+                    break;
+                }
+            }
+        });
+        let mut sha256 = Sha256::new();
+        for result in full_rx {
+            let (mut buf, n) = result?;
+            if n == 0 {
+                break;
+            }
+            sha256.update(&buf.as_mut_slice()[..n]);
+            // the reader may already have stopped
+            let _ = empty_tx.send(buf);
+        }
+        Ok(Hash256::new(sha256.finalize()))
+    })
+}
+
+/// Reads until `buf` is full or EOF, returning how much was read.
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// A buffer whose start is aligned, as `O_DIRECT` reads require.
+struct AlignedBuf {
+    storage: Vec<u8>,
+    start: usize,
+    len: usize,
+}
+
+impl AlignedBuf {
+    fn new(len: usize, align: usize) -> Self {
+        let storage = vec![0u8; len + align - 1];
+        let start = storage.as_ptr().align_offset(align);
+        Self { storage, start, len }
+    }
+
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        &mut self.storage[self.start..self.start + self.len]
+    }
+}
+
+#[cfg(all(feature = "direct-io", target_os = "linux"))]
+mod direct {
+    use std::fs::{File, OpenOptions};
+    use std::io;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::path::Path;
+
+    /// Opens a file with `O_DIRECT`, returning the alignment its reads need, or without it if the filesystem doesn't support it.
+    pub(super) fn open(path: &Path) -> io::Result<(File, usize)> {
+        match OpenOptions::new().read(true).custom_flags(libc::O_DIRECT).open(path) {
+            // 4KiB covers the logical block size of every common device
+            Ok(file) => Ok((file, 4096)),
+            Err(e) if e.raw_os_error() == Some(libc::EINVAL) => Ok((File::open(path)?, 1)),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(all(feature = "direct-io", target_vendor = "apple"))]
+mod direct {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+
+    /// Opens a file and turns off caching of its pages, which needs no particular alignment.
+    pub(super) fn open(path: &Path) -> io::Result<(File, usize)> {
+        let file = File::open(path)?;
+        // SAFETY: only sets a flag on a descriptor we own, failure just leaves caching on
+        unsafe { libc::fcntl(file.as_raw_fd(), libc::F_NOCACHE, 1) };
+        Ok((file, 1))
+    }
+}
+
+#[cfg(all(feature = "direct-io", not(any(target_os = "linux", target_vendor = "apple"))))]
+mod direct {
+    use std::fs::File;
+    use std::io;
+    use std::path::Path;
+
+    /// Bypassing the cache isn't supported here, so this is a normal open.
+    pub(super) fn open(path: &Path) -> io::Result<(File, usize)> {
+        Ok((File::open(path)?, 1))
+    }
+}
+
+/// Hashes several files, up to `jobs` of them at a time on separate threads.
+///
+/// # Arguments
+/// * `paths` - The files to be hashed.
+/// * `jobs` - The maximum number of files to hash concurrently, 0 uses the number of available cores.
+///
+/// # Returns
+/// The hash (or IO error) of each file, in the same order as `paths`.
+pub fn hash_files<P: AsRef<Path> + Sync>(paths: &[P], jobs: usize) -> Vec<io::Result<Hash256>> {
+    let jobs = match jobs {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        jobs => jobs,
+    };
+    let jobs = jobs.min(paths.len());
+    if jobs <= 1 {
+        return paths.iter().map(hash_file).collect();
+    }
+    // threads take the next unclaimed file, so one large file doesn't hold up the rest
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, io::Result<Hash256>)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut results = Vec::new();
+                    loop {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(i) else { break };
+                        results.push((i, hash_file(path)));
+                    }
+                    results
+                })
+            })
+            .collect();
+        workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+    });
+    results.sort_unstable_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Copies a file and proves the copy is identical by reading it back.
+///
+/// The source is hashed as it is copied, then the destination is flushed to disk and hashed again. The permissions are copied too, like `fs::copy`.
+/// The read back may be served from the OS page cache rather than the disk itself, so this catches corruption on the way to the file system but not every media fault.
+///
+/// The copy is written to a temporary file next to `dst` and only renamed over it once it has been verified, so a failed copy never leaves a truncated `dst` behind.
+///
+/// # Arguments
+/// * `src` - The file to be copied.
+/// * `dst` - Where the copy is written, replacing any existing file.
+///
+/// # Returns
+/// The hash of the file, otherwise `Error::Io` or an `Error::Mismatch` with the source's hash as `expected` and the copy's as `actual`.
+/// `Error::Io` with `InvalidInput` if `dst` is `src`, or a link to it.
+pub fn copy_verified<P: AsRef<Path>, Q: AsRef<Path>>(src: P, dst: Q) -> Result<Hash256, Error> {
+    let (src, dst) = (src.as_ref(), dst.as_ref());
+    let src_file = File::open(src)?;
+    let metadata = src_file.metadata()?;
+    if same_file(src, &metadata, dst) {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "source and destination are the same file").into());
+    }
+    let name = dst.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "destination is not a file path"))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(std::format!(".{}.tmp", std::process::id()));
+    let tmp = dst.with_file_name(tmp_name);
+
+    let result = copy_verified_to(src_file, metadata, &tmp).and_then(|hash| {
+        fs::rename(&tmp, dst)?;
+        Ok(hash)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result
+}
+
+fn copy_verified_to(src: File, metadata: Metadata, tmp: &Path) -> Result<Hash256, Error> {
+    let mut tmp_file = fs::OpenOptions::new().write(true).create_new(true).open(tmp)?;
+    let (bytes_read, expected) = copy_and_hash(&mut io::BufReader::with_capacity(64 * 1024, src), &mut tmp_file)?;
+    tmp_file.set_permissions(metadata.permissions())?;
+    tmp_file.sync_all()?;
+    drop(tmp_file);
+    let actual = hash_file(tmp)?;
+    if actual == expected {
+        Ok(expected)
+    } else {
+        Err(Error::Mismatch { expected, actual, bytes_read })
+    }
+}
+
+/// Whether `dst` exists and is the file `src` already refers to, through the same path, a symlink or a hard link.
+#[cfg(unix)]
+fn same_file(_: &Path, src_metadata: &Metadata, dst: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(dst).is_ok_and(|dst| (dst.dev(), dst.ino()) == (src_metadata.dev(), src_metadata.ino()))
+}
+
+/// Whether `dst` exists and is the file `src` already refers to, through the same path or a symlink.
+// the volume serial and file index aren't available on stable, so hard links aren't detected here
+#[cfg(not(unix))]
+fn same_file(src: &Path, _: &Metadata, dst: &Path) -> bool {
+    match (fs::canonicalize(src), fs::canonicalize(dst)) {
+        (Ok(src), Ok(dst)) => src == dst,
+        _ => false,
+    }
+}
+
+#[cfg(all(unix, feature = "sparse"))]
+mod sparse {
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::fs::FileExt;
+    use std::os::unix::io::AsRawFd;
+    use std::vec;
+
+    use crate::{Hash256, Sha256};
+
+    /// Finds the next data (`SEEK_DATA`) or hole (`SEEK_HOLE`) at or after `offset`, `None` if there is none before EOF.
+    fn seek(file: &File, offset: u64, whence: libc::c_int) -> io::Result<Option<u64>> {
+        let pos = unsafe { libc::lseek(file.as_raw_fd(), offset as libc::off_t, whence) };
+        if pos >= 0 {
+            return Ok(Some(pos as u64));
+        }
+        let e = io::Error::last_os_error();
+        match e.raw_os_error() {
+            Some(libc::ENXIO) => Ok(None),
+            _ => Err(e),
+        }
+    }
+
+    pub(super) fn hash_file(file: File) -> io::Result<Hash256> {
+        let size = file.metadata()?.len();
+        let mut sha256 = Sha256::new();
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut pos = 0;
+        while pos < size {
+            let data = match seek(&file, pos, libc::SEEK_DATA) {
+                Ok(data) => data.unwrap_or(size).min(size),
+                // the filesystem does not report holes, so treat the rest of the file as data
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) => pos,
+                Err(e) => return Err(e),
+            };
+            sha256.update_repeated(0, data - pos);
+            pos = data;
+            if pos == size {
+                break;
+            }
+            let hole = match seek(&file, pos, libc::SEEK_HOLE) {
+                Ok(hole) => hole.unwrap_or(size).min(size),
+                Err(e) if e.raw_os_error() == Some(libc::EINVAL) => size,
+                Err(e) => return Err(e),
+            };
+            while pos < hole {
+                let len = buf.len().min((hole - pos) as usize);
+                let n = match file.read_at(&mut buf[..len], pos) {
+                    Ok(0) => return Ok(Hash256::new(sha256.finalize())), // truncated while hashing
+                    Ok(n) => n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                sha256.update(&buf[..n]);
+                pos += n as u64;
+            }
+        }
+        Ok(Hash256::new(sha256.finalize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_vectored() {
+        let msg: Vec<u8> = (0..300u32).map(|i| (i * 7) as u8).collect();
+        let (a, rest) = msg.split_at(10);
+        let (b, c) = rest.split_at(100);
+        let bufs = [IoSlice::new(a), IoSlice::new(&[]), IoSlice::new(b), IoSlice::new(c)];
+        let mut sha256 = Sha256::new();
+        sha256.update_vectored(&bufs);
+        assert_eq!(sha256.finalize(), Sha256::new().digest(&msg));
+        let mut sha256 = Sha256::new();
+        assert_eq!(sha256.write_vectored(&bufs).unwrap(), msg.len());
+        assert_eq!(sha256.finalize(), Sha256::new().digest(&msg));
+    }
+
+    #[test]
+    fn adapters_hash_what_passes_through() {
+        let msg = [9u8; 1000];
+        let expected = Hash256::new(Sha256::new().digest(&msg));
+
+        let mut reader = HashingReader::new(&msg[..]);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+        assert_eq!(reader.finalize().1, expected);
+
+        let mut writer = HashingWriter::new(Vec::new());
+        writer.write_all(&msg).unwrap();
+        let (out, hash) = writer.finalize();
+        assert_eq!(out, msg);
+        assert_eq!(hash, expected);
+
+        assert_eq!(hash_reader(&msg[..]).unwrap(), expected);
+        assert_eq!(hash_bufread(io::BufReader::with_capacity(7, &msg[..])).unwrap(), expected);
+    }
+
+    #[test]
+    fn copy_and_hash_copies_and_hashes() {
+        let msg = [1u8; 20_000];
+        let mut out = Vec::new();
+        let (n, hash) = copy_and_hash(&mut &msg[..], &mut out).unwrap();
+        assert_eq!(n, 20_000);
+        assert_eq!(out, msg);
+        assert_eq!(hash, Hash256::new(Sha256::new().digest(&msg)));
+    }
+
+    #[test]
+    fn verify_reader_reports_mismatch() {
+        let msg = [5u8; 3000];
+        let expected = Hash256::new(Sha256::new().digest(&msg));
+        assert!(verify_reader(&msg[..], expected).is_ok());
+        match verify_reader(&msg[..2999], expected) {
+            Err(Error::Mismatch { expected: e, actual, bytes_read }) => {
+                assert_eq!(e, expected);
+                assert_eq!(actual, Hash256::new(Sha256::new().digest(&msg[..2999])));
+                assert_eq!(bytes_read, 2999);
+            }
+            other => panic!("expected a mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn hash_file_handles_holes() {
+        let path = std::env::temp_dir().join(std::format!("sha_256-sparse-{}", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        file.write_all(b"start").unwrap();
+        file.set_len(3 << 20).unwrap();
+        io::Seek::seek(&mut file, io::SeekFrom::End(-3)).unwrap();
+        file.write_all(b"end").unwrap();
+        drop(file);
+        let hash = hash_file(&path);
+        let contents = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(hash.unwrap(), Hash256::new(Sha256::new().digest(&contents)));
+    }
+
+    #[test]
+    fn hash_file_with_matches_hash_file() {
+        let path = std::env::temp_dir().join(std::format!("sha_256-hash_file_with-{}", std::process::id()));
+        let msg: Vec<u8> = (0..300_000u32).map(|i| (i * 31 % 251) as u8).collect();
+        std::fs::write(&path, &msg).unwrap();
+        let expected = Hash256::new(Sha256::new().digest(&msg));
+        // chunks which don't divide the file, with and without read ahead
+        for options in [FileHashOptions::new(), FileHashOptions::new().chunk_size(1000), FileHashOptions::new().chunk_size(7).read_ahead(1), FileHashOptions::new().read_ahead(3)] {
+            assert_eq!(hash_file_with(&path, options).unwrap(), expected, "{:?}", options);
+        }
+        #[cfg(feature = "direct-io")]
+        assert_eq!(hash_file_with(&path, FileHashOptions::new().chunk_size(5000).read_ahead(2).direct(true)).unwrap(), expected);
+        std::fs::write(&path, b"").unwrap();
+        assert_eq!(hash_file_with(&path, FileHashOptions::new().read_ahead(2)).unwrap(), Hash256::new(Sha256::new().digest(b"")));
+        std::fs::remove_file(&path).unwrap();
+
+        let metadata = std::fs::metadata("Cargo.toml").unwrap();
+        assert_eq!(FileHashOptions::new().chunk_size(10).resolve_chunk_size(&metadata), 10);
+        assert!(FileHashOptions::new().resolve_chunk_size(&metadata) <= (metadata.len() as usize).next_multiple_of(4096));
+        assert_eq!(FileHashOptions::new().resolve_read_ahead(&metadata), 0);
+    }
+
+    #[test]
+    fn hash_files_keeps_order() {
+        let dir = std::env::temp_dir();
+        let paths: Vec<_> = (0..5u8).map(|i| dir.join(std::format!("sha_256-hash_files-{}-{}", std::process::id(), i))).collect();
+        for (i, path) in paths.iter().enumerate() {
+            std::fs::write(path, std::vec![i as u8; i * 1000]).unwrap();
+        }
+        let missing = dir.join("sha_256-hash_files-missing");
+        let mut all = paths.clone();
+        all.push(missing);
+        for jobs in [0, 1, 3] {
+            let results = hash_files(&all, jobs);
+            for (i, result) in results[..5].iter().enumerate() {
+                assert_eq!(*result.as_ref().unwrap(), Hash256::new(Sha256::new().digest(&std::vec![i as u8; i * 1000])));
+            }
+            assert!(results[5].is_err());
+        }
+        for path in paths {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn copy_verified_copies() {
+        let src = std::env::temp_dir().join(std::format!("sha_256-copy-src-{}", std::process::id()));
+        let dst = src.with_file_name(std::format!("sha_256-copy-dst-{}", std::process::id()));
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&src, &data).unwrap();
+        let hash = copy_verified(&src, &dst).unwrap();
+        assert_eq!(hash, Hash256::new(Sha256::new().digest(&data)));
+        assert_eq!(std::fs::read(&dst).unwrap(), data);
+        // copying a file onto itself is refused rather than truncating it
+        let err = copy_verified(&src, &src).unwrap_err();
+        assert!(matches!(err, Error::Io(ref e) if e.kind() == io::ErrorKind::InvalidInput));
+        assert_eq!(std::fs::read(&src).unwrap(), data);
+        #[cfg(unix)]
+        {
+            let link = src.with_file_name(std::format!("sha_256-copy-link-{}", std::process::id()));
+            std::fs::hard_link(&src, &link).unwrap();
+            assert!(matches!(copy_verified(&src, &link), Err(Error::Io(_))));
+            assert_eq!(std::fs::read(&src).unwrap(), data);
+            std::fs::remove_file(&link).unwrap();
+        }
+        std::fs::remove_file(&src).unwrap();
+        assert!(matches!(copy_verified(&src, &dst), Err(Error::Io(_))));
+        // the earlier copy is still intact and no temporary file is left behind
+        assert_eq!(std::fs::read(&dst).unwrap(), data);
+        let tmp = dst.with_file_name(std::format!(".sha_256-copy-dst-{}.{}.tmp", std::process::id(), std::process::id()));
+        assert!(!tmp.exists());
+        std::fs::remove_file(&dst).unwrap();
+    }
+
+    #[test]
+    fn hash_file_matches_digest() {
+        let msg = [7u8; 100_000];
+        let path = std::env::temp_dir().join(std::format!("sha_256-hash_file-{}", std::process::id()));
+        std::fs::write(&path, msg).unwrap();
+        let hash = hash_file(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(hash.unwrap(), Hash256::new(Sha256::new().digest(&msg)));
+    }
+}
@@ -1,9 +1,210 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::convert::TryInto;
 use core::iter::Iterator;
 
+use primitives::{big_sigma0, big_sigma1, ch, maj, small_sigma0, small_sigma1};
+
+#[cfg(feature = "alloc")]
+mod abbrev;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+#[cfg(feature = "archive")]
+mod archive;
+mod backend;
+mod bitcoin;
+#[cfg(feature = "borsh")]
+mod borsh_impl;
+#[cfg(feature = "pem")]
+mod cert;
+#[cfg(feature = "alloc")]
+mod chunk_index;
+mod compact;
+#[cfg(feature = "digest")]
+pub mod compat;
+#[cfg(feature = "std")]
+mod dedup;
+#[cfg(feature = "diesel")]
+mod diesel_impl;
+#[cfg(feature = "digest")]
+mod digest_impl;
+#[cfg(feature = "std")]
+pub mod dir;
+#[cfg(feature = "embedded-io")]
+mod embedded;
+mod error;
+#[cfg(all(feature = "esp", any(target_arch = "xtensa", target_arch = "riscv32")))]
+mod esp;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod hash256;
+mod hashable;
+mod hex;
+pub mod hkdf;
+mod hmac;
+#[cfg(feature = "http")]
+pub mod http;
+#[cfg(feature = "std")]
+mod io;
+#[cfg(feature = "alloc")]
+mod manifest;
+// built on the tree module's leaf and node hashes
+#[cfg(all(feature = "alloc", not(target_pointer_width = "16")))]
+pub mod merkle;
+pub mod mining;
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+#[cfg(feature = "alloc")]
+mod multi;
+#[cfg(feature = "multihash")]
+pub mod multihash;
+#[cfg(feature = "node")]
+pub mod node;
+mod observer;
+#[cfg(feature = "std")]
+mod oci;
+#[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+mod os_crypto;
+#[cfg(feature = "alloc")]
+mod pkce;
+mod placement;
+mod pow;
+pub mod primitives;
+#[cfg(feature = "python")]
+pub mod python;
+mod reference;
+mod repeated;
+#[cfg(feature = "research")]
+pub mod research;
+#[cfg(feature = "std")]
+mod resumable;
+#[cfg(feature = "rand_core")]
+mod rng;
+mod salted;
+mod self_test;
+#[cfg(feature = "schemars")]
+mod schemars_impl;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod sha224;
+mod sha512;
+#[cfg(feature = "sqlx")]
+mod sqlx_impl;
+mod sri;
+#[cfg(any(test, all(feature = "esp", target_arch = "riscv32")))]
+mod staging;
+mod state;
+#[cfg(feature = "stream")]
+mod stream;
+mod text;
+#[cfg(feature = "timing-tests")]
+pub mod timing;
+#[cfg(feature = "tower")]
+pub mod tower;
+// the 1MiB chunk size doesn't fit in a 16-bit usize, and a buffer that small is only ever one chunk anyway
+#[cfg(not(target_pointer_width = "16"))]
+pub mod tree;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod wipe;
+#[cfg(feature = "std")]
+mod worker;
+
+#[cfg(feature = "alloc")]
+pub use abbrev::Abbreviations;
+#[cfg(feature = "arbitrary")]
+pub use arbitrary_impl::HashScript;
+#[cfg(feature = "archive")]
+pub use archive::{hash_archive, ArchiveEntry};
+pub use backend::{Backend, BackendKind, SelectableSha256};
+pub use bitcoin::{sha256d, BlockHeader, HeaderChain};
+#[cfg(feature = "pem")]
+pub use cert::{cert_fingerprint, fingerprint_colon_hex, fingerprint_hex, parse_fingerprint};
+#[cfg(feature = "alloc")]
+pub use chunk_index::{ChunkIndex, ChunkLocation};
+#[cfg(feature = "std")]
+pub use dedup::{find_duplicates, DuplicateGroup, DuplicateReport};
+#[cfg(feature = "digest")]
+pub use digest_impl::Sha256Core;
+#[cfg(feature = "std")]
+pub use dir::{hash_directory, manifest_directory, DirHashOptions, SymlinkPolicy};
+#[cfg(feature = "embedded-io")]
+pub use embedded::{EmbeddedHashingReader, EmbeddedHashingWriter};
+pub use error::Error;
+#[cfg(all(feature = "esp", any(target_arch = "xtensa", target_arch = "riscv32")))]
+pub use esp::EspSha256;
+#[cfg(all(feature = "esp", target_arch = "riscv32"))]
+pub use esp::EspSha256Dma;
+#[cfg(feature = "rkyv")]
+pub use hash256::ArchivedHash256;
+pub use hash256::Hash256;
+pub use hashable::Hashable;
+pub use hex::HexDigestWriter;
+pub use hmac::{hmac_sha256, HmacKey, HmacSha256};
+#[cfg(feature = "std")]
+pub use hmac::LockedHmacKey;
+#[cfg(feature = "std")]
+pub use io::{copy_and_hash, copy_verified, hash_bufread, hash_file, hash_file_with, hash_files, hash_reader, verify_reader, FileHashOptions, HashingReader, HashingWriter};
+#[cfg(feature = "alloc")]
+pub use manifest::{parse_manifest, verify_manifest, write_manifest, write_signed_manifest, ManifestEntry, ManifestFormat};
+#[cfg(feature = "std")]
+pub use multi::BroadcastWriter;
+#[cfg(feature = "alloc")]
+pub use multi::{ChunkedSha256, DynHasher, MultiHasher};
+pub use observer::ObservedSha256;
+#[cfg(feature = "std")]
+pub use oci::{oci_digest, parse_oci_digest, LayerVerifier};
+// the UniFFI scaffolding expects its tag type at the crate root
+#[cfg(feature = "uniffi")]
+use mobile::UniFfiTag;
+#[cfg(all(feature = "os-crypto", any(windows, target_vendor = "apple")))]
+pub use os_crypto::OsSha256;
+#[cfg(feature = "alloc")]
+pub use pkce::{pkce_s256, pkce_verify_s256};
+#[cfg(feature = "std")]
+pub use placement::rendezvous_weighted;
+pub use placement::{rendezvous, rendezvous_score, shard_for};
+pub use pow::{leading_zero_bits, Target};
+pub use reference::ReferenceSha256;
+pub use repeated::{digest_repeated, digest_runs};
+#[cfg(feature = "std")]
+pub use resumable::ResumableFileHash;
+#[cfg(feature = "rand_core")]
+pub use rng::Sha256Rng;
+#[cfg(feature = "rand_core")]
+pub use salted::hash_with_generated_salt;
+pub use salted::{hash_salted, Salt, SALT_LEN};
+#[cfg(feature = "std")]
+pub use self_test::register_known_answers;
+pub use self_test::{self_test, self_test_with, KnownAnswer, BUILTIN_KNOWN_ANSWERS};
+#[cfg(feature = "serde")]
+pub use serde_impl::{BytesHash256, HexHash256};
+pub use sha224::{sha224, Sha224};
+pub use sha512::{sha384, sha512, sha512_224, sha512_256, Sha384, Sha512, Sha512_224, Sha512_256};
+pub use state::SavedState;
+#[cfg(feature = "stream")]
+pub use stream::hash_stream;
+pub use text::FmtHasher;
+#[cfg(feature = "std")]
+pub use tree::tree_hash_parallel;
+#[cfg(not(target_pointer_width = "16"))]
+pub use tree::{tree_hash, TREE_CHUNK_SIZE};
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use uring::{hash_file_uring, hash_file_uring_with};
+#[cfg(feature = "alloc")]
+pub use wipe::digest_consuming;
+#[cfg(feature = "std")]
+pub use worker::spawn_hasher;
+
 /// A structure representing the SHA-256 hash algorithm.
+#[derive(Clone)]
 pub struct Sha256 {
     w: [u32; 64], // words for the message schedule
     // the 8 hash values
@@ -15,6 +216,11 @@ pub struct Sha256 {
     h5: u32,
     h6: u32,
     h7: u32,
+    iv: [u32; 8], // the initial hash values, restored by reset
+    // streaming state
+    buf: [u8; 64], // bytes of a partial chunk which have not been processed yet
+    buf_len: usize, // how many bytes of buf are in use
+    len: u64, // total number of message bytes absorbed so far
 }
 
 impl Default for Sha256 {
@@ -29,19 +235,49 @@ impl Sha256 {
     /// # Returns
     /// A new `Sha256` instance with initialized state.
     pub fn new() -> Self {
+        Self::with_iv(H)
+    }
+
+    /// Creates a hasher which starts from the given initial hash values instead of SHA-256's, for variants built on the same compression function.
+    ///
+    /// E.g. SHA-224 style truncations, tree hashing modes which give each kind of node its own IV, or protocol specific variants.
+    /// The padding is unchanged, and `reset` (and so `finalize_reset` and `digest`) goes back to `iv` rather than the standard values.
+    ///
+    /// ```
+    /// use sha_256::Sha256;
+    /// // the SHA-224 IV, SHA-224 being this truncated to 28 bytes
+    /// let iv = [0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4];
+    /// let hash = Sha256::with_iv(iv).chain(b"abc").finalize();
+    /// assert_eq!(hash[..28], sha_256::sha224(b"abc"));
+    /// ```
+    ///
+    /// # Arguments
+    /// * `iv` - The 8 initial hash values.
+    pub fn with_iv(iv: [u32; 8]) -> Self {
         Self {
             w: [0; 64],
-            h0: 0,
-            h1: 0,
-            h2: 0,
-            h3: 0,
-            h4: 0,
-            h5: 0,
-            h6: 0,
-            h7: 0,
+            h0: iv[0],
+            h1: iv[1],
+            h2: iv[2],
+            h3: iv[3],
+            h4: iv[4],
+            h5: iv[5],
+            h6: iv[6],
+            h7: iv[7],
+            iv,
+            buf: [0; 64],
+            buf_len: 0,
+            len: 0,
         }
     }
 
+    /// Resets the hasher to its initial state, discarding any data passed to `update`.
+    pub fn reset(&mut self) {
+        [self.h0, self.h1, self.h2, self.h3, self.h4, self.h5, self.h6, self.h7] = self.iv;
+        self.buf_len = 0;
+        self.len = 0;
+    }
+
     /// Sets a chunk of the message for SHA-256 processing.
     ///
     /// # Arguments
@@ -49,17 +285,31 @@ impl Sha256 {
     /// * `index` - The index of the chunk to be set.
     #[inline(always)]
     fn set_chunk(&mut self, msg: &[u8], index: usize) {
-        unsafe {
-            // message entirely saturates this chunk, so straight-up copy the bytes into u32's
-            let start = index * 64;
-            let end = start + 64;
-            let slice = &msg[start..end];
-            for (i, chunk) in slice.chunks_exact(4).enumerate() {
-                self.w[i] = u32::from_be_bytes(chunk.try_into().unwrap());
-            }
+        // message entirely saturates this chunk, so straight-up copy the bytes into u32's
+        let start = index * 64;
+        let end = start + 64;
+        self.set_block(&msg[start..end]);
+    }
+
+    /// Copies a 64 byte block into the first 16 words of the message schedule.
+    #[inline(always)]
+    fn set_block(&mut self, block: &[u8]) {
+        if compact::ENABLED {
+            compact::set_block(&mut self.w, block);
+            return;
+        }
+        for (i, chunk) in block.chunks_exact(4).enumerate() {
+            self.w[i] = u32::from_be_bytes(chunk.try_into().unwrap());
         }
     }
 
+    /// Processes a single 64 byte block, updating the hash values.
+    #[inline(always)]
+    fn compress_block(&mut self, block: &[u8]) {
+        self.set_block(block);
+        self.process_chunk();
+    }
+
     #[inline(always)]
     fn set_chunk_last(&mut self, msg: &[u8], index: usize) {
         // copy the remaining msg into the w array
@@ -87,7 +337,7 @@ impl Sha256 {
 
         // any u32s after the message but before the last 2 u32s are 0
         let i = n_u32s + 1;
-        self.set_chunk_padding_zeros(msg, i);
+        self.set_chunk_padding_zeros(i);
 
         // if the message length is <=55 bytes and >=1 byte, the padding will fit into the last chunk
         // a message of <=55 bytes will have space for the length field in this chunk
@@ -96,7 +346,7 @@ impl Sha256 {
         if i <= 14 {
             // space for length field
             // remaining message fits into the last chunk with padding included.
-            self.set_chunk_msg_len(msg);
+            self.set_chunk_msg_len(msg.len() as u64);
         } else if i == 15 {
             // else no space for length field, so will be in next chunk
             // set where length field would have been to 0's
@@ -105,10 +355,9 @@ impl Sha256 {
     }
 
     #[inline(always)]
-    fn set_chunk_msg_len(&mut self, msg: &[u8]) {
+    fn set_chunk_msg_len(&mut self, msg_len: u64) {
         // the last 2 u32s are the length of the message in bits
-        let msg_len = msg.len();
-        let len = (msg_len * 8) as u64;
+        let len = msg_len.wrapping_mul(8);
         let len_upper_bytes = ((len >> 32) as u32).to_be_bytes();
         let len_lower_bytes = ((len & 0xFFFFFFFF) as u32).to_be_bytes();
         self.w[14] = u32::from_be_bytes(len_upper_bytes);
@@ -116,7 +365,7 @@ impl Sha256 {
     }
 
     #[inline(always)]
-    fn set_chunk_padding_zeros(&mut self, msg: &[u8], start: usize) {
+    fn set_chunk_padding_zeros(&mut self, start: usize) {
         // the padding is all zeros except for the last 2 u32s which are the length of the message in bits
         for i in start..14 {
             self.w[i] = 0;
@@ -132,286 +381,303 @@ impl Sha256 {
     /// Processes a single chunk of the message using the SHA-256 algorithm.
     #[inline(always)]
     fn process_chunk(&mut self) {
-        unsafe {
-            // Extend w to 64 words
-            // partially unrolled loop, 8 iterations at a time
-            // why 8? gets a reasonable amount of variable reuse through the indexing of the w array, but doesn't unroll the loop too a point where the code size is too large for the gains
-            for i in (16..64).step_by(8) {
-                // could reuse repeats of variables, but we don't because benchmarks show it's slower. I _think_ it's something to do with cache hits for array elements being faster than reusing variables
-
-                // First iteration: i
-                let w15_0 = self.w[i - 15];
-                let s0_0 = w15_0.rotate_right(7) ^ w15_0.rotate_right(18) ^ (w15_0 >> 3);
-                let w2_0 = self.w[i - 2];
-                let s1_0 = w2_0.rotate_right(17) ^ w2_0.rotate_right(19) ^ (w2_0 >> 10);
-                self.w[i] = self.w[i - 16]
-                    .wrapping_add(s0_0)
-                    .wrapping_add(self.w[i - 7])
-                    .wrapping_add(s1_0);
-
-                // Second iteration: i + 1
-                let w15_1 = self.w[i - 14];
-                let s0_1 = w15_1.rotate_right(7) ^ w15_1.rotate_right(18) ^ (w15_1 >> 3);
-                let w2_1 = self.w[i - 1];
-                let s1_1 = w2_1.rotate_right(17) ^ w2_1.rotate_right(19) ^ (w2_1 >> 10);
-                self.w[i + 1] = self.w[i - 15]
-                    .wrapping_add(s0_1)
-                    .wrapping_add(self.w[i - 6])
-                    .wrapping_add(s1_1);
-
-                // Third iteration: i + 2
-                let w15_2 = self.w[i - 13];
-                let s0_2 = w15_2.rotate_right(7) ^ w15_2.rotate_right(18) ^ (w15_2 >> 3);
-                let w2_2 = self.w[i];
-                let s1_2 = w2_2.rotate_right(17) ^ w2_2.rotate_right(19) ^ (w2_2 >> 10);
-                self.w[i + 2] = self.w[i - 14]
-                    .wrapping_add(s0_2)
-                    .wrapping_add(self.w[i - 5])
-                    .wrapping_add(s1_2);
-
-                // Fourth iteration: i + 3
-                let w15_3 = self.w[i - 12];
-                let s0_3 = w15_3.rotate_right(7) ^ w15_3.rotate_right(18) ^ (w15_3 >> 3);
-                let w2_3 = self.w[i + 1];
-                let s1_3 = w2_3.rotate_right(17) ^ w2_3.rotate_right(19) ^ (w2_3 >> 10);
-                self.w[i + 3] = self.w[i - 13]
-                    .wrapping_add(s0_3)
-                    .wrapping_add(self.w[i - 4])
-                    .wrapping_add(s1_3);
-
-                // Fifth iteration: i + 4
-                let w15_4 = self.w[i - 11];
-                let s0_4 = w15_4.rotate_right(7) ^ w15_4.rotate_right(18) ^ (w15_4 >> 3);
-                let w2_4 = self.w[i + 2];
-                let s1_4 = w2_4.rotate_right(17) ^ w2_4.rotate_right(19) ^ (w2_4 >> 10);
-                self.w[i + 4] = self.w[i - 12]
-                    .wrapping_add(s0_4)
-                    .wrapping_add(self.w[i - 3])
-                    .wrapping_add(s1_4);
-
-                // Sixth iteration: i + 5
-                let w15_5 = self.w[i - 10];
-                let s0_5 = w15_5.rotate_right(7) ^ w15_5.rotate_right(18) ^ (w15_5 >> 3);
-                let w2_5 = self.w[i + 3];
-                let s1_5 = w2_5.rotate_right(17) ^ w2_5.rotate_right(19) ^ (w2_5 >> 10);
-                self.w[i + 5] = self.w[i - 11]
-                    .wrapping_add(s0_5)
-                    .wrapping_add(self.w[i - 2])
-                    .wrapping_add(s1_5);
-
-                // Seventh iteration: i + 6
-                let w15_6 = self.w[i - 9];
-                let s0_6 = w15_6.rotate_right(7) ^ w15_6.rotate_right(18) ^ (w15_6 >> 3);
-                let w2_6 = self.w[i + 4];
-                let s1_6 = w2_6.rotate_right(17) ^ w2_6.rotate_right(19) ^ (w2_6 >> 10);
-                self.w[i + 6] = self.w[i - 10]
-                    .wrapping_add(s0_6)
-                    .wrapping_add(self.w[i - 1])
-                    .wrapping_add(s1_6);
-
-                // Eighth iteration: i + 7
-                let w15_7 = self.w[i - 8];
-                let s0_7 = w15_7.rotate_right(7) ^ w15_7.rotate_right(18) ^ (w15_7 >> 3);
-                let w2_7 = self.w[i + 5];
-                let s1_7 = w2_7.rotate_right(17) ^ w2_7.rotate_right(19) ^ (w2_7 >> 10);
-                self.w[i + 7] = self.w[i - 9]
-                    .wrapping_add(s0_7)
-                    .wrapping_add(self.w[i])
-                    .wrapping_add(s1_7);
-            }
+        self.expand_schedule();
+        self.compress_rounds();
+    }
 
-            let mut a = self.h0;
-            let mut b = self.h1;
-            let mut c = self.h2;
-            let mut d = self.h3;
-            let mut e = self.h4;
-            let mut f = self.h5;
-            let mut g = self.h6;
-            let mut h = self.h7;
-
-            // partially unrolled loop, 8 iterations at a time
-            for i in (0..64).step_by(8) {
-                // First iteration: i
-                let s1_0 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_0 = (e & f) ^ ((!e) & g);
-                let temp1_0 = h
-                    .wrapping_add(s1_0)
-                    .wrapping_add(ch_0)
-                    .wrapping_add(K[i])
-                    .wrapping_add(self.w[i]);
-                let s0_0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_0 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_0 = s0_0.wrapping_add(maj_0);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_0);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_0.wrapping_add(temp2_0);
-
-                // Second iteration: i + 1
-                let s1_1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_1 = (e & f) ^ ((!e) & g);
-                let temp1_1 = h
-                    .wrapping_add(s1_1)
-                    .wrapping_add(ch_1)
-                    .wrapping_add(K[i + 1])
-                    .wrapping_add(self.w[i + 1]);
-                let s0_1 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_1 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_1 = s0_1.wrapping_add(maj_1);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_1);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_1.wrapping_add(temp2_1);
-
-                // Third iteration: i + 2
-                let s1_2 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_2 = (e & f) ^ ((!e) & g);
-                let temp1_2 = h
-                    .wrapping_add(s1_2)
-                    .wrapping_add(ch_2)
-                    .wrapping_add(K[i + 2])
-                    .wrapping_add(self.w[i + 2]);
-                let s0_2 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_2 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_2 = s0_2.wrapping_add(maj_2);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_2);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_2.wrapping_add(temp2_2);
-
-                // Fourth iteration: i + 3
-                let s1_3 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_3 = (e & f) ^ ((!e) & g);
-                let temp1_3 = h
-                    .wrapping_add(s1_3)
-                    .wrapping_add(ch_3)
-                    .wrapping_add(K[i + 3])
-                    .wrapping_add(self.w[i + 3]);
-                let s0_3 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_3 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_3 = s0_3.wrapping_add(maj_3);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_3);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_3.wrapping_add(temp2_3);
-
-                // Fifth iteration: i + 4
-                let s1_4 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_4 = (e & f) ^ ((!e) & g);
-                let temp1_4 = h
-                    .wrapping_add(s1_4)
-                    .wrapping_add(ch_4)
-                    .wrapping_add(K[i + 4])
-                    .wrapping_add(self.w[i + 4]);
-                let s0_4 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_4 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_4 = s0_4.wrapping_add(maj_4);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_4);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_4.wrapping_add(temp2_4);
-
-                // Sixth iteration: i + 5
-                let s1_5 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_5 = (e & f) ^ ((!e) & g);
-                let temp1_5 = h
-                    .wrapping_add(s1_5)
-                    .wrapping_add(ch_5)
-                    .wrapping_add(K[i + 5])
-                    .wrapping_add(self.w[i + 5]);
-                let s0_5 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_5 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_5 = s0_5.wrapping_add(maj_5);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_5);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_5.wrapping_add(temp2_5);
-
-                // Seventh iteration: i + 6
-                let s1_6 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_6 = (e & f) ^ ((!e) & g);
-                let temp1_6 = h
-                    .wrapping_add(s1_6)
-                    .wrapping_add(ch_6)
-                    .wrapping_add(K[i + 6])
-                    .wrapping_add(self.w[i + 6]);
-                let s0_6 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_6 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_6 = s0_6.wrapping_add(maj_6);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_6);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_6.wrapping_add(temp2_6);
-
-                // Eighth iteration: i + 7
-                let s1_7 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
-                let ch_7 = (e & f) ^ ((!e) & g);
-                let temp1_7 = h
-                    .wrapping_add(s1_7)
-                    .wrapping_add(ch_7)
-                    .wrapping_add(K[i + 7])
-                    .wrapping_add(self.w[i + 7]);
-                let s0_7 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
-                let maj_7 = (a & b) ^ (a & c) ^ (b & c);
-                let temp2_7 = s0_7.wrapping_add(maj_7);
-
-                h = g;
-                g = f;
-                f = e;
-                e = d.wrapping_add(temp1_7);
-                d = c;
-                c = b;
-                b = a;
-                a = temp1_7.wrapping_add(temp2_7);
-            }
+    /// Extends the first 16 words of the message schedule to all 64.
+    #[inline(always)]
+    fn expand_schedule(&mut self) {
+        if compact::ENABLED {
+            compact::expand_schedule(&mut self.w);
+            return;
+        }
+        // Extend w to 64 words
+        // partially unrolled loop, 8 iterations at a time
+        // why 8? gets a reasonable amount of variable reuse through the indexing of the w array, but doesn't unroll the loop too a point where the code size is too large for the gains
+        for i in (16..64).step_by(8) {
+            // could reuse repeats of variables, but we don't because benchmarks show it's slower. I _think_ it's something to do with cache hits for array elements being faster than reusing variables
 
-            self.h0 = self.h0.wrapping_add(a);
-            self.h1 = self.h1.wrapping_add(b);
-            self.h2 = self.h2.wrapping_add(c);
-            self.h3 = self.h3.wrapping_add(d);
-            self.h4 = self.h4.wrapping_add(e);
-            self.h5 = self.h5.wrapping_add(f);
-            self.h6 = self.h6.wrapping_add(g);
-            self.h7 = self.h7.wrapping_add(h);
+            // First iteration: i
+            let w15_0 = self.w[i - 15];
+            let s0_0 = small_sigma0(w15_0);
+            let w2_0 = self.w[i - 2];
+            let s1_0 = small_sigma1(w2_0);
+            self.w[i] = self.w[i - 16]
+                .wrapping_add(s0_0)
+                .wrapping_add(self.w[i - 7])
+                .wrapping_add(s1_0);
+
+            // Second iteration: i + 1
+            let w15_1 = self.w[i - 14];
+            let s0_1 = small_sigma0(w15_1);
+            let w2_1 = self.w[i - 1];
+            let s1_1 = small_sigma1(w2_1);
+            self.w[i + 1] = self.w[i - 15]
+                .wrapping_add(s0_1)
+                .wrapping_add(self.w[i - 6])
+                .wrapping_add(s1_1);
+
+            // Third iteration: i + 2
+            let w15_2 = self.w[i - 13];
+            let s0_2 = small_sigma0(w15_2);
+            let w2_2 = self.w[i];
+            let s1_2 = small_sigma1(w2_2);
+            self.w[i + 2] = self.w[i - 14]
+                .wrapping_add(s0_2)
+                .wrapping_add(self.w[i - 5])
+                .wrapping_add(s1_2);
+
+            // Fourth iteration: i + 3
+            let w15_3 = self.w[i - 12];
+            let s0_3 = small_sigma0(w15_3);
+            let w2_3 = self.w[i + 1];
+            let s1_3 = small_sigma1(w2_3);
+            self.w[i + 3] = self.w[i - 13]
+                .wrapping_add(s0_3)
+                .wrapping_add(self.w[i - 4])
+                .wrapping_add(s1_3);
+
+            // Fifth iteration: i + 4
+            let w15_4 = self.w[i - 11];
+            let s0_4 = small_sigma0(w15_4);
+            let w2_4 = self.w[i + 2];
+            let s1_4 = small_sigma1(w2_4);
+            self.w[i + 4] = self.w[i - 12]
+                .wrapping_add(s0_4)
+                .wrapping_add(self.w[i - 3])
+                .wrapping_add(s1_4);
+
+            // Sixth iteration: i + 5
+            let w15_5 = self.w[i - 10];
+            let s0_5 = small_sigma0(w15_5);
+            let w2_5 = self.w[i + 3];
+            let s1_5 = small_sigma1(w2_5);
+            self.w[i + 5] = self.w[i - 11]
+                .wrapping_add(s0_5)
+                .wrapping_add(self.w[i - 2])
+                .wrapping_add(s1_5);
+
+            // Seventh iteration: i + 6
+            let w15_6 = self.w[i - 9];
+            let s0_6 = small_sigma0(w15_6);
+            let w2_6 = self.w[i + 4];
+            let s1_6 = small_sigma1(w2_6);
+            self.w[i + 6] = self.w[i - 10]
+                .wrapping_add(s0_6)
+                .wrapping_add(self.w[i - 1])
+                .wrapping_add(s1_6);
+
+            // Eighth iteration: i + 7
+            let w15_7 = self.w[i - 8];
+            let s0_7 = small_sigma0(w15_7);
+            let w2_7 = self.w[i + 5];
+            let s1_7 = small_sigma1(w2_7);
+            self.w[i + 7] = self.w[i - 9]
+                .wrapping_add(s0_7)
+                .wrapping_add(self.w[i])
+                .wrapping_add(s1_7);
         }
     }
 
-    /// Computes the SHA-256 digest of the given message.
+    /// Runs the 64 rounds over an expanded message schedule, updating the hash values. Leaves the schedule untouched.
+    #[inline(always)]
+    fn compress_rounds(&mut self) {
+        if compact::ENABLED {
+            compact::compress_rounds(self);
+            return;
+        }
+        let mut a = self.h0;
+        let mut b = self.h1;
+        let mut c = self.h2;
+        let mut d = self.h3;
+        let mut e = self.h4;
+        let mut f = self.h5;
+        let mut g = self.h6;
+        let mut h = self.h7;
+
+        // partially unrolled loop, 8 iterations at a time
+        for i in (0..64).step_by(8) {
+            // First iteration: i
+            let s1_0 = big_sigma1(e);
+            let ch_0 = ch(e, f, g);
+            let temp1_0 = h
+                .wrapping_add(s1_0)
+                .wrapping_add(ch_0)
+                .wrapping_add(K[i])
+                .wrapping_add(self.w[i]);
+            let s0_0 = big_sigma0(a);
+            let maj_0 = maj(a, b, c);
+            let temp2_0 = s0_0.wrapping_add(maj_0);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_0);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_0.wrapping_add(temp2_0);
+
+            // Second iteration: i + 1
+            let s1_1 = big_sigma1(e);
+            let ch_1 = ch(e, f, g);
+            let temp1_1 = h
+                .wrapping_add(s1_1)
+                .wrapping_add(ch_1)
+                .wrapping_add(K[i + 1])
+                .wrapping_add(self.w[i + 1]);
+            let s0_1 = big_sigma0(a);
+            let maj_1 = maj(a, b, c);
+            let temp2_1 = s0_1.wrapping_add(maj_1);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_1);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_1.wrapping_add(temp2_1);
+
+            // Third iteration: i + 2
+            let s1_2 = big_sigma1(e);
+            let ch_2 = ch(e, f, g);
+            let temp1_2 = h
+                .wrapping_add(s1_2)
+                .wrapping_add(ch_2)
+                .wrapping_add(K[i + 2])
+                .wrapping_add(self.w[i + 2]);
+            let s0_2 = big_sigma0(a);
+            let maj_2 = maj(a, b, c);
+            let temp2_2 = s0_2.wrapping_add(maj_2);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_2);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_2.wrapping_add(temp2_2);
+
+            // Fourth iteration: i + 3
+            let s1_3 = big_sigma1(e);
+            let ch_3 = ch(e, f, g);
+            let temp1_3 = h
+                .wrapping_add(s1_3)
+                .wrapping_add(ch_3)
+                .wrapping_add(K[i + 3])
+                .wrapping_add(self.w[i + 3]);
+            let s0_3 = big_sigma0(a);
+            let maj_3 = maj(a, b, c);
+            let temp2_3 = s0_3.wrapping_add(maj_3);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_3);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_3.wrapping_add(temp2_3);
+
+            // Fifth iteration: i + 4
+            let s1_4 = big_sigma1(e);
+            let ch_4 = ch(e, f, g);
+            let temp1_4 = h
+                .wrapping_add(s1_4)
+                .wrapping_add(ch_4)
+                .wrapping_add(K[i + 4])
+                .wrapping_add(self.w[i + 4]);
+            let s0_4 = big_sigma0(a);
+            let maj_4 = maj(a, b, c);
+            let temp2_4 = s0_4.wrapping_add(maj_4);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_4);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_4.wrapping_add(temp2_4);
+
+            // Sixth iteration: i + 5
+            let s1_5 = big_sigma1(e);
+            let ch_5 = ch(e, f, g);
+            let temp1_5 = h
+                .wrapping_add(s1_5)
+                .wrapping_add(ch_5)
+                .wrapping_add(K[i + 5])
+                .wrapping_add(self.w[i + 5]);
+            let s0_5 = big_sigma0(a);
+            let maj_5 = maj(a, b, c);
+            let temp2_5 = s0_5.wrapping_add(maj_5);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_5);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_5.wrapping_add(temp2_5);
+
+            // Seventh iteration: i + 6
+            let s1_6 = big_sigma1(e);
+            let ch_6 = ch(e, f, g);
+            let temp1_6 = h
+                .wrapping_add(s1_6)
+                .wrapping_add(ch_6)
+                .wrapping_add(K[i + 6])
+                .wrapping_add(self.w[i + 6]);
+            let s0_6 = big_sigma0(a);
+            let maj_6 = maj(a, b, c);
+            let temp2_6 = s0_6.wrapping_add(maj_6);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_6);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_6.wrapping_add(temp2_6);
+
+            // Eighth iteration: i + 7
+            let s1_7 = big_sigma1(e);
+            let ch_7 = ch(e, f, g);
+            let temp1_7 = h
+                .wrapping_add(s1_7)
+                .wrapping_add(ch_7)
+                .wrapping_add(K[i + 7])
+                .wrapping_add(self.w[i + 7]);
+            let s0_7 = big_sigma0(a);
+            let maj_7 = maj(a, b, c);
+            let temp2_7 = s0_7.wrapping_add(maj_7);
+
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(temp1_7);
+            d = c;
+            c = b;
+            b = a;
+            a = temp1_7.wrapping_add(temp2_7);
+        }
+
+        self.h0 = self.h0.wrapping_add(a);
+        self.h1 = self.h1.wrapping_add(b);
+        self.h2 = self.h2.wrapping_add(c);
+        self.h3 = self.h3.wrapping_add(d);
+        self.h4 = self.h4.wrapping_add(e);
+        self.h5 = self.h5.wrapping_add(f);
+        self.h6 = self.h6.wrapping_add(g);
+        self.h7 = self.h7.wrapping_add(h);
+    }
+
+    /// Computes the SHA-256 digest of the given message. Any data previously passed to `update` is discarded.
     ///
     /// # Arguments
     /// * `msg` - A byte slice representing the message to be hashed.
@@ -419,14 +685,21 @@ impl Sha256 {
     /// # Returns
     /// A 32-byte array representing the SHA-256 hash of the message.
     pub fn digest(&mut self, msg: &[u8]) -> [u8; 32] {
-        self.h0 = 0x6a09e667;
-        self.h1 = 0xbb67ae85;
-        self.h2 = 0x3c6ef372;
-        self.h3 = 0xa54ff53a;
-        self.h4 = 0x510e527f;
-        self.h5 = 0x9b05688c;
-        self.h6 = 0x1f83d9ab;
-        self.h7 = 0x5be0cd19;
+        let mut hash = [0; 32];
+        self.digest_into(msg, &mut hash);
+        hash
+    }
+
+    /// Computes the SHA-256 digest of the given message into `out`, rather than returning it.
+    ///
+    /// The same as `digest`, but the digest is written in place instead of being returned by value, which saves a 32 byte copy and its
+    /// stack slot on small targets. Any data previously passed to `update` is discarded.
+    ///
+    /// # Arguments
+    /// * `msg` - A byte slice representing the message to be hashed.
+    /// * `out` - Where the 32 byte digest is written.
+    pub fn digest_into(&mut self, msg: &[u8], out: &mut [u8; 32]) {
+        self.reset();
 
         let msg_len = msg.len();
         let n_chunks_saturated = msg_len / 64; // how many full chunks the message fits into
@@ -447,8 +720,8 @@ impl Sha256 {
 
         if msg_rem_len == 0 {
             self.set_chunk_padding_start_byte();
-            self.set_chunk_padding_zeros(msg, 1);
-            self.set_chunk_msg_len(msg);
+            self.set_chunk_padding_zeros(1);
+            self.set_chunk_msg_len(msg.len() as u64);
         } else {
             // copy the remaining message into the w array
             self.set_chunk_last(msg, n_chunks_saturated);
@@ -457,28 +730,441 @@ impl Sha256 {
         if msg_rem_len > 55 {
             // an extra chunk is required for the padding
             // padding is all zeros with the message length in bits at the end
-            self.set_chunk_padding_zeros(msg, 0);
-            self.set_chunk_msg_len(msg);
+            self.set_chunk_padding_zeros(0);
+            self.set_chunk_msg_len(msg.len() as u64);
             self.process_chunk();
         }
 
-        // Create the output hash
-        let mut hash = [0; 32];
-        unsafe {
-            hash[0..4].copy_from_slice(&self.h0.to_be_bytes());
-            hash[4..8].copy_from_slice(&self.h1.to_be_bytes());
-            hash[8..12].copy_from_slice(&self.h2.to_be_bytes());
-            hash[12..16].copy_from_slice(&self.h3.to_be_bytes());
-            hash[16..20].copy_from_slice(&self.h4.to_be_bytes());
-            hash[20..24].copy_from_slice(&self.h5.to_be_bytes());
-            hash[24..28].copy_from_slice(&self.h6.to_be_bytes());
-            hash[28..32].copy_from_slice(&self.h7.to_be_bytes());
+        self.write_hash(out);
+        // leave the hasher ready for streaming use
+        self.reset();
+    }
+
+    /// Absorbs more of the message into the hasher.
+    ///
+    /// Can be called any number of times. Full chunks are processed immediately, any remainder is buffered until the next call or `finalize`.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.update_with(data, |_| {});
+    }
+
+    /// Absorbs more of the message and returns the hasher, so a message made of a few pieces can be hashed in one expression.
+    ///
+    /// `Sha256::new().chain(a).chain(b).finalize()` is the same as calling `update` with `a` then `b`.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn chain(mut self, data: &[u8]) -> Self {
+        self.update(data);
+        self
+    }
+
+    /// Returns a copy of the hasher with the same progress, so a common prefix is hashed once and finalized with different suffixes.
+    ///
+    /// The same as `clone`, named for what it's used for, e.g. transcript hashes taken part way through a handshake or trial hashing loops.
+    ///
+    /// ```
+    /// use sha_256::Sha256;
+    /// let mut transcript = Sha256::new();
+    /// transcript.update(b"client hello, server hello");
+    /// let handshake_hash = transcript.fork().finalize();
+    /// transcript.update(b", finished");
+    /// assert_eq!(handshake_hash, sha_256::sha256(b"client hello, server hello"));
+    /// assert_eq!(transcript.finalize(), sha_256::sha256(b"client hello, server hello, finished"));
+    /// ```
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Absorbs whole 64 byte blocks, compressing them straight from the input.
+    ///
+    /// Equivalent to calling `update` with the blocks concatenated, but skips the buffering when the data absorbed so far is a multiple of 64 bytes long. For callers which already produce aligned blocks, e.g. protocol engines and Merkle tree internals.
+    ///
+    /// # Arguments
+    /// * `blocks` - The next blocks of the message.
+    pub fn update_blocks(&mut self, blocks: &[[u8; 64]]) {
+        if self.buf_len > 0 {
+            // the blocks are not aligned with the chunks, so they have to go through the buffer
+            for block in blocks {
+                self.update(block);
+            }
+            return;
+        }
+        self.len = self.len.wrapping_add((blocks.len() as u64).wrapping_mul(64));
+        for block in blocks {
+            self.compress_block(block);
+        }
+    }
+
+    /// Absorbs `count` copies of `byte` without materializing them, e.g. the zero filled holes of a sparse file.
+    ///
+    /// Every full block of the run is identical, so its message schedule is expanded once and reused, leaving only the rounds to compute per block.
+    ///
+    /// # Arguments
+    /// * `byte` - The repeated byte.
+    /// * `count` - How many times it repeats.
+    pub fn update_repeated(&mut self, byte: u8, count: u64) {
+        let block = [byte; 64];
+        let mut count = count;
+        // complete any partially filled chunk so the run is aligned
+        if self.buf_len > 0 {
+            let n = core::cmp::min((64 - self.buf_len) as u64, count);
+            self.update(&block[..n as usize]);
+            count -= n;
         }
+        let blocks = count / 64;
+        if blocks > 0 {
+            self.len = self.len.wrapping_add(blocks.wrapping_mul(64));
+            self.set_block(&block);
+            self.expand_schedule();
+            for _ in 0..blocks {
+                self.compress_rounds();
+            }
+        }
+        self.update(&block[..(count % 64) as usize]);
+    }
+
+    /// `update`, calling `on_block` after each block is compressed.
+    #[inline(always)]
+    pub(crate) fn update_with<F: FnMut(&Self)>(&mut self, data: &[u8], mut on_block: F) {
+        self.len = self.len.wrapping_add(data.len() as u64);
+        let mut data = data;
+
+        // top up a partially filled chunk first
+        if self.buf_len > 0 {
+            let n = core::cmp::min(64 - self.buf_len, data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < 64 {
+                return;
+            }
+            let buf = self.buf;
+            self.compress_block(&buf);
+            on_block(self);
+            self.buf_len = 0;
+        }
+
+        // process whole chunks straight from the input, no need to copy them into the buffer
+        let mut chunks = data.chunks_exact(64);
+        for chunk in &mut chunks {
+            self.compress_block(chunk);
+            on_block(self);
+        }
+
+        // keep hold of the leftover bytes until more data arrives
+        let rem = chunks.remainder();
+        self.buf[..rem.len()].copy_from_slice(rem);
+        self.buf_len = rem.len();
+    }
+
+    /// Absorbs a value into the hasher using its canonical `Hashable` encoding.
+    ///
+    /// # Arguments
+    /// * `value` - The value to be hashed.
+    pub fn absorb<T: Hashable + ?Sized>(&mut self, value: &T) {
+        value.hash_into(self);
+    }
+
+    /// Absorbs a field prefixed with its length, as a big-endian `u64`, so a sequence of fields can't be confused with a different split of the same bytes.
+    ///
+    /// The encoding is the same as `absorb` uses for byte slices, e.g. `absorb_field(b"ab")` matches `absorb(&b"ab"[..])`.
+    ///
+    /// # Arguments
+    /// * `bytes` - The field to be hashed.
+    pub fn absorb_field(&mut self, bytes: &[u8]) {
+        self.update(&(bytes.len() as u64).to_be_bytes());
+        self.update(bytes);
+    }
+
+    /// Pads the message absorbed via `update` and returns its SHA-256 digest.
+    ///
+    /// # Returns
+    /// A 32-byte array representing the SHA-256 hash of the message.
+    pub fn finalize(mut self) -> [u8; 32] {
+        self.finalize_with(|_| {})
+    }
 
+    /// Pads the message absorbed via `update` and writes its SHA-256 digest into `out`, e.g. straight into a preallocated packet buffer.
+    ///
+    /// # Arguments
+    /// * `out` - Where the 32 byte digest is written.
+    pub fn finalize_into(mut self, out: &mut [u8; 32]) {
+        self.pad_with(|_| {});
+        self.write_hash(out);
+    }
+
+    /// `finalize_into` for an output buffer which is a slice, e.g. the digest field of a larger message.
+    ///
+    /// # Arguments
+    /// * `out` - Where the digest is written, into its first 32 bytes. Any bytes after those are left as they were.
+    ///
+    /// # Returns
+    /// `Error::BufferTooSmall` if `out` is shorter than 32 bytes, in which case nothing is written.
+    pub fn finalize_into_slice(self, out: &mut [u8]) -> Result<(), Error> {
+        let out = out.get_mut(..32).ok_or(Error::BufferTooSmall { needed: 32 })?;
+        self.finalize_into(out.try_into().unwrap());
+        Ok(())
+    }
+
+    /// Pads the message absorbed via `update`, returns its SHA-256 digest and resets the hasher, ready for the next message.
+    ///
+    /// The same as `finalize` followed by `reset`, but in place, so hashing many messages in a loop needn't move or recreate the hasher.
+    ///
+    /// # Returns
+    /// A 32-byte array representing the SHA-256 hash of the message.
+    pub fn finalize_reset(&mut self) -> [u8; 32] {
+        let hash = self.finalize_with(|_| {});
+        self.reset();
         hash
     }
+
+    /// `finalize`, calling `on_block` after each padding block is compressed.
+    ///
+    /// Works in place rather than taking `self`, so e.g. HMAC can finish its inner hash without copying the whole hasher, which matters with the small stacks of 8/16-bit targets.
+    #[inline(always)]
+    pub(crate) fn finalize_with<F: FnMut(&Self)>(&mut self, on_block: F) -> [u8; 32] {
+        self.pad_with(on_block);
+        self.hash()
+    }
+
+    /// Pads the message and compresses the padding blocks, leaving the digest in the hash values.
+    #[inline(always)]
+    fn pad_with<F: FnMut(&Self)>(&mut self, mut on_block: F) {
+        let n = self.buf_len;
+        // after the msg ends, we pad with a 0b10000000 byte followed by 0's
+        self.buf[n] = 0b10000000;
+        self.buf[n + 1..].fill(0);
+        let buf = self.buf;
+        self.set_block(&buf);
+        if n > 55 {
+            // no space for the length field, so it goes at the end of an extra chunk of 0's
+            self.process_chunk();
+            on_block(self);
+            self.set_chunk_padding_zeros(0);
+        }
+        self.set_chunk_msg_len(self.len);
+        self.process_chunk();
+        on_block(self);
+    }
+
+    /// The length of the encoding produced by `export_state`.
+    pub const STATE_LEN: usize = 136;
+
+    /// The longest message SHA-256 can hash in bytes, its length in bits has to fit the 64 bit length field.
+    pub const MAX_LEN: u64 = u64::MAX / 8;
+
+    /// Checks that a resumed hasher's length is one SHA-256 can finish, rather than one whose bit count would wrap in the padding.
+    fn check_len(len: u64) -> Result<(), Error> {
+        if len > Self::MAX_LEN {
+            return Err(Error::LengthOverflow);
+        }
+        Ok(())
+    }
+
+    /// Exports the hasher's progress so it can be persisted and picked up later with `import_state`.
+    ///
+    /// The encoding is the 8 hash values and the number of bytes absorbed (all big-endian), followed by the buffered bytes of the partial chunk padded with 0's to 64 bytes,
+    /// then the 8 initial hash values (big-endian), so a hasher created with `with_iv` still resets to its own IV once imported.
+    ///
+    /// # Returns
+    /// The `STATE_LEN` byte encoding of the hasher's state.
+    pub fn export_state(&self) -> [u8; Self::STATE_LEN] {
+        let mut out = [0u8; Self::STATE_LEN];
+        for (chunk, h) in out[..32].chunks_exact_mut(4).zip(self.state()) {
+            chunk.copy_from_slice(&h.to_be_bytes());
+        }
+        out[32..40].copy_from_slice(&self.len.to_be_bytes());
+        out[40..40 + self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+        for (chunk, iv) in out[104..].chunks_exact_mut(4).zip(self.iv) {
+            chunk.copy_from_slice(&iv.to_be_bytes());
+        }
+        out
+    }
+
+    /// Recreates a hasher from the output of `export_state`.
+    ///
+    /// # Arguments
+    /// * `state` - The exported state.
+    ///
+    /// # Returns
+    /// The hasher, `Error::Parse` if the padding after the buffered bytes is not all 0's, or `Error::LengthOverflow` if the length is over `MAX_LEN`.
+    pub fn import_state(state: &[u8; Self::STATE_LEN]) -> Result<Self, Error> {
+        let words = |bytes: &[u8]| -> [u32; 8] { core::array::from_fn(|i| u32::from_be_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap())) };
+        let mut sha256 = Sha256::with_iv(words(&state[104..]));
+        [sha256.h0, sha256.h1, sha256.h2, sha256.h3, sha256.h4, sha256.h5, sha256.h6, sha256.h7] = words(&state[..32]);
+        sha256.len = u64::from_be_bytes(state[32..40].try_into().unwrap());
+        Self::check_len(sha256.len)?;
+        // the buffer always holds the bytes after the last whole chunk
+        sha256.buf_len = (sha256.len % 64) as usize;
+        let (buf, padding) = state[40..104].split_at(sha256.buf_len);
+        if padding.iter().any(|&b| b != 0) {
+            return Err(Error::Parse { reason: "non-zero padding in exported state" });
+        }
+        sha256.buf[..sha256.buf_len].copy_from_slice(buf);
+        Ok(sha256)
+    }
+
+    /// Returns the midstate, the 8 hash values after the whole 64 byte blocks absorbed so far.
+    ///
+    /// With `midstate_len` it's enough to resume hashing with `from_midstate`, so a fixed prefix, e.g. the first block of a header
+    /// whose nonce changes, only needs compressing once. Bytes buffered after the last whole block aren't included, see `save_state` for those.
+    ///
+    /// ```
+    /// use sha_256::Sha256;
+    /// let prefix = [0x42u8; 64];
+    /// let mut sha256 = Sha256::new();
+    /// sha256.update(&prefix);
+    /// let (midstate, len) = (sha256.midstate(), sha256.midstate_len());
+    /// for nonce in 0..4u32 {
+    ///     let mut sha256 = Sha256::from_midstate(midstate, len).unwrap();
+    ///     sha256.update(&nonce.to_le_bytes());
+    ///     assert_eq!(sha256.finalize(), sha_256::sha256(&[&prefix[..], &nonce.to_le_bytes()].concat()));
+    /// }
+    /// ```
+    pub fn midstate(&self) -> [u32; 8] {
+        self.state()
+    }
+
+    /// Returns the number of bytes the midstate covers, those absorbed up to the end of the last whole block, always a multiple of 64.
+    pub fn midstate_len(&self) -> u64 {
+        self.len - self.buf_len as u64
+    }
+
+    /// Creates a hasher which resumes from a midstate, as if the `len` bytes it covers had been passed to `update`.
+    ///
+    /// The hasher is plain SHA-256, `reset` goes back to the standard IV. Use `from_midstate_with_iv` for a midstate of a `with_iv` hasher.
+    ///
+    /// # Arguments
+    /// * `state` - The midstate, from `midstate`.
+    /// * `len` - The number of bytes the midstate covers, from `midstate_len`.
+    ///
+    /// # Returns
+    /// The hasher, `Error::Parse` if `len` is not a multiple of 64, or `Error::LengthOverflow` if it is over `MAX_LEN`.
+    pub fn from_midstate(state: [u32; 8], len: u64) -> Result<Self, Error> {
+        Self::from_midstate_with_iv(H, state, len)
+    }
+
+    /// Creates a hasher which started from the initial hash values `iv` and resumes from a midstate, see `with_iv` and `from_midstate`.
+    ///
+    /// # Arguments
+    /// * `iv` - The initial hash values the midstate was reached from, from `iv`.
+    /// * `state` - The midstate, from `midstate`.
+    /// * `len` - The number of bytes the midstate covers, from `midstate_len`.
+    ///
+    /// # Returns
+    /// The hasher, `Error::Parse` if `len` is not a multiple of 64, or `Error::LengthOverflow` if it is over `MAX_LEN`.
+    pub fn from_midstate_with_iv(iv: [u32; 8], state: [u32; 8], len: u64) -> Result<Self, Error> {
+        if !len.is_multiple_of(64) {
+            return Err(Error::Parse { reason: "a midstate covers whole 64 byte blocks" });
+        }
+        Self::check_len(len)?;
+        Ok(Self::from_state_with_iv(iv, state, len))
+    }
+
+    /// Returns the initial hash values the hasher started from and goes back to on `reset`, SHA-256's unless it was created with `with_iv`.
+    pub fn iv(&self) -> [u32; 8] {
+        self.iv
+    }
+
+    /// Creates a hasher which has absorbed `len` bytes, `len` being a multiple of 64, and reached the hash values `state`.
+    pub(crate) fn from_state(state: [u32; 8], len: u64) -> Self {
+        Self::from_state_with_iv(H, state, len)
+    }
+
+    /// `from_state` for a hasher which started from, and resets to, `iv`.
+    pub(crate) fn from_state_with_iv(iv: [u32; 8], state: [u32; 8], len: u64) -> Self {
+        let mut sha256 = Sha256::with_iv(iv);
+        [sha256.h0, sha256.h1, sha256.h2, sha256.h3, sha256.h4, sha256.h5, sha256.h6, sha256.h7] = state;
+        sha256.len = len;
+        sha256
+    }
+
+    /// Returns the 8 hash values, i.e. the chaining state after the blocks processed so far.
+    #[inline(always)]
+    pub(crate) fn state(&self) -> [u32; 8] {
+        [self.h0, self.h1, self.h2, self.h3, self.h4, self.h5, self.h6, self.h7]
+    }
+
+    /// Converts the 8 hash values into the output bytes.
+    #[inline(always)]
+    fn hash(&self) -> [u8; 32] {
+        let mut hash = [0; 32];
+        self.write_hash(&mut hash);
+        hash
+    }
+
+    /// Writes the 8 hash values into `out` as the output bytes.
+    #[inline(always)]
+    fn write_hash(&self, out: &mut [u8; 32]) {
+        out[0..4].copy_from_slice(&self.h0.to_be_bytes());
+        out[4..8].copy_from_slice(&self.h1.to_be_bytes());
+        out[8..12].copy_from_slice(&self.h2.to_be_bytes());
+        out[12..16].copy_from_slice(&self.h3.to_be_bytes());
+        out[16..20].copy_from_slice(&self.h4.to_be_bytes());
+        out[20..24].copy_from_slice(&self.h5.to_be_bytes());
+        out[24..28].copy_from_slice(&self.h6.to_be_bytes());
+        out[28..32].copy_from_slice(&self.h7.to_be_bytes());
+    }
 }
 
+/// Computes the SHA-256 digest of a message in one call, for when there's only the one message to hash.
+///
+/// ```
+/// let hash: [u8; 32] = sha_256::sha256(b"hello");
+/// ```
+///
+/// # Arguments
+/// * `msg` - A byte slice representing the message to be hashed.
+///
+/// # Returns
+/// A 32-byte array representing the SHA-256 hash of the message.
+pub fn sha256(msg: &[u8]) -> [u8; 32] {
+    Sha256::new().digest(msg)
+}
+
+/// Runs the SHA-256 compression function on one block, with no padding or length, for building other constructions out of it.
+///
+/// This is the same compression function `Sha256` uses, e.g. tree hashes with their own node encoding or custom padding schemes.
+/// Feeding it the padded message blocks from the standard initial hash values gives the SHA-256 hash values.
+///
+/// ```
+/// // "abc" padded to one block, as FIPS 180-4 appendix B.1
+/// let mut block = [0u8; 64];
+/// block[..4].copy_from_slice(b"abc\x80");
+/// block[63] = 24;
+/// let mut state = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+/// sha_256::compress(&mut state, &block);
+/// assert_eq!(state[0], 0xba7816bf);
+/// ```
+///
+/// # Arguments
+/// * `state` - The 8 hash values, updated in place.
+/// * `block` - The 64 byte message block.
+pub fn compress(state: &mut [u32; 8], block: &[u8; 64]) {
+    compress_blocks(state, core::slice::from_ref(block));
+}
+
+/// Runs the SHA-256 compression function on each block in turn, the same as calling `compress` for each.
+///
+/// # Arguments
+/// * `state` - The 8 hash values, updated in place.
+/// * `blocks` - The 64 byte message blocks, in order.
+pub fn compress_blocks(state: &mut [u32; 8], blocks: &[[u8; 64]]) {
+    let mut sha256 = Sha256::from_state(*state, 0);
+    for block in blocks {
+        sha256.compress_block(block);
+    }
+    *state = sha256.state();
+}
+
+// the initial hash values
+const H: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
 const K: [u32; 64] = [
     0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
     0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
@@ -492,21 +1178,24 @@ const K: [u32; 64] = [
 
 #[cfg(test)]
 mod tests {
+    extern crate std;
+
 	use super::*;
-    use sha2::{digest::generic_array::GenericArray, Digest, Sha256 as Theirs};
+    use sha2::Digest;
+    use std::{println, vec, vec::Vec};
 
-    struct Rng {
+    pub(crate) struct Rng {
         state: u64,
     }
 
     impl Rng {
-        fn new(seed: u64) -> Self {
+        pub(crate) fn new(seed: u64) -> Self {
             Self {
                 state: if seed == 0 { 1 } else { seed },
             }
         }
 
-        fn next(&mut self) -> u64 {
+        pub(crate) fn next(&mut self) -> u64 {
             self.state ^= self.state << 13;
             self.state ^= self.state >> 7;
             self.state ^= self.state << 17;
@@ -514,6 +1203,18 @@ mod tests {
         }
     }
 
+    // the futures in the tests never return pending, so a busy loop is enough of an executor
+    #[cfg(any(feature = "stream", feature = "http"))]
+    pub(crate) fn block_on<F: core::future::Future>(future: F) -> F::Output {
+        let mut future = core::pin::pin!(future);
+        let mut cx = core::task::Context::from_waker(core::task::Waker::noop());
+        loop {
+            if let core::task::Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+        }
+    }
+
     #[test]
     fn test_against_sha2_lib() {
         // generate random messages
@@ -545,6 +1246,250 @@ mod tests {
         println!("total test cases: {}", count);
     }
 
+    #[test]
+    fn update_matches_digest() {
+        // feed random messages in randomly sized pieces and compare with the one-shot digest
+        let mut rng = Rng::new(1);
+
+        let mut ours = Sha256::new();
+        for _ in 0..10_000 {
+            let len = (rng.next() % 1024) as usize;
+            let message_bytes: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+            let expected = ours.digest(&message_bytes);
+
+            let mut sha256 = Sha256::new();
+            let mut rem = &message_bytes[..];
+            while !rem.is_empty() {
+                let n = (rng.next() % 130) as usize;
+                let n = n.min(rem.len());
+                sha256.update(&rem[..n]);
+                rem = &rem[n..];
+            }
+            assert_eq!(sha256.finalize(), expected, "message of len {}", len);
+        }
+    }
+
+    #[test]
+    fn every_split_matches_digest() {
+        // every way of splitting a message in two, and byte at a time, across the padding boundaries
+        let msg: Vec<u8> = (0..200u32).map(|i| (i * 7 + 3) as u8).collect();
+        let mut ours = Sha256::new();
+        for len in 0..msg.len() {
+            let expected = ours.digest(&msg[..len]);
+            for split in 0..=len {
+                let mut sha256 = Sha256::new();
+                sha256.update(&msg[..split]);
+                sha256.update(&msg[split..len]);
+                assert_eq!(sha256.finalize(), expected, "len {} split {}", len, split);
+            }
+            let mut sha256 = Sha256::new();
+            for byte in &msg[..len] {
+                sha256.update(core::slice::from_ref(byte));
+            }
+            assert_eq!(sha256.finalize(), expected, "len {} byte at a time", len);
+        }
+    }
+
+    #[test]
+    fn finalize_reset_starts_a_new_message() {
+        let mut sha256 = Sha256::new();
+        for msg in [&b"abc"[..], b"", &[7; 100], b"abc"] {
+            sha256.update(msg);
+            assert_eq!(sha256.finalize_reset(), Sha256::new().digest(msg));
+        }
+    }
+
+    #[test]
+    fn sha256_matches_digest() {
+        for msg in [&b""[..], b"abc", &[0xa5; 1000]] {
+            assert_eq!(sha256(msg), Sha256::new().digest(msg));
+        }
+    }
+
+    #[test]
+    fn chain_matches_update() {
+        assert_eq!(Sha256::new().chain(b"ab").chain(b"").chain(&[7; 100]).finalize(), Sha256::new().digest(&[&b"ab"[..], &[7; 100]].concat()));
+    }
+
+    #[test]
+    fn update_blocks_matches_update() {
+        let blocks: Vec<[u8; 64]> = (0..5u8).map(|i| [i; 64]).collect();
+        let msg = blocks.concat();
+
+        let mut sha256 = Sha256::new();
+        sha256.update_blocks(&blocks);
+        sha256.update(b"tail");
+        let mut expected = msg.clone();
+        expected.extend_from_slice(b"tail");
+        assert_eq!(sha256.finalize(), Sha256::new().digest(&expected));
+
+        // not aligned to a block boundary
+        let mut sha256 = Sha256::new();
+        sha256.update(b"head");
+        sha256.update_blocks(&blocks);
+        let mut expected = b"head".to_vec();
+        expected.extend_from_slice(&msg);
+        assert_eq!(sha256.finalize(), Sha256::new().digest(&expected));
+    }
+
+    #[test]
+    fn finalizes_into_buffers() {
+        let expected = Sha256::new().digest(b"abc");
+        let mut out = [0u8; 32];
+        Sha256::new().chain(b"abc").finalize_into(&mut out);
+        assert_eq!(out, expected);
+
+        let mut packet = [0xffu8; 40];
+        Sha256::new().chain(b"abc").finalize_into_slice(&mut packet[4..]).unwrap();
+        assert_eq!(packet[4..36], expected);
+        assert_eq!((&packet[..4], &packet[36..]), (&[0xff; 4][..], &[0xff; 4][..]));
+        assert!(matches!(Sha256::new().finalize_into_slice(&mut packet[..31]), Err(Error::BufferTooSmall { needed: 32 })));
+        assert_eq!(packet[..4], [0xff; 4]);
+    }
+
+    #[test]
+    fn digests_into_buffers() {
+        let mut sha256 = Sha256::new();
+        sha256.update(b"discarded");
+        for len in [0, 1, 55, 56, 63, 64, 65, 200] {
+            let msg = [0x61u8; 200];
+            let mut out = [0u8; 32];
+            sha256.digest_into(&msg[..len], &mut out);
+            assert_eq!(out, sha2::Sha256::digest(&msg[..len])[..], "len {}", len);
+        }
+    }
+
+    #[test]
+    fn starts_from_a_custom_iv() {
+        let iv: [u32; 8] = core::array::from_fn(|i| 0x01234567u32.rotate_left(4 * i as u32));
+        let msg = [0x5au8; 100];
+        let mut sha256 = Sha256::with_iv(iv);
+        sha256.update(&msg);
+        let mut state = iv;
+        compress(&mut state, msg[..64].try_into().unwrap());
+        assert_eq!(sha256.midstate(), state);
+        let hash = sha256.finalize_reset();
+        assert_ne!(hash, crate::sha256(&msg));
+        // resetting goes back to the custom IV
+        assert_eq!(sha256.midstate(), iv);
+        assert_eq!(sha256.digest(&msg), hash);
+        assert_eq!(Sha256::with_iv(H).digest(&msg), crate::sha256(&msg));
+    }
+
+    #[test]
+    fn compresses_raw_blocks() {
+        let msg: [u8; 256] = core::array::from_fn(|i| (i * 11) as u8);
+        let blocks: [[u8; 64]; 4] = core::array::from_fn(|i| msg[64 * i..64 * (i + 1)].try_into().unwrap());
+        let mut sha256 = Sha256::new();
+        sha256.update(&msg);
+        let mut state = H;
+        compress_blocks(&mut state, &blocks);
+        assert_eq!(state, sha256.midstate());
+        let mut state = H;
+        for block in &blocks {
+            compress(&mut state, block);
+        }
+        assert_eq!(state, sha256.midstate());
+        // the padding block of a 256 byte message finishes the hash
+        let mut padding = [0u8; 64];
+        padding[0] = 0x80;
+        padding[56..].copy_from_slice(&(256u64 * 8).to_be_bytes());
+        compress(&mut state, &padding);
+        let hash: Vec<u8> = state.iter().flat_map(|h| h.to_be_bytes()).collect();
+        assert_eq!(hash, sha256.finalize());
+    }
+
+    #[test]
+    fn forks_share_the_prefix() {
+        let msg: Vec<u8> = (0..200u32).map(|i| (i * 5) as u8).collect();
+        for split in [0, 1, 63, 64, 65, 200] {
+            let mut prefix = Sha256::new();
+            prefix.update(&msg[..split]);
+            for suffix in [&b""[..], b"a", &[7; 100]] {
+                let mut expected = msg[..split].to_vec();
+                expected.extend_from_slice(suffix);
+                assert_eq!(prefix.fork().chain(suffix).finalize(), Sha256::new().digest(&expected), "split {}", split);
+            }
+            // forking doesn't disturb the original
+            assert_eq!(prefix.finalize(), Sha256::new().digest(&msg[..split]), "split {}", split);
+        }
+    }
+
+    #[test]
+    fn resumes_from_midstate() {
+        let msg: Vec<u8> = (0..300u32).map(|i| (i * 3) as u8).collect();
+        for split in [0, 1, 63, 64, 65, 128, 300] {
+            let mut sha256 = Sha256::new();
+            sha256.update(&msg[..split]);
+            let len = sha256.midstate_len();
+            assert_eq!(len, (split / 64 * 64) as u64);
+            let mut resumed = Sha256::from_midstate(sha256.midstate(), len).unwrap();
+            resumed.update(&msg[len as usize..]);
+            assert_eq!(resumed.finalize(), Sha256::new().digest(&msg), "split {}", split);
+        }
+        assert!(matches!(Sha256::from_midstate([0; 8], 65), Err(Error::Parse { .. })));
+        assert!(Sha256::from_midstate_with_iv(H, [0; 8], 1).is_err());
+        assert!(Sha256::from_midstate(H, Sha256::MAX_LEN / 64 * 64).is_ok());
+        assert!(matches!(Sha256::from_midstate(H, (Sha256::MAX_LEN / 64 + 1) * 64), Err(Error::LengthOverflow)));
+    }
+
+    #[test]
+    fn export_import_state_round_trips() {
+        let msg: Vec<u8> = (0..300u32).map(|i| i as u8).collect();
+        for split in [0, 1, 63, 64, 100, 300] {
+            let mut sha256 = Sha256::new();
+            sha256.update(&msg[..split]);
+            let mut resumed = Sha256::import_state(&sha256.export_state()).unwrap();
+            resumed.update(&msg[split..]);
+            assert_eq!(resumed.finalize(), Sha256::new().digest(&msg), "split {}", split);
+        }
+        let mut state = Sha256::new().export_state();
+        state[103] = 1;
+        assert!(Sha256::import_state(&state).is_err());
+        // a length whose bit count doesn't fit the padding
+        let mut state = Sha256::new().export_state();
+        state[32..40].copy_from_slice(&(Sha256::MAX_LEN + 1).to_be_bytes());
+        assert!(matches!(Sha256::import_state(&state), Err(Error::LengthOverflow)));
+    }
+
+    #[test]
+    fn resumed_hashers_keep_their_iv() {
+        let iv: [u32; 8] = core::array::from_fn(|i| 0x89abcdefu32.rotate_left(4 * i as u32));
+        let msg = [0x33u8; 150];
+        let expected = Sha256::with_iv(iv).chain(&msg).finalize();
+        let mut sha256 = Sha256::with_iv(iv);
+        sha256.update(&msg[..100]);
+        assert_eq!(sha256.iv(), iv);
+
+        let mut imported = Sha256::import_state(&sha256.export_state()).unwrap();
+        let mut restored = Sha256::restore_state(&sha256.save_state()).unwrap();
+        let mut resumed = Sha256::from_midstate_with_iv(iv, sha256.midstate(), sha256.midstate_len()).unwrap();
+        resumed.update(&msg[64..100]);
+        for resumed in [&mut imported, &mut restored, &mut resumed] {
+            assert_eq!(resumed.iv(), iv);
+            resumed.update(&msg[100..]);
+            assert_eq!(resumed.finalize_reset(), expected);
+            // and resetting goes back to the custom IV, not SHA-256's
+            assert_eq!(resumed.digest(&msg), expected);
+        }
+        assert_eq!(Sha256::new().iv(), H);
+    }
+
+    #[test]
+    fn absorb_field_is_length_prefixed() {
+        let fields = |a: &[u8], b: &[u8]| {
+            let mut sha256 = Sha256::new();
+            sha256.absorb_field(a);
+            sha256.absorb_field(b);
+            sha256.finalize()
+        };
+        assert_ne!(fields(b"ab", b"c"), fields(b"a", b"bc"));
+
+        let mut sha256 = Sha256::new();
+        sha256.absorb(&(&b"ab"[..], &b"c"[..]));
+        assert_eq!(fields(b"ab", b"c"), sha256.finalize());
+    }
+
     #[test]
     fn hash_hello() {
 		let mut sha256 = Sha256::new();
@@ -574,7 +1519,7 @@ mod tests {
     // 'aa'
     // 'aaa'
     // ...
-    const HASHES: [[u8; 32]; 1024] = [[202, 151, 129, 18, 202, 27, 189, 202, 250, 194, 49, 179, 154, 35, 220, 77, 167, 134, 239, 248, 20, 124, 78, 114, 185, 128, 119, 133, 175, 238, 72, 187],
+    static HASHES: [[u8; 32]; 1024] = [[202, 151, 129, 18, 202, 27, 189, 202, 250, 194, 49, 179, 154, 35, 220, 77, 167, 134, 239, 248, 20, 124, 78, 114, 185, 128, 119, 133, 175, 238, 72, 187],
     [150, 27, 109, 211, 237, 227, 203, 142, 203, 170, 203, 214, 141, 224, 64, 205, 120, 235, 46, 213, 136, 145, 48, 204, 235, 76, 73, 38, 142, 164, 213, 6],
     [152, 52, 135, 109, 207, 176, 92, 177, 103, 165, 194, 73, 83, 235, 165, 140, 74, 200, 155, 26, 223, 87, 242, 143, 47, 157, 9, 175, 16, 126, 232, 240],
     [97, 190, 85, 168, 226, 246, 180, 225, 114, 51, 139, 221, 241, 132, 214, 219, 238, 41, 201, 136, 83, 224, 160, 72, 94, 206, 231, 242, 123, 154, 240, 180],
@@ -1604,16 +2549,13 @@ mod tests {
     #[test]
     fn hash_variable_len_bytes() {
 		let mut sha256 = Sha256::new();
-        for i in 0..HASHES.len() {
-            let mut message_bytes = Vec::<u8>::new();
-            for _ in 0..=i {
-                message_bytes.push(97); // 'a'
-            }
+        for (i, expected) in HASHES.iter().enumerate() {
+            let message_bytes = vec![97u8; i + 1]; // 'a'
             println!("testing msg of len {}", message_bytes.len());
             let hash = sha256.digest(&message_bytes);
             println!("hash: {:?}", hash);
-            println!("expected: {:?}", HASHES[i]);
-            assert_eq!(hash, HASHES[i], "hashes[{}] with {}x'a'", i, i+1);
+            println!("expected: {:?}", expected);
+            assert_eq!(&hash, expected, "hashes[{}] with {}x'a'", i, i+1);
         }
     }
 
@@ -1627,12 +2569,9 @@ mod tests {
         let mut count: usize = 0;
         let mut sha256 = Sha256::new();
         loop {
-            let mut message_bytes = Vec::<u8>::new();
             let i = (rng.next() % HASHES.len() as u64) as usize;
             println!("i {}", i);
-            for _ in 0..=i {
-                message_bytes.push(97); // 'a'
-            }
+            let message_bytes = vec![97u8; i + 1]; // 'a'
             println!("testing msg of len {}", message_bytes.len());
             let hash = sha256.digest(&message_bytes);
             println!("hash: {:?}", hash);
@@ -0,0 +1,635 @@
+//! Reading and writing checksum files.
+//!
+//! Three formats are supported:
+//! * GNU, as written by `sha256sum`: `<hex>  <path>` (`<hex> *<path>` for binary mode, which is treated the same).
+//! * BSD tags, as written by `sha256sum --tag` and `shasum --tag`: `SHA256 (<path>) = <hex>`.
+//! * JSON, as written by `sha256 --json`: an array of `{"path", "size", "mtime", "algorithm", "digest"}` objects.
+//!
+//! Paths containing a backslash, newline or carriage return are escaped the same way as coreutils: the line starts with a
+//! backslash and those characters are written as `\\`, `\n` and `\r`.
+//!
+//! A manifest in any format can be signed with a shared key by `write_signed_manifest`, which appends a last line
+//! `HMAC-SHA256 = <hex>` holding the HMAC-SHA256 of everything before it. `verify_manifest` checks it before parsing.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Write};
+use core::str;
+
+use crate::{hex, Error, Hash256, HmacKey, HmacSha256};
+
+/// What the trailer line of a signed manifest starts with, followed by the lowercase hex HMAC.
+const TRAILER_PREFIX: &str = "HMAC-SHA256 = ";
+
+/// One file listed in a checksum file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ManifestEntry {
+    /// The path of the file, unescaped.
+    pub path: String,
+    /// The hash of the file.
+    pub hash: Hash256,
+    /// The size of the file in bytes, only stored in JSON manifests.
+    pub size: Option<u64>,
+    /// When the file was last modified in seconds since the Unix epoch, only stored in JSON manifests.
+    pub mtime: Option<u64>,
+}
+
+/// The layout of a checksum file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ManifestFormat {
+    /// `sha256sum` lines.
+    Gnu,
+    /// `sha256sum --tag` lines.
+    Bsd,
+    /// A JSON array of objects.
+    Json,
+}
+
+impl ManifestEntry {
+    /// Creates an entry without the optional metadata.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    /// * `hash` - The hash of the file.
+    pub fn new(path: impl Into<String>, hash: Hash256) -> Self {
+        Self { path: path.into(), hash, size: None, mtime: None }
+    }
+
+    /// Parses a single GNU or BSD line, whichever it is.
+    ///
+    /// # Arguments
+    /// * `line` - The line, without its trailing newline.
+    ///
+    /// # Returns
+    /// The entry, or `Error::Parse` if the line is in neither format.
+    pub fn parse_line(line: &str) -> Result<Self, Error> {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let (escaped, line) = match line.strip_prefix('\\') {
+            Some(line) => (true, line),
+            None => (false, line),
+        };
+        let (hash, path) = if let Some(rest) = line.strip_prefix("SHA256 (") {
+            rest.rsplit_once(") = ").map(|(path, hash)| (hash, path)).ok_or(Error::Parse { reason: "BSD line is missing ') = '" })?
+        } else {
+            let (hash, rest) = line.split_at_checked(64).ok_or(Error::Parse { reason: "line is too short" })?;
+            let path = rest.strip_prefix("  ").or_else(|| rest.strip_prefix(" *")).ok_or(Error::Parse { reason: "hash is not followed by two spaces or ' *'" })?;
+            (hash, path)
+        };
+        let hash = hex::decode(hash.as_bytes()).ok_or(Error::Parse { reason: "hash is not 64 hex characters" })?;
+        let path = if escaped { unescape(path)? } else { path.into() };
+        if path.is_empty() {
+            return Err(Error::Parse { reason: "path is empty" });
+        }
+        Ok(Self::new(path, Hash256::new(hash)))
+    }
+
+    /// Writes the entry as a line, without the trailing newline.
+    ///
+    /// # Arguments
+    /// * `out` - Where the line is written.
+    /// * `format` - `Gnu` or `Bsd`, `Json` entries can't be written on their own so are written as `Gnu`.
+    pub fn write_line<W: Write>(&self, out: &mut W, format: ManifestFormat) -> fmt::Result {
+        let hex = hex::encode(self.hash.as_bytes());
+        // hex digits are always ASCII
+        let hex = str::from_utf8(&hex).unwrap();
+        if needs_escaping(&self.path) {
+            out.write_char('\\')?;
+        }
+        match format {
+            ManifestFormat::Bsd => {
+                out.write_str("SHA256 (")?;
+                write_escaped(out, &self.path)?;
+                write!(out, ") = {}", hex)
+            }
+            ManifestFormat::Gnu | ManifestFormat::Json => {
+                write!(out, "{}  ", hex)?;
+                write_escaped(out, &self.path)
+            }
+        }
+    }
+
+    /// Writes the entry as a GNU line with the digest in another encoding, e.g. base64, without the trailing newline.
+    ///
+    /// The path is escaped the same as by `write_line`, so a file name can't start a line of its own.
+    ///
+    /// # Arguments
+    /// * `out` - Where the line is written.
+    /// * `digest` - The encoded digest, written in place of the hex.
+    pub fn write_line_encoded<W: Write>(&self, out: &mut W, digest: &str) -> fmt::Result {
+        if needs_escaping(&self.path) {
+            out.write_char('\\')?;
+        }
+        write!(out, "{}  ", digest)?;
+        write_escaped(out, &self.path)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ManifestEntry {
+    /// Creates an entry for a file, taking its size and modification time from its metadata.
+    ///
+    /// # Arguments
+    /// * `path` - The path of the file.
+    /// * `hash` - The hash of the file.
+    /// * `metadata` - The file's metadata, e.g. from `std::fs::metadata`.
+    pub fn with_metadata(path: impl Into<String>, hash: Hash256, metadata: &std::fs::Metadata) -> Self {
+        Self { path: path.into(), hash, size: Some(metadata.len()), mtime: mtime(metadata) }
+    }
+
+    /// Returns whether the file can be assumed unchanged since this entry was made, without reading it.
+    ///
+    /// That's when both its size and modification time match. Times are stored to the second,
+    /// so a file rewritten with the same size within the same second as the entry was made is missed.
+    ///
+    /// # Arguments
+    /// * `metadata` - The file's current metadata.
+    pub fn is_unchanged(&self, metadata: &std::fs::Metadata) -> bool {
+        self.size == Some(metadata.len()) && self.mtime.is_some() && self.mtime == mtime(metadata)
+    }
+}
+
+#[cfg(feature = "std")]
+fn mtime(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok().map(|mtime| mtime.as_secs())
+}
+
+/// Parses a checksum file in any of the supported formats, JSON if it starts with `[` otherwise lines of either GNU or BSD format.
+///
+/// # Arguments
+/// * `text` - The contents of the checksum file.
+///
+/// # Returns
+/// The entries in the order they are listed, or `Error::Parse` at the first malformed entry. Blank lines are skipped.
+pub fn parse_manifest(text: &str) -> Result<Vec<ManifestEntry>, Error> {
+    if text.trim_start().starts_with('[') {
+        return Json { bytes: text.as_bytes(), pos: 0 }.manifest();
+    }
+    text.lines().filter(|line| !line.trim().is_empty()).map(ManifestEntry::parse_line).collect()
+}
+
+/// Writes a checksum file.
+///
+/// # Arguments
+/// * `out` - Where the checksum file is written.
+/// * `entries` - The files to list.
+/// * `format` - The layout to write them in.
+pub fn write_manifest<W: Write>(out: &mut W, entries: &[ManifestEntry], format: ManifestFormat) -> fmt::Result {
+    if format != ManifestFormat::Json {
+        return entries.iter().try_for_each(|entry| {
+            entry.write_line(out, format)?;
+            out.write_char('\n')
+        });
+    }
+    out.write_str("[\n")?;
+    for (i, entry) in entries.iter().enumerate() {
+        out.write_str("  {\"path\": ")?;
+        write_json_string(out, &entry.path)?;
+        out.write_str(", \"size\": ")?;
+        write_json_u64(out, entry.size)?;
+        out.write_str(", \"mtime\": ")?;
+        write_json_u64(out, entry.mtime)?;
+        let hex = hex::encode(entry.hash.as_bytes());
+        write!(out, ", \"algorithm\": \"sha256\", \"digest\": \"{}\"}}", str::from_utf8(&hex).unwrap())?;
+        out.write_str(if i + 1 < entries.len() { ",\n" } else { "\n" })?;
+    }
+    out.write_str("]\n")
+}
+
+/// Writes a checksum file followed by an HMAC-SHA256 trailer line, so it can't be altered on shared storage without the key.
+///
+/// ```
+/// use sha_256::{verify_manifest, write_signed_manifest, Hash256, HmacKey, ManifestEntry, ManifestFormat};
+/// let key = HmacKey::new(b"shared secret");
+/// let entries = [ManifestEntry::new("a.txt", Hash256::new([0; 32]))];
+/// let mut text = String::new();
+/// write_signed_manifest(&mut text, &entries, ManifestFormat::Gnu, &key).unwrap();
+/// assert_eq!(verify_manifest(&text, &key).unwrap(), entries);
+/// ```
+///
+/// # Arguments
+/// * `out` - Where the checksum file is written.
+/// * `entries` - The files to list.
+/// * `format` - The layout to write them in. The trailer follows the closing `]` of JSON, so the file is no longer plain JSON.
+/// * `key` - The key shared by the writers and readers of the manifest.
+pub fn write_signed_manifest<W: Write>(out: &mut W, entries: &[ManifestEntry], format: ManifestFormat, key: &HmacKey) -> fmt::Result {
+    let mut signing = Signing { out, hmac: HmacSha256::with_key(key) };
+    write_manifest(&mut signing, entries, format)?;
+    let tag = hex::encode(&signing.hmac.finalize());
+    writeln!(out, "{}{}", TRAILER_PREFIX, str::from_utf8(&tag).unwrap())
+}
+
+/// Checks the HMAC trailer of a signed checksum file, then parses the rest of it like `parse_manifest`.
+///
+/// # Arguments
+/// * `text` - The contents of the checksum file, as written by `write_signed_manifest`.
+/// * `key` - The key the manifest was signed with.
+///
+/// # Returns
+/// The entries, `Error::Parse` if the last line isn't a trailer or an entry is malformed, or `Error::Unauthenticated` if the HMAC doesn't match.
+pub fn verify_manifest(text: &str, key: &HmacKey) -> Result<Vec<ManifestEntry>, Error> {
+    const MISSING: Error = Error::Parse { reason: "manifest has no HMAC-SHA256 trailer" };
+    let without_newline = text.strip_suffix('\n').unwrap_or(text);
+    let without_newline = without_newline.strip_suffix('\r').unwrap_or(without_newline);
+    // the trailer covers everything up to the start of its line
+    let start = without_newline.rfind('\n').map_or(0, |i| i + 1);
+    let (body, trailer) = (&text[..start], &without_newline[start..]);
+    let tag = trailer.strip_prefix(TRAILER_PREFIX).ok_or(MISSING)?;
+    let tag = hex::decode(tag.as_bytes()).ok_or(Error::Parse { reason: "HMAC is not 64 hex characters" })?;
+    let mut hmac = HmacSha256::with_key(key);
+    hmac.update(body.as_bytes());
+    if !hmac.verify(&tag) {
+        return Err(Error::Unauthenticated);
+    }
+    parse_manifest(body)
+}
+
+/// Passes text on to a writer while computing its HMAC.
+struct Signing<'a, W> {
+    out: &'a mut W,
+    hmac: HmacSha256,
+}
+
+impl<W: Write> Write for Signing<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.hmac.update(s.as_bytes());
+        self.out.write_str(s)
+    }
+}
+
+fn needs_escaping(path: &str) -> bool {
+    path.contains(['\\', '\n', '\r'])
+}
+
+fn write_escaped<W: Write>(out: &mut W, path: &str) -> fmt::Result {
+    if !needs_escaping(path) {
+        return out.write_str(path);
+    }
+    for c in path.chars() {
+        match c {
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            c => out.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+fn unescape(path: &str) -> Result<String, Error> {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        out.push(match c {
+            '\\' => match chars.next() {
+                Some('\\') => '\\',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                _ => return Err(Error::Parse { reason: "invalid escape in path" }),
+            },
+            c => c,
+        });
+    }
+    Ok(out)
+}
+
+fn write_json_string<W: Write>(out: &mut W, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+fn write_json_u64<W: Write>(out: &mut W, value: Option<u64>) -> fmt::Result {
+    match value {
+        Some(value) => write!(out, "{}", value),
+        None => out.write_str("null"),
+    }
+}
+
+/// How deeply the value of an unknown key may nest arrays and objects, so a hostile manifest can't exhaust the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Just enough of a JSON parser for manifests, unknown keys are skipped whatever their value.
+struct Json<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Json<'_> {
+    fn manifest(&mut self) -> Result<Vec<ManifestEntry>, Error> {
+        let mut entries = Vec::new();
+        self.expect(b'[')?;
+        if !self.eat(b']') {
+            loop {
+                entries.push(self.entry()?);
+                if self.eat(b']') {
+                    break;
+                }
+                self.expect(b',')?;
+            }
+        }
+        self.skip_whitespace();
+        if self.pos != self.bytes.len() {
+            return Err(Error::Parse { reason: "trailing characters after JSON manifest" });
+        }
+        Ok(entries)
+    }
+
+    fn entry(&mut self) -> Result<ManifestEntry, Error> {
+        let (mut path, mut hash, mut size, mut mtime) = (None, None, None, None);
+        self.expect(b'{')?;
+        if !self.eat(b'}') {
+            loop {
+                let key = self.string()?;
+                self.expect(b':')?;
+                match key.as_str() {
+                    "path" => path = Some(self.string()?),
+                    "digest" => hash = Some(hex::decode(self.string()?.as_bytes()).ok_or(Error::Parse { reason: "digest is not 64 hex characters" })?),
+                    "algorithm" if self.string()? != "sha256" => return Err(Error::Parse { reason: "algorithm is not sha256" }),
+                    "algorithm" => {}
+                    "size" => size = self.optional_u64()?,
+                    "mtime" => mtime = self.optional_u64()?,
+                    _ => self.skip_value(0)?,
+                }
+                if self.eat(b'}') {
+                    break;
+                }
+                self.expect(b',')?;
+            }
+        }
+        let path = path.ok_or(Error::Parse { reason: "entry is missing its path" })?;
+        let hash = hash.ok_or(Error::Parse { reason: "entry is missing its digest" })?;
+        Ok(ManifestEntry { path, hash: Hash256::new(hash), size, mtime })
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.pos).is_some_and(|b| b.is_ascii_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        let found = self.peek() == Some(b);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), Error> {
+        if self.eat(b) {
+            Ok(())
+        } else {
+            Err(Error::Parse { reason: "malformed JSON manifest" })
+        }
+    }
+
+    fn string(&mut self) -> Result<String, Error> {
+        const MALFORMED: Error = Error::Parse { reason: "malformed JSON string" };
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            // copy everything up to the next quote or escape in one go
+            while self.bytes.get(self.pos).is_some_and(|&b| b != b'"' && b != b'\\') {
+                self.pos += 1;
+            }
+            // the input is a str and the run ends at an ASCII character, so it's valid UTF-8
+            out.push_str(str::from_utf8(&self.bytes[start..self.pos]).unwrap());
+            match self.bytes.get(self.pos) {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    let escape = *self.bytes.get(self.pos + 1).ok_or(MALFORMED)?;
+                    self.pos += 2;
+                    out.push(match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => {
+                            let high = self.hex4()?;
+                            let code = if (0xd800..0xdc00).contains(&high) {
+                                // a surrogate pair
+                                if self.bytes.get(self.pos..self.pos + 2) != Some(b"\\u") {
+                                    return Err(MALFORMED);
+                                }
+                                self.pos += 2;
+                                let low = self.hex4()?;
+                                if !(0xdc00..0xe000).contains(&low) {
+                                    return Err(MALFORMED);
+                                }
+                                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                            } else {
+                                high
+                            };
+                            char::from_u32(code).ok_or(MALFORMED)?
+                        }
+                        _ => return Err(MALFORMED),
+                    });
+                }
+                _ => return Err(MALFORMED),
+            }
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32, Error> {
+        let digits = self.bytes.get(self.pos..self.pos + 4).and_then(|digits| str::from_utf8(digits).ok());
+        let value = digits.and_then(|digits| u32::from_str_radix(digits, 16).ok()).ok_or(Error::Parse { reason: "malformed JSON string" })?;
+        self.pos += 4;
+        Ok(value)
+    }
+
+    fn optional_u64(&mut self) -> Result<Option<u64>, Error> {
+        if self.literal("null") {
+            return Ok(None);
+        }
+        let start = self.pos;
+        while self.bytes.get(self.pos).is_some_and(u8::is_ascii_digit) {
+            self.pos += 1;
+        }
+        str::from_utf8(&self.bytes[start..self.pos]).unwrap().parse().map(Some).map_err(|_| Error::Parse { reason: "expected an unsigned integer or null" })
+    }
+
+    fn literal(&mut self, literal: &str) -> bool {
+        self.skip_whitespace();
+        let found = self.bytes[self.pos..].starts_with(literal.as_bytes());
+        if found {
+            self.pos += literal.len();
+        }
+        found
+    }
+
+    /// Skips a value nested `depth` arrays or objects deep within the value of an unknown key.
+    fn skip_value(&mut self, depth: usize) -> Result<(), Error> {
+        if depth == MAX_DEPTH && matches!(self.peek(), Some(b'[' | b'{')) {
+            return Err(Error::Parse { reason: "JSON manifest is nested too deeply" });
+        }
+        match self.peek() {
+            Some(b'"') => self.string().map(drop),
+            Some(b'[') => {
+                self.pos += 1;
+                if !self.eat(b']') {
+                    loop {
+                        self.skip_value(depth + 1)?;
+                        if self.eat(b']') {
+                            return Ok(());
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                Ok(())
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                if !self.eat(b'}') {
+                    loop {
+                        self.string()?;
+                        self.expect(b':')?;
+                        self.skip_value(depth + 1)?;
+                        if self.eat(b'}') {
+                            return Ok(());
+                        }
+                        self.expect(b',')?;
+                    }
+                }
+                Ok(())
+            }
+            _ => {
+                if self.literal("null") || self.literal("true") || self.literal("false") {
+                    return Ok(());
+                }
+                let start = self.pos;
+                while self.bytes.get(self.pos).is_some_and(|b| matches!(b, b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')) {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(Error::Parse { reason: "malformed JSON manifest" });
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+    use alloc::vec;
+
+    const HELLO: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn hello(path: &str) -> ManifestEntry {
+        ManifestEntry::new(path, Hash256::new(Sha256::new().digest(b"hello")))
+    }
+
+    fn write(entries: &[ManifestEntry], format: ManifestFormat) -> String {
+        let mut out = String::new();
+        write_manifest(&mut out, entries, format).unwrap();
+        out
+    }
+
+    #[test]
+    fn reads_and_writes_lines() {
+        let entries = vec![hello("a.txt"), hello("dir/with space"), hello("back\\slash\nnewline")];
+        let gnu = write(&entries, ManifestFormat::Gnu);
+        assert_eq!(gnu, alloc::format!("{0}  a.txt\n{0}  dir/with space\n\\{0}  back\\\\slash\\nnewline\n", HELLO));
+        let bsd = write(&entries, ManifestFormat::Bsd);
+        assert_eq!(bsd, alloc::format!("SHA256 (a.txt) = {0}\nSHA256 (dir/with space) = {0}\n\\SHA256 (back\\\\slash\\nnewline) = {0}\n", HELLO));
+        assert_eq!(parse_manifest(&gnu).unwrap(), entries);
+        assert_eq!(parse_manifest(&bsd).unwrap(), entries);
+
+        // binary mode, CRLF line endings and a BSD path containing ") = "
+        assert_eq!(ManifestEntry::parse_line(&alloc::format!("{} *a.txt\r", HELLO)).unwrap(), hello("a.txt"));
+        assert_eq!(ManifestEntry::parse_line(&alloc::format!("SHA256 (a) = b) = {}", HELLO)).unwrap(), hello("a) = b"));
+        for line in ["", "abc  a.txt", &alloc::format!("{} a.txt", HELLO), &alloc::format!("{}  ", HELLO), &alloc::format!("\\{}  a\\x", HELLO)] {
+            assert!(ManifestEntry::parse_line(line).is_err(), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn writes_other_encodings() {
+        let mut line = String::new();
+        hello("a\nb").write_line_encoded(&mut line, "sha256-x").unwrap();
+        assert_eq!(line, "\\sha256-x  a\\nb");
+        line.clear();
+        hello("a b").write_line_encoded(&mut line, "x").unwrap();
+        assert_eq!(line, "x  a b");
+    }
+
+    #[test]
+    fn reads_and_writes_json() {
+        let mut entry = hello("quote\" tab\t");
+        entry.size = Some(5);
+        entry.mtime = Some(1_700_000_000);
+        let entries = vec![entry, hello("b")];
+        let json = write(&entries, ManifestFormat::Json);
+        assert_eq!(
+            json,
+            alloc::format!(
+                "[\n  {{\"path\": \"quote\\\" tab\\t\", \"size\": 5, \"mtime\": 1700000000, \"algorithm\": \"sha256\", \"digest\": \"{0}\"}},\n  {{\"path\": \"b\", \"size\": null, \"mtime\": null, \"algorithm\": \"sha256\", \"digest\": \"{0}\"}}\n]\n",
+                HELLO
+            )
+        );
+        assert_eq!(parse_manifest(&json).unwrap(), entries);
+        assert_eq!(write(&[], ManifestFormat::Json), "[\n]\n");
+        assert_eq!(parse_manifest(" [ ] ").unwrap(), []);
+
+        // other producers may escape differently and add fields
+        let other = alloc::format!(r#"[{{"extra": [1, {{"x": null}}, -2.5e3, true], "digest": "{}", "path": "\u00e9\ud83d\ude00\/"}}]"#, HELLO);
+        assert_eq!(parse_manifest(&other).unwrap(), [hello("é😀/")]);
+        // deeply nested unknown values are refused rather than overflowing the stack
+        let nested = |depth| alloc::format!(r#"[{{"x": {}{}, "digest": "{}", "path": "a"}}]"#, "[".repeat(depth), "]".repeat(depth), HELLO);
+        assert!(parse_manifest(&nested(MAX_DEPTH)).is_ok());
+        assert!(matches!(parse_manifest(&nested(MAX_DEPTH + 1)), Err(Error::Parse { .. })));
+        assert!(parse_manifest(&nested(200_000)).is_err());
+        for json in ["[", "[{}]", &alloc::format!(r#"[{{"path": "a", "digest": "{}", "algorithm": "md5"}}]"#, HELLO), "[] x"] {
+            assert!(parse_manifest(json).is_err(), "{:?}", json);
+        }
+    }
+
+    #[test]
+    fn signs_and_verifies() {
+        let key = HmacKey::new(b"key");
+        let entries = vec![hello("a.txt"), hello("b.txt")];
+        for format in [ManifestFormat::Gnu, ManifestFormat::Bsd, ManifestFormat::Json] {
+            let mut signed = String::new();
+            write_signed_manifest(&mut signed, &entries, format, &key).unwrap();
+            let body = write(&entries, format);
+            let tag = hex::encode(&crate::hmac_sha256(b"key", body.as_bytes()));
+            assert_eq!(signed, alloc::format!("{}HMAC-SHA256 = {}\n", body, str::from_utf8(&tag).unwrap()));
+            assert_eq!(verify_manifest(&signed, &key).unwrap(), entries);
+
+            // a changed entry or line endings, a different key, or no trailer at all
+            assert!(matches!(verify_manifest(&signed.replace('\n', "\r\n"), &key), Err(Error::Unauthenticated)));
+            assert!(matches!(verify_manifest(&signed.replacen("a.txt", "c.txt", 1), &key), Err(Error::Unauthenticated)));
+            assert!(matches!(verify_manifest(&signed, &HmacKey::new(b"other")), Err(Error::Unauthenticated)));
+            assert!(matches!(verify_manifest(&body, &key), Err(Error::Parse { .. })));
+        }
+        // an empty manifest is still signed
+        let mut signed = String::new();
+        write_signed_manifest(&mut signed, &[], ManifestFormat::Gnu, &key).unwrap();
+        assert_eq!(verify_manifest(&signed, &key).unwrap(), []);
+    }
+}
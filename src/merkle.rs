@@ -0,0 +1,198 @@
+//! Merkle trees over a list of leaves, with inclusion proofs that a leaf is in a tree with a given root.
+//!
+//! The tree is that of RFC 6962 (Certificate Transparency), the same hashing as the `tree` module uses over chunks of one buffer:
+//! `leaf = SHA-256(0x00 || data)`, `node = SHA-256(0x01 || left || right)`, and for `n > 1` leaves the left subtree holds the largest
+//! power of two of leaves less than `n`. So roots and proofs interoperate with CT logs and other RFC 6962/9162 implementations.
+//! The root of a tree with no leaves is `SHA-256("")`.
+//!
+//! ```
+//! use sha_256::merkle::{verify, Tree};
+//! let tree = Tree::from_leaves(["alice", "bob", "carol"]);
+//! let proof = tree.prove(1).unwrap();
+//! assert!(verify(&tree.root(), &proof, b"bob"));
+//! assert!(!verify(&tree.root(), &proof, b"mallory"));
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::tree::{leaf as hash_leaf, node};
+use crate::{Hash256, Sha256};
+
+/// A Merkle tree, keeping every level so proofs can be read off it.
+#[derive(Clone, Debug)]
+pub struct Tree {
+    // levels[0] are the leaf hashes and the last level the root, a node without a sibling is carried up to the next level unchanged
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+/// The proof that a leaf is in a tree: its position, and the hashes of the sibling subtrees on the path from it to the root.
+///
+/// The path is the RFC 9162 inclusion path, nearest the leaf first.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Proof {
+    /// The index of the leaf.
+    pub index: u64,
+    /// The number of leaves in the tree.
+    pub leaf_count: u64,
+    /// The sibling hashes, from the leaf's up to one of the root's children.
+    pub path: Vec<Hash256>,
+}
+
+impl Tree {
+    /// Builds the tree over the given leaves, in order.
+    ///
+    /// # Arguments
+    /// * `leaves` - The leaf data, e.g. records or log entries. Each is hashed with the leaf prefix, so it can be any length.
+    pub fn from_leaves<I, T>(leaves: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+        T: AsRef<[u8]>,
+    {
+        let mut levels = Vec::new();
+        let mut level: Vec<[u8; 32]> = leaves.into_iter().map(|data| hash_leaf(data.as_ref())).collect();
+        while level.len() > 1 {
+            let next = level.chunks(2).map(|pair| if let [left, right] = pair { node(left, right) } else { pair[0] }).collect();
+            levels.push(level);
+            level = next;
+        }
+        levels.push(level);
+        Self { levels }
+    }
+
+    /// Returns the number of leaves.
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns whether the tree has no leaves.
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Returns the root hash, which commits to every leaf and its position.
+    pub fn root(&self) -> Hash256 {
+        match self.levels.last().and_then(|level| level.first()) {
+            Some(root) => Hash256::new(*root),
+            None => Hash256::new(Sha256::new().digest(b"")),
+        }
+    }
+
+    /// Builds the inclusion proof for a leaf.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the leaf, in the order passed to `from_leaves`.
+    ///
+    /// # Returns
+    /// The proof, or `None` if there is no leaf `index`.
+    pub fn prove(&self, index: usize) -> Option<Proof> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut path = Vec::new();
+        let mut i = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            // the last node of a level has no sibling when the level is odd, and it contributes nothing to the path
+            if let Some(sibling) = level.get(i ^ 1) {
+                path.push(Hash256::new(*sibling));
+            }
+            i /= 2;
+        }
+        Some(Proof { index: index as u64, leaf_count: self.len() as u64, path })
+    }
+}
+
+/// Checks an inclusion proof, that `leaf` is at `proof.index` in a tree of `proof.leaf_count` leaves with the given root.
+///
+/// # Arguments
+/// * `root` - The trusted root, e.g. from a signed tree head.
+/// * `proof` - The proof, from `Tree::prove`.
+/// * `leaf` - The leaf data, as it was passed to `Tree::from_leaves`.
+///
+/// # Returns
+/// `true` if the proof is valid. `false` if the leaf, index or any path hash is wrong, or the path is the wrong length for the tree.
+pub fn verify(root: &Hash256, proof: &Proof, leaf: &[u8]) -> bool {
+    if proof.index >= proof.leaf_count {
+        return false;
+    }
+    // RFC 9162 section 2.1.3.2
+    let mut index = proof.index;
+    let mut last = proof.leaf_count - 1;
+    let mut hash = hash_leaf(leaf);
+    for sibling in &proof.path {
+        if last == 0 {
+            return false;
+        }
+        if index & 1 == 1 || index == last {
+            hash = node(sibling.as_bytes(), &hash);
+            // skip the levels this subtree was carried up without a sibling
+            while index & 1 == 0 && index != 0 {
+                index >>= 1;
+                last >>= 1;
+            }
+        } else {
+            hash = node(&hash, sibling.as_bytes());
+        }
+        index >>= 1;
+        last >>= 1;
+    }
+    last == 0 && hash == *root.as_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tree::tree_hash_with;
+
+    #[test]
+    fn roots_match_the_tree_hash() {
+        let data: [u8; 40] = core::array::from_fn(|i| i as u8);
+        for len in 1..=data.len() {
+            let tree = Tree::from_leaves(data[..len].chunks(1));
+            assert_eq!(tree.len(), len);
+            assert_eq!(tree.root(), tree_hash_with(&data[..len], 1), "len {}", len);
+        }
+        let empty = Tree::from_leaves(core::iter::empty::<&[u8]>());
+        assert!(empty.is_empty());
+        assert_eq!(empty.root(), Hash256::new(crate::sha256(b"")));
+        assert_eq!(empty.prove(0), None);
+    }
+
+    #[test]
+    fn proves_every_leaf() {
+        let leaves: Vec<[u8; 2]> = (0..20u16).map(|i| i.to_be_bytes()).collect();
+        for len in 1..=leaves.len() {
+            let tree = Tree::from_leaves(&leaves[..len]);
+            let root = tree.root();
+            for (index, leaf) in leaves[..len].iter().enumerate() {
+                let proof = tree.prove(index).unwrap();
+                assert!(verify(&root, &proof, leaf), "len {} index {}", len, index);
+                assert!(proof.path.len() <= 64 - (len as u64 - 1).leading_zeros() as usize);
+                // the wrong leaf, position, a tree too small to hold it, or the wrong path length
+                assert!(!verify(&root, &proof, b"x"));
+                assert!(!verify(&root, &Proof { index: (index as u64 + 1) % len as u64, ..proof.clone() }, leaf) || len == 1);
+                assert!(!verify(&root, &Proof { leaf_count: index as u64, ..proof.clone() }, leaf));
+                if let Some((last, rest)) = proof.path.split_last() {
+                    assert!(!verify(&root, &Proof { path: rest.to_vec(), ..proof.clone() }, leaf));
+                    let mut path = proof.path.clone();
+                    path.push(*last);
+                    assert!(!verify(&root, &Proof { path, ..proof.clone() }, leaf));
+                }
+            }
+            assert_eq!(tree.prove(len), None);
+        }
+    }
+
+    #[test]
+    fn rfc_9162_paths() {
+        // the 7 leaf tree of RFC 9162 section 2.1.5: d0 d1 d2 d3 d4 d5 d6
+        let leaves = ["0", "1", "2", "3", "4", "5", "6"];
+        let tree = Tree::from_leaves(leaves);
+        let hash = |range: core::ops::Range<usize>| Tree::from_leaves(&leaves[range]).root();
+        // the path for d0 is [b, h, l], for d3 [c, g, l] and for d4 [f, j, k]
+        assert_eq!(tree.prove(0).unwrap().path, [hash(1..2), hash(2..4), hash(4..7)]);
+        assert_eq!(tree.prove(3).unwrap().path, [hash(2..3), hash(0..2), hash(4..7)]);
+        assert_eq!(tree.prove(4).unwrap().path, [hash(5..6), hash(6..7), hash(0..4)]);
+        // d6 is carried up past the level where it has no sibling
+        assert_eq!(tree.prove(6).unwrap().path, [hash(4..6), hash(0..4)]);
+    }
+}
@@ -0,0 +1,145 @@
+//! Searching for a block header nonce, hashing only what changes per nonce.
+//!
+//! A header's double SHA-256 takes three compressions: two for the 80 header bytes and one for the 32 byte first hash.
+//! The nonce is in the last 16 bytes, so the first block's midstate is the same for every nonce and `HeaderMiner` computes it once,
+//! leaving two compressions per nonce.
+//!
+//! ```
+//! use sha_256::mining::HeaderMiner;
+//! use sha_256::Target;
+//! # let header = [0u8; 80];
+//! let miner = HeaderMiner::new(&header);
+//! if let Some((nonce, hash)) = miner.scan_nonces(.., &Target::from_leading_zero_bits(8)) {
+//!     # assert_eq!(hash, miner.hash(nonce));
+//!     println!("nonce {} gives {}", nonce, hash);
+//! }
+//! ```
+
+use core::ops::{Bound, RangeBounds};
+
+use crate::{compress, Hash256, Target, H};
+
+/// A block header with its first 64 bytes already hashed, for hashing it with many different nonces.
+#[derive(Clone, Debug)]
+pub struct HeaderMiner {
+    midstate: [u32; 8],
+    // the second block of the padded header, with the nonce at 12..16
+    tail: [u8; 64],
+}
+
+impl HeaderMiner {
+    /// Hashes the first block of a header, the part no nonce changes.
+    ///
+    /// # Arguments
+    /// * `header` - The serialized header, see `BlockHeader::to_bytes`. Its nonce is ignored.
+    pub fn new(header: &[u8; 80]) -> Self {
+        let mut midstate = H;
+        compress(&mut midstate, header[..64].try_into().unwrap());
+        let mut tail = [0u8; 64];
+        tail[..16].copy_from_slice(&header[64..]);
+        tail[16] = 0x80;
+        tail[56..].copy_from_slice(&(80u64 * 8).to_be_bytes());
+        Self { midstate, tail }
+    }
+
+    /// Returns the header's hash with the given nonce, the same as `sha256d` of the header bytes.
+    ///
+    /// # Arguments
+    /// * `nonce` - The nonce, written into the header little-endian.
+    pub fn hash(&self, nonce: u32) -> Hash256 {
+        let mut tail = self.tail;
+        tail[12..16].copy_from_slice(&nonce.to_le_bytes());
+        let mut state = self.midstate;
+        compress(&mut state, &tail);
+
+        // the first hash padded to a block of its own
+        let mut block = [0u8; 64];
+        for (out, h) in block.chunks_exact_mut(4).zip(state) {
+            out.copy_from_slice(&h.to_be_bytes());
+        }
+        block[32] = 0x80;
+        block[56..].copy_from_slice(&(32u64 * 8).to_be_bytes());
+        let mut state = H;
+        compress(&mut state, &block);
+
+        let mut hash = [0u8; 32];
+        for (out, h) in hash.chunks_exact_mut(4).zip(state) {
+            out.copy_from_slice(&h.to_be_bytes());
+        }
+        Hash256::new(hash)
+    }
+
+    /// Tries nonces in order until the header's hash meets the target.
+    ///
+    /// The hash is compared with the target as a little-endian integer, as Bitcoin does, the same as `HeaderChain` checks it.
+    ///
+    /// # Arguments
+    /// * `nonces` - The nonces to try, e.g. `..` for all of them or a slice of the space per thread.
+    /// * `target` - The target to meet, e.g. from `Target::from_compact` of the header's `bits`.
+    ///
+    /// # Returns
+    /// The first nonce which meets the target and the header's hash with it, or `None` if no nonce in the range does.
+    pub fn scan_nonces<R: RangeBounds<u32>>(&self, nonces: R, target: &Target) -> Option<(u32, Hash256)> {
+        let start = match nonces.start_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match nonces.end_bound() {
+            Bound::Included(&n) => n,
+            Bound::Excluded(&n) => n.checked_sub(1)?,
+            Bound::Unbounded => u32::MAX,
+        };
+        (start..=end).find_map(|nonce| {
+            let hash = self.hash(nonce);
+            let mut reversed = *hash.as_bytes();
+            reversed.reverse();
+            target.is_met_by(&Hash256::new(reversed)).then_some((nonce, hash))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{sha256d, BlockHeader};
+
+    fn genesis() -> [u8; 80] {
+        let hex = "0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c";
+        core::array::from_fn(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap())
+    }
+
+    #[test]
+    fn finds_the_genesis_nonce() {
+        let header = genesis();
+        let fields = BlockHeader::parse(&header);
+        let miner = HeaderMiner::new(&header);
+        assert_eq!(miner.hash(fields.nonce), sha256d(&header));
+        let target = Target::from_compact(fields.bits).unwrap();
+        assert_eq!(miner.scan_nonces(fields.nonce - 1000..fields.nonce + 1000, &target), Some((fields.nonce, sha256d(&header))));
+        assert_eq!(miner.scan_nonces(fields.nonce - 1000..fields.nonce, &target), None);
+        assert_eq!(miner.scan_nonces(5..5, &target), None);
+    }
+
+    #[test]
+    fn matches_hashing_each_header() {
+        let mut fields = BlockHeader::parse(&genesis());
+        let miner = HeaderMiner::new(&fields.to_bytes());
+        let target = Target::from_leading_zero_bits(6);
+        let mut expected = None;
+        for nonce in 0..1000 {
+            fields.nonce = nonce;
+            let hash = sha256d(&fields.to_bytes());
+            assert_eq!(miner.hash(nonce), hash);
+            let mut reversed = *hash.as_bytes();
+            reversed.reverse();
+            if expected.is_none() && target.is_met_by(&Hash256::new(reversed)) {
+                expected = Some((nonce, hash));
+            }
+        }
+        assert!(expected.is_some());
+        assert_eq!(miner.scan_nonces(..1000, &target), expected);
+        // the nonces at the ends of the range
+        assert_eq!(miner.scan_nonces(u32::MAX.., &Target::MAX), Some((u32::MAX, miner.hash(u32::MAX))));
+    }
+}
@@ -0,0 +1,83 @@
+//! Kotlin/Swift bindings via UniFFI, the interface is defined in `src/sha_256.udl`.
+//!
+//! Build the library with `cargo rustc --release --features uniffi --crate-type cdylib`, then generate the bindings with
+//! `uniffi-bindgen generate --library <path to the library> --language kotlin` (or `swift`).
+
+// the generated scaffolding trips this lint
+#![allow(clippy::empty_line_after_doc_comments)]
+
+use std::sync::Mutex;
+use std::vec::Vec;
+
+use crate::{HmacSha256, Sha256};
+
+uniffi::include_scaffolding!("sha_256");
+
+fn sha256(data: Vec<u8>) -> Vec<u8> {
+    Sha256::new().digest(&data).to_vec()
+}
+
+fn hmac_sha256(key: Vec<u8>, data: Vec<u8>) -> Vec<u8> {
+    crate::hmac_sha256(&key, &data).to_vec()
+}
+
+/// A streaming hasher shared with the foreign language.
+pub struct Sha256Hasher {
+    inner: Mutex<Sha256>,
+}
+
+impl Sha256Hasher {
+    fn new() -> Self {
+        Self { inner: Mutex::new(Sha256::new()) }
+    }
+
+    fn update(&self, data: Vec<u8>) {
+        self.inner.lock().unwrap().update(&data);
+    }
+
+    fn finalize(&self) -> Vec<u8> {
+        let sha256 = core::mem::take(&mut *self.inner.lock().unwrap());
+        sha256.finalize().to_vec()
+    }
+}
+
+/// A streaming HMAC shared with the foreign language.
+pub struct HmacSha256Hasher {
+    keyed: HmacSha256, // the state after absorbing the key, restored after each finalize
+    inner: Mutex<HmacSha256>,
+}
+
+impl HmacSha256Hasher {
+    fn new(key: Vec<u8>) -> Self {
+        let keyed = HmacSha256::new(&key);
+        Self { inner: Mutex::new(keyed.clone()), keyed }
+    }
+
+    fn update(&self, data: Vec<u8>) {
+        self.inner.lock().unwrap().update(&data);
+    }
+
+    fn finalize(&self) -> Vec<u8> {
+        let hmac = core::mem::replace(&mut *self.inner.lock().unwrap(), self.keyed.clone());
+        hmac.finalize().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let hasher = Sha256Hasher::new();
+        hasher.update(b"hel".to_vec());
+        hasher.update(b"lo".to_vec());
+        assert_eq!(hasher.finalize(), sha256(b"hello".to_vec()));
+        assert_eq!(hasher.finalize(), sha256(Vec::new()));
+
+        let hmac = HmacSha256Hasher::new(b"key".to_vec());
+        hmac.update(b"hello".to_vec());
+        assert_eq!(hmac.finalize(), hmac_sha256(b"key".to_vec(), b"hello".to_vec()));
+        assert_eq!(hmac.finalize(), hmac_sha256(b"key".to_vec(), Vec::new()));
+    }
+}
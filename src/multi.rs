@@ -0,0 +1,288 @@
+//! Computing several digests of the same data in one pass.
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::{Backend, Sha256};
+
+/// A streaming hash function which can be used as a trait object, with a variable length output.
+///
+/// Implemented for every `Backend` (and so `Sha256`). Implement it for other hash functions to feed them from a `MultiHasher`.
+pub trait DynHasher {
+    /// Absorbs more of the message.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    fn update(&mut self, data: &[u8]);
+
+    /// Consumes the hasher, returning the digest of everything absorbed.
+    fn finalize_boxed(self: Box<Self>) -> Vec<u8>;
+}
+
+impl<B: Backend> DynHasher for B {
+    fn update(&mut self, data: &[u8]) {
+        Backend::update(self, data);
+    }
+
+    fn finalize_boxed(self: Box<Self>) -> Vec<u8> {
+        Backend::finalize(*self).to_vec()
+    }
+}
+
+/// Feeds one stream of data into several hashers at once, so the data only has to be read once.
+///
+/// ```
+/// use sha_256::{MultiHasher, Sha256};
+/// let mut hasher = MultiHasher::new().with(Sha256::new()).with(Sha256::new());
+/// hasher.update(b"hello");
+/// let digests = hasher.finalize();
+/// assert_eq!(digests.len(), 2);
+/// ```
+#[derive(Default)]
+pub struct MultiHasher {
+    hashers: Vec<Box<dyn DynHasher + Send>>,
+}
+
+impl MultiHasher {
+    /// Creates a `MultiHasher` without any hashers.
+    pub fn new() -> Self {
+        Self { hashers: Vec::new() }
+    }
+
+    /// Adds a hasher, its digest will be at the same position in `finalize`'s output as the order it was added in.
+    ///
+    /// # Arguments
+    /// * `hasher` - The hasher to be fed.
+    pub fn with<H: DynHasher + Send + 'static>(mut self, hasher: H) -> Self {
+        self.push(hasher);
+        self
+    }
+
+    /// Adds a hasher, like `with` but in place.
+    ///
+    /// # Arguments
+    /// * `hasher` - The hasher to be fed.
+    pub fn push<H: DynHasher + Send + 'static>(&mut self, hasher: H) {
+        self.hashers.push(Box::new(hasher));
+    }
+
+    /// Absorbs more of the message into every hasher.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        for hasher in &mut self.hashers {
+            hasher.update(data);
+        }
+    }
+
+    /// Finishes every hasher.
+    ///
+    /// # Returns
+    /// The digests, in the order the hashers were added.
+    pub fn finalize(self) -> Vec<Vec<u8>> {
+        self.hashers.into_iter().map(|hasher| hasher.finalize_boxed()).collect()
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::io::Write for MultiHasher {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A `DynHasher` producing the digest of each fixed size chunk of the message, e.g. for content-defined storage or resumable uploads.
+///
+/// Its output is the 32 byte digests of the chunks concatenated in order. A final partial chunk gets a digest too, an empty message gets none.
+pub struct ChunkedSha256 {
+    chunk_size: u64,
+    sha256: Sha256,
+    /// How much of the current chunk has been absorbed.
+    filled: u64,
+    digests: Vec<u8>,
+}
+
+impl ChunkedSha256 {
+    /// Creates a hasher for chunks of `chunk_size` bytes.
+    ///
+    /// # Arguments
+    /// * `chunk_size` - The size of each chunk, at least 1.
+    ///
+    /// # Panics
+    /// If `chunk_size` is 0.
+    pub fn new(chunk_size: u64) -> Self {
+        assert!(chunk_size > 0, "chunks must be at least 1 byte");
+        Self { chunk_size, sha256: Sha256::new(), filled: 0, digests: Vec::new() }
+    }
+}
+
+impl DynHasher for ChunkedSha256 {
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let n = ((self.chunk_size - self.filled).min(data.len() as u64)) as usize;
+            self.sha256.update(&data[..n]);
+            self.filled += n as u64;
+            data = &data[n..];
+            if self.filled == self.chunk_size {
+                self.digests.extend_from_slice(&self.sha256.finalize_reset());
+                self.filled = 0;
+            }
+        }
+    }
+
+    fn finalize_boxed(mut self: Box<Self>) -> Vec<u8> {
+        if self.filled > 0 {
+            let last = self.sha256.finalize_reset();
+            self.digests.extend_from_slice(&last);
+        }
+        self.digests
+    }
+}
+
+/// A writer which passes everything written to an inner writer and feeds the same bytes to any number of hashers,
+/// e.g. computing a file's digest and its chunks' digests while saving it, in one pass.
+///
+/// ```
+/// use sha_256::{BroadcastWriter, ChunkedSha256, Sha256};
+/// use std::io::Write;
+/// let mut writer = BroadcastWriter::new(Vec::new()).with(Sha256::new()).with(ChunkedSha256::new(4096));
+/// writer.write_all(b"hello")?;
+/// let (saved, digests) = writer.finalize();
+/// assert_eq!(saved, b"hello");
+/// assert_eq!(digests[0], Sha256::new().digest(b"hello"));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[cfg(feature = "std")]
+pub struct BroadcastWriter<W> {
+    inner: W,
+    hashers: MultiHasher,
+}
+
+#[cfg(feature = "std")]
+impl<W> BroadcastWriter<W> {
+    /// Wraps a writer, without any hashers yet.
+    ///
+    /// # Arguments
+    /// * `inner` - The writer the data is passed on to, `io::sink()` if only the digests are wanted.
+    pub fn new(inner: W) -> Self {
+        Self { inner, hashers: MultiHasher::new() }
+    }
+
+    /// Adds a hasher, its digest will be at the same position in `finalize`'s output as the order it was added in.
+    ///
+    /// # Arguments
+    /// * `hasher` - The hasher to be fed.
+    pub fn with<H: DynHasher + Send + 'static>(mut self, hasher: H) -> Self {
+        self.hashers.push(hasher);
+        self
+    }
+
+    /// Returns a reference to the wrapped writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped writer. Bytes written directly to it are not hashed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Finishes every hasher.
+    ///
+    /// # Returns
+    /// The wrapped writer and the digests, in the order the hashers were added.
+    pub fn finalize(self) -> (W, Vec<Vec<u8>>) {
+        (self.inner, self.hashers.finalize())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> std::io::Write for BroadcastWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        // only hash what the inner writer accepted, the rest will be passed to write again
+        let n = self.inner.write(buf)?;
+        self.hashers.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Length(u64);
+
+    impl DynHasher for Length {
+        fn update(&mut self, data: &[u8]) {
+            self.0 += data.len() as u64;
+        }
+
+        fn finalize_boxed(self: Box<Self>) -> Vec<u8> {
+            self.0.to_be_bytes().to_vec()
+        }
+    }
+
+    #[test]
+    fn feeds_every_hasher() {
+        let mut hasher = MultiHasher::new().with(Sha256::new()).with(Length(0));
+        hasher.update(b"hel");
+        hasher.update(b"lo");
+        let digests = hasher.finalize();
+        assert_eq!(digests[0], Sha256::new().digest(b"hello"));
+        assert_eq!(digests[1], 5u64.to_be_bytes());
+    }
+
+    #[test]
+    fn chunked_digests() {
+        let data: Vec<u8> = (0..250u32).map(|i| i as u8).collect();
+        let mut hasher = Box::new(ChunkedSha256::new(100));
+        for piece in data.chunks(33) {
+            hasher.update(piece);
+        }
+        let digests = hasher.finalize_boxed();
+        let expected: Vec<u8> = data.chunks(100).flat_map(|chunk| Sha256::new().digest(chunk)).collect();
+        assert_eq!(digests, expected);
+        assert!(Box::new(ChunkedSha256::new(100)).finalize_boxed().is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn broadcast_writer_feeds_sink_and_hashers() {
+        use std::io::Write;
+
+        // a sink accepting at most 7 bytes a write
+        struct Short(Vec<u8>);
+
+        impl Write for Short {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                let n = buf.len().min(7);
+                self.0.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        let mut writer = BroadcastWriter::new(Short(Vec::new())).with(Sha256::new()).with(ChunkedSha256::new(256)).with(Length(0));
+        writer.write_all(&data).unwrap();
+        let (sink, digests) = writer.finalize();
+        assert_eq!(sink.0, data);
+        assert_eq!(digests[0], Sha256::new().digest(&data));
+        assert_eq!(digests[1].len(), 4 * 32);
+        assert_eq!(digests[1][96..], Sha256::new().digest(&data[768..]));
+        assert_eq!(digests[2], 1000u64.to_be_bytes());
+    }
+}
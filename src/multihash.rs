@@ -0,0 +1,209 @@
+//! [Multihash](https://multiformats.io/multihash/) and CIDv1 encodings of digests, for IPFS and libp2p.
+//!
+//! A multihash is the hash function's code (`0x12` for sha2-256) and the digest length as varints, followed by the digest.
+//! A CIDv1 adds the version and the codec of the content, e.g. `RAW` for plain bytes, and is usually written in base32 with a `b` multibase prefix.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+use crate::{Error, Hash256};
+
+/// The multicodec code of sha2-256.
+pub const SHA2_256: u64 = 0x12;
+/// The multicodec code for raw bytes, the codec of a file added to IPFS with `--raw-leaves` or `--cid-version 1`.
+pub const RAW: u64 = 0x55;
+/// The multicodec code for MerkleDAG protobuf nodes, the codec of UnixFS directories and chunked files.
+pub const DAG_PB: u64 = 0x70;
+/// The multicodec code for MerkleDAG CBOR nodes.
+pub const DAG_CBOR: u64 = 0x71;
+
+// RFC 4648 base32 in lowercase, the alphabet of the `b` multibase
+const BASE32: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+
+impl Hash256 {
+    /// Encodes the digest as a sha2-256 multihash.
+    ///
+    /// # Returns
+    /// `0x12`, the digest length `0x20` and the 32 digest bytes.
+    pub fn to_multihash(&self) -> [u8; 34] {
+        let mut multihash = [0u8; 34];
+        multihash[0] = SHA2_256 as u8;
+        multihash[1] = 32;
+        multihash[2..].copy_from_slice(self.as_bytes());
+        multihash
+    }
+
+    /// Decodes a sha2-256 multihash.
+    ///
+    /// # Arguments
+    /// * `multihash` - The binary multihash, with nothing after the digest.
+    ///
+    /// # Returns
+    /// The hash, or `Error::Parse` if it's the multihash of another hash function or malformed.
+    pub fn from_multihash(multihash: &[u8]) -> Result<Self, Error> {
+        let mut rest = multihash;
+        if read_varint(&mut rest)? != SHA2_256 {
+            return Err(Error::Parse { reason: "multihash is not sha2-256" });
+        }
+        if read_varint(&mut rest)? != 32 {
+            return Err(Error::Parse { reason: "multihash length is not 32" });
+        }
+        rest.try_into().map(Hash256::new).map_err(|_| Error::Parse { reason: "multihash length doesn't match its digest" })
+    }
+
+    /// Formats the digest as a CIDv1 in base32, the default string form of CIDv1, e.g. `bafkrei...` for raw content.
+    ///
+    /// ```
+    /// let hash = sha_256::Hash256::from(sha_256::sha256(b""));
+    /// assert_eq!(hash.to_cid(sha_256::multihash::RAW), "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku");
+    /// ```
+    ///
+    /// # Arguments
+    /// * `codec` - The multicodec code of the hashed content, e.g. `RAW` or `DAG_PB`.
+    #[cfg(feature = "alloc")]
+    pub fn to_cid(&self, codec: u64) -> String {
+        let mut cid = [0u8; 2 * 10 + 34];
+        let mut len = write_varint(&mut cid, 1);
+        len += write_varint(&mut cid[len..], codec);
+        cid[len..len + 34].copy_from_slice(&self.to_multihash());
+        len += 34;
+
+        // base32 without padding, 5 bits per character
+        let mut encoded = String::with_capacity(1 + (len * 8).div_ceil(5));
+        encoded.push('b');
+        let (mut bits, mut n_bits) = (0u32, 0);
+        for &byte in &cid[..len] {
+            bits = bits << 8 | byte as u32;
+            n_bits += 8;
+            while n_bits >= 5 {
+                n_bits -= 5;
+                encoded.push(char::from(BASE32[(bits >> n_bits) as usize & 31]));
+            }
+        }
+        if n_bits > 0 {
+            encoded.push(char::from(BASE32[(bits << (5 - n_bits)) as usize & 31]));
+        }
+        encoded
+    }
+
+    /// Parses a base32 CIDv1 of sha2-256 content.
+    ///
+    /// # Arguments
+    /// * `cid` - The CID, starting with the `b` multibase prefix. CIDv0 (`Qm...`) and other bases aren't supported.
+    ///
+    /// # Returns
+    /// The codec of the content and its hash, or `Error::Parse` if the CID is malformed, isn't a base32 CIDv1 or its multihash isn't sha2-256.
+    pub fn from_cid(cid: &str) -> Result<(u64, Self), Error> {
+        let encoded = cid.strip_prefix('b').ok_or(Error::Parse { reason: "CID is not base32 (multibase b)" })?;
+        // the version and codec varints take at most 10 bytes each
+        let mut bytes = [0u8; 2 * 10 + 34];
+        let mut len = 0;
+        let (mut bits, mut n_bits) = (0u32, 0);
+        for c in encoded.bytes() {
+            let value = BASE32.iter().position(|&b| b == c).ok_or(Error::Parse { reason: "CID has a non-base32 character" })?;
+            bits = bits << 5 | value as u32;
+            n_bits += 5;
+            if n_bits >= 8 {
+                n_bits -= 8;
+                *bytes.get_mut(len).ok_or(Error::Parse { reason: "CID is too long" })? = (bits >> n_bits) as u8;
+                len += 1;
+            }
+        }
+        if n_bits >= 5 || bits & ((1 << n_bits) - 1) != 0 {
+            return Err(Error::Parse { reason: "CID has trailing base32 bits" });
+        }
+
+        let mut rest = &bytes[..len];
+        if read_varint(&mut rest)? != 1 {
+            return Err(Error::Parse { reason: "CID is not version 1" });
+        }
+        let codec = read_varint(&mut rest)?;
+        Ok((codec, Hash256::from_multihash(rest)?))
+    }
+}
+
+/// Writes an unsigned LEB128 varint, returning its length.
+#[cfg(feature = "alloc")]
+fn write_varint(out: &mut [u8], mut value: u64) -> usize {
+    let mut len = 0;
+    loop {
+        let byte = value as u8 & 0x7f;
+        value >>= 7;
+        if value == 0 {
+            out[len] = byte;
+            return len + 1;
+        }
+        out[len] = byte | 0x80;
+        len += 1;
+    }
+}
+
+/// Reads an unsigned LEB128 varint off the front of `bytes`, rejecting the non-minimal encodings multiformats forbids.
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, Error> {
+    let mut value = 0u64;
+    for (i, &byte) in bytes.iter().enumerate().take(10) {
+        value |= ((byte & 0x7f) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            if byte == 0 && i > 0 {
+                return Err(Error::Parse { reason: "varint is not minimally encoded" });
+            }
+            *bytes = &bytes[i + 1..];
+            return Ok(value);
+        }
+    }
+    Err(Error::Parse { reason: "varint is truncated or too long" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multihash() {
+        let hash = Hash256::new(crate::sha256(b"hello world"));
+        let multihash = hash.to_multihash();
+        assert_eq!(multihash[..2], [0x12, 0x20]);
+        assert_eq!(Hash256::from_multihash(&multihash).unwrap(), hash);
+        assert!(Hash256::from_multihash(&multihash[..33]).is_err());
+        let mut longer = [0u8; 35];
+        longer[..34].copy_from_slice(&multihash);
+        assert!(Hash256::from_multihash(&longer).is_err());
+        // sha2-512's code
+        let mut other = multihash;
+        other[0] = 0x13;
+        assert!(Hash256::from_multihash(&other).is_err());
+        // 0x12 encoded in two bytes
+        longer[..3].copy_from_slice(&[0x92, 0x00, 0x20]);
+        longer[3..].copy_from_slice(hash.as_bytes());
+        assert!(Hash256::from_multihash(&longer).is_err());
+    }
+
+    #[test]
+    fn cid() {
+        let empty = Hash256::new(crate::sha256(b""));
+        let hello = Hash256::new(crate::sha256(b"hello world"));
+        let cases = [
+            (empty, RAW, "bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"),
+            (empty, DAG_PB, "bafybeihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyku"),
+            (hello, RAW, "bafkreifzjut3te2nhyekklss27nh3k72ysco7y32koao5eei66wof36n5e"),
+        ];
+        for (hash, codec, cid) in cases {
+            assert_eq!(Hash256::from_cid(cid).unwrap(), (codec, hash));
+            #[cfg(feature = "alloc")]
+            assert_eq!(hash.to_cid(codec), cid);
+        }
+        #[cfg(feature = "alloc")]
+        {
+            // dag-json's code takes two bytes as a varint
+            let cid = hello.to_cid(0x0129);
+            assert_eq!(Hash256::from_cid(&cid).unwrap(), (0x0129, hello));
+        }
+
+        // CIDv0, base58btc
+        assert!(Hash256::from_cid("QmbWqxBEKC3P8tqsKc98xmWNzrzDtRLMiMPL8wBuTGsMnR").is_err());
+        assert!(Hash256::from_cid("zb2rhe5P4gXftAwvA4eXQ5HJwsER2owDyS9sKaQRRVQPn93bA").is_err());
+        assert!(Hash256::from_cid("bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvyk").is_err());
+        assert!(Hash256::from_cid("bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvykua").is_err());
+        assert!(Hash256::from_cid("bafkreihdwdcefgh4dqkjv67uzcmw7ojee6xedzdetojuzjevtenxquvykU").is_err());
+    }
+}
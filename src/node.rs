@@ -0,0 +1,73 @@
+//! Node.js bindings via napi-rs, taking and returning `Buffer`s.
+//!
+//! Build the addon with `cargo rustc --release --features node --crate-type cdylib` and copy the library to `sha_256.node`, then:
+//! ```js
+//! const { sha256Hex, Sha256 } = require("./sha_256.node");
+//!
+//! sha256Hex(Buffer.from("hello"));
+//! const hasher = new Sha256();
+//! stream.on("data", (chunk) => hasher.update(chunk));
+//! stream.on("end", () => console.log(hasher.hexDigest()));
+//! ```
+
+use napi::bindgen_prelude::Buffer;
+use napi_derive::napi;
+// the code generated by #[napi] expects the std prelude
+#[allow(unused_imports)]
+use std::prelude::v1::*;
+#[allow(unused_imports)]
+use std::{format, vec};
+
+use crate::{Hash256, Sha256};
+
+/// Hashes the buffer in one go, returning the 32 byte digest.
+#[napi]
+pub fn sha256(data: Buffer) -> Buffer {
+    Sha256::new().digest(&data).to_vec().into()
+}
+
+/// Hashes the buffer in one go, returning the digest as lowercase hex.
+#[napi(js_name = "sha256Hex")]
+pub fn sha256_hex(data: Buffer) -> String {
+    Hash256::new(Sha256::new().digest(&data)).to_string()
+}
+
+/// A streaming hasher for data arriving in chunks, e.g. from a `Readable` stream.
+#[napi(js_name = "Sha256")]
+pub struct NodeSha256 {
+    inner: Sha256,
+}
+
+#[napi]
+impl NodeSha256 {
+    /// Creates a new streaming hasher.
+    #[napi(constructor)]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self { inner: Sha256::new() }
+    }
+
+    /// Absorbs the next chunk of data.
+    #[napi]
+    pub fn update(&mut self, data: Buffer) {
+        self.inner.update(&data);
+    }
+
+    /// Returns the 32 byte digest of the data absorbed so far and resets the hasher.
+    #[napi]
+    pub fn digest(&mut self) -> Buffer {
+        self.inner.finalize_reset().to_vec().into()
+    }
+
+    /// Returns the digest of the data absorbed so far as lowercase hex and resets the hasher.
+    #[napi(js_name = "hexDigest")]
+    pub fn hex_digest(&mut self) -> String {
+        Hash256::new(self.inner.finalize_reset()).to_string()
+    }
+
+    /// Discards the data absorbed so far.
+    #[napi]
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
@@ -0,0 +1,74 @@
+//! Streaming hashing with a callback after every compressed block.
+
+use crate::Sha256;
+
+/// A streaming hasher which calls an observer after each 64 byte block is compressed.
+///
+/// The observer receives the index of the block (counting from 0, including the padding blocks added by `finalize`) and the 8 word chaining state after that block.
+/// Useful for debugging, teaching, co-simulating hardware or gathering per-block metadata in the same pass as hashing.
+pub struct ObservedSha256<F: FnMut(u64, &[u32; 8])> {
+    sha256: Sha256,
+    observer: F,
+    blocks: u64,
+}
+
+impl<F: FnMut(u64, &[u32; 8])> ObservedSha256<F> {
+    /// Creates a new hasher with an observer installed.
+    ///
+    /// # Arguments
+    /// * `observer` - Called with `(block_index, state)` after each block.
+    pub fn new(observer: F) -> Self {
+        Self { sha256: Sha256::new(), observer, blocks: 0 }
+    }
+
+    /// Absorbs more of the message, observing any blocks it completes.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        let Self { sha256, observer, blocks } = self;
+        sha256.update_with(data, |sha256| {
+            observer(*blocks, &sha256.state());
+            *blocks += 1;
+        });
+    }
+
+    /// Pads the message, observing the final block(s), and returns its SHA-256 digest.
+    ///
+    /// # Returns
+    /// A 32-byte array representing the SHA-256 hash of the message.
+    pub fn finalize(self) -> [u8; 32] {
+        let Self { mut sha256, mut observer, mut blocks } = self;
+        sha256.finalize_with(|sha256| {
+            observer(blocks, &sha256.state());
+            blocks += 1;
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn observes_every_block() {
+        for len in [0usize, 55, 56, 64, 200] {
+            let msg: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let mut seen = Vec::new();
+            let mut sha256 = ObservedSha256::new(|index, state: &[u32; 8]| seen.push((index, *state)));
+            sha256.update(&msg[..len / 3]);
+            sha256.update(&msg[len / 3..]);
+            let hash = sha256.finalize();
+
+            assert_eq!(hash, Sha256::new().digest(&msg));
+            // the message plus at least 9 bytes of padding, rounded up to whole blocks
+            assert_eq!(seen.len(), (len + 9).div_ceil(64), "len {}", len);
+            assert!(seen.iter().enumerate().all(|(i, (index, _))| *index == i as u64));
+            let last: Vec<u8> = seen.last().unwrap().1.iter().flat_map(|word| word.to_be_bytes()).collect();
+            assert_eq!(last, hash);
+        }
+    }
+}
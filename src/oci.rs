@@ -0,0 +1,165 @@
+//! Verification of OCI (container image) blobs, such as layers, against the `sha256:<hex>` digest in their descriptor.
+//!
+//! Registries serve layers by digest, so a layer can be checked as it is downloaded: wrap the response body in a `LayerVerifier`,
+//! unpack or store it by reading through the verifier, and the digest is checked when the body ends, before the data is trusted.
+
+use std::io::{self, Read};
+use std::string::String;
+
+use crate::{hex, Error, Hash256, Sha256};
+
+/// Parses an OCI digest of the SHA-256 algorithm, `sha256:` followed by 64 lowercase hex characters.
+///
+/// # Arguments
+/// * `digest` - The digest from a descriptor or `Docker-Content-Digest` header.
+///
+/// # Returns
+/// The hash, or `Error::Parse` for another algorithm or a malformed digest. The OCI spec only allows lowercase hex, so uppercase is rejected.
+pub fn parse_oci_digest(digest: &str) -> Result<Hash256, Error> {
+    let (algorithm, encoded) = digest.split_once(':').ok_or(Error::Parse { reason: "OCI digest has no algorithm" })?;
+    if algorithm != "sha256" {
+        return Err(Error::Parse { reason: "OCI digest algorithm is not sha256" });
+    }
+    if encoded.bytes().any(|b| b.is_ascii_uppercase()) {
+        return Err(Error::Parse { reason: "OCI digest must be lowercase hex" });
+    }
+    hex::decode(encoded.as_bytes()).map(Hash256::new).ok_or(Error::Parse { reason: "OCI digest is not 64 hex characters" })
+}
+
+/// Formats a hash as an OCI digest, `sha256:<hex>`.
+///
+/// # Arguments
+/// * `hash` - The hash of the blob.
+pub fn oci_digest(hash: &Hash256) -> String {
+    let mut digest = String::with_capacity(7 + 64);
+    digest.push_str("sha256:");
+    digest.extend(hex::encode(hash.as_bytes()).iter().map(|&c| char::from(c)));
+    digest
+}
+
+/// A reader which passes a blob through while hashing it, and fails the read reaching the end of the blob if its digest is wrong.
+///
+/// ```no_run
+/// # fn download() -> std::io::Result<std::fs::File> { std::fs::File::open("layer.tar") }
+/// let body = download()?;
+/// let mut layer = sha_256::LayerVerifier::new(body, "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")?;
+/// std::io::copy(&mut layer, &mut std::io::sink())?;
+/// let size = layer.finish()?;
+/// # Ok::<(), sha_256::Error>(())
+/// ```
+///
+/// The mismatch is reported as an `io::Error` of kind `InvalidData` wrapping `Error::Mismatch`, so it stops whatever is reading the layer
+/// (e.g. a tar extractor) and can be recovered with `get_ref().downcast_ref::<sha_256::Error>()`.
+pub struct LayerVerifier<R> {
+    inner: R,
+    sha256: Sha256,
+    expected: Hash256,
+    bytes_read: u64,
+}
+
+impl<R: Read> LayerVerifier<R> {
+    /// Wraps a blob's stream.
+    ///
+    /// # Arguments
+    /// * `inner` - The blob being downloaded or read.
+    /// * `digest` - The digest the blob should have, `sha256:<hex>`.
+    ///
+    /// # Returns
+    /// The verifier, or `Error::Parse` if `digest` isn't a SHA-256 OCI digest.
+    pub fn new(inner: R, digest: &str) -> Result<Self, Error> {
+        Ok(Self::with_hash(inner, parse_oci_digest(digest)?))
+    }
+
+    /// Wraps a blob's stream, with the expected digest already parsed.
+    ///
+    /// # Arguments
+    /// * `inner` - The blob being downloaded or read.
+    /// * `expected` - The hash the blob should have.
+    pub fn with_hash(inner: R, expected: Hash256) -> Self {
+        Self { inner, sha256: Sha256::new(), expected, bytes_read: 0 }
+    }
+
+    /// Returns the number of bytes read so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Reads whatever is left of the blob and checks its digest.
+    ///
+    /// # Returns
+    /// The size of the blob in bytes, otherwise `Error::Io` or `Error::Mismatch` with the actual hash and the number of bytes read.
+    pub fn finish(mut self) -> Result<u64, Error> {
+        io::copy(&mut self.inner, &mut self.sha256).map(|n| self.bytes_read += n)?;
+        self.check()
+    }
+
+    fn check(&self) -> Result<u64, Error> {
+        // the hasher is cloned as a read returning 0 may be repeated
+        let actual = Hash256::new(self.sha256.clone().finalize());
+        if actual == self.expected {
+            Ok(self.bytes_read)
+        } else {
+            Err(Error::Mismatch { expected: self.expected, actual, bytes_read: self.bytes_read })
+        }
+    }
+}
+
+impl<R: Read> Read for LayerVerifier<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n == 0 && !buf.is_empty() {
+            self.check().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+        self.sha256.update(&buf[..n]);
+        self.bytes_read += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    const EMPTY: &str = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn parses_and_formats_digests() {
+        let hash = parse_oci_digest(EMPTY).unwrap();
+        assert_eq!(hash, Hash256::new(Sha256::new().digest(b"")));
+        assert_eq!(oci_digest(&hash), EMPTY);
+        assert!(parse_oci_digest(&EMPTY.to_uppercase()[..]).is_err());
+        assert!(parse_oci_digest(&EMPTY.replace("sha256", "sha512")).is_err());
+        assert!(parse_oci_digest(&EMPTY[7..]).is_err());
+        assert!(parse_oci_digest(&EMPTY[..70]).is_err());
+    }
+
+    #[test]
+    fn verifies_layer() {
+        let layer: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        let digest = oci_digest(&Hash256::new(Sha256::new().digest(&layer)));
+
+        let mut verifier = LayerVerifier::new(&layer[..], &digest).unwrap();
+        let mut out = Vec::new();
+        verifier.read_to_end(&mut out).unwrap();
+        assert_eq!(out, layer);
+        assert_eq!(verifier.finish().unwrap(), layer.len() as u64);
+
+        // partly read, the rest read by finish
+        let mut verifier = LayerVerifier::new(&layer[..], &digest).unwrap();
+        verifier.read_exact(&mut [0; 10]).unwrap();
+        assert_eq!(verifier.finish().unwrap(), layer.len() as u64);
+
+        // a corrupted layer fails the read reaching the end, and finish
+        let mut corrupt = layer.clone();
+        corrupt[5] ^= 1;
+        let mut verifier = LayerVerifier::new(&corrupt[..], &digest).unwrap();
+        let e = verifier.read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+        match e.get_ref().and_then(|e| e.downcast_ref::<Error>()) {
+            Some(Error::Mismatch { bytes_read, .. }) => assert_eq!(*bytes_read, layer.len() as u64),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(matches!(verifier.finish(), Err(Error::Mismatch { .. })));
+    }
+}
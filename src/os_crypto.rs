@@ -0,0 +1,151 @@
+//! A `Backend` using the operating system's crypto library: CNG (bcrypt) on Windows, CommonCrypto on macOS/iOS.
+//!
+//! For deployments which must use OS provided (e.g. FIPS 140 validated) primitives. Only available on those platforms.
+
+// the FFI declarations keep the names from the platform headers
+#![allow(non_camel_case_types, clippy::upper_case_acronyms)]
+
+use core::ffi::c_void;
+
+use crate::{Backend, Error};
+
+/// SHA-256 computed by the operating system's crypto library.
+pub struct OsSha256 {
+    inner: imp::Context,
+}
+
+impl OsSha256 {
+    /// Creates a hasher, reporting rather than panicking if the OS refuses to provide SHA-256 (e.g. CNG before Windows 10).
+    ///
+    /// # Returns
+    /// The hasher, or `Error::BackendUnavailable`.
+    pub fn try_new() -> Result<Self, Error> {
+        imp::Context::new().map(|inner| Self { inner }).ok_or(Error::BackendUnavailable)
+    }
+}
+
+impl Backend for OsSha256 {
+    fn new() -> Self {
+        Self::try_new().expect("OS SHA-256 is unavailable")
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        // both APIs take 32 bit lengths
+        for chunk in data.chunks(u32::MAX as usize) {
+            self.inner.update(chunk.as_ptr() as *const c_void, chunk.len() as u32);
+        }
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.inner.finalize()
+    }
+}
+
+// the OS contexts are plain heap/stack state without thread affinity
+unsafe impl Send for OsSha256 {}
+
+impl Default for OsSha256 {
+    fn default() -> Self {
+        Backend::new()
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use core::ffi::c_void;
+    use core::ptr;
+
+    type BCRYPT_HANDLE = *mut c_void;
+    type NTSTATUS = i32;
+
+    // pseudo-handle for the SHA-256 provider, saves opening (and closing) one, available from Windows 10
+    const BCRYPT_SHA256_ALG_HANDLE: BCRYPT_HANDLE = 0x41 as BCRYPT_HANDLE;
+
+    #[link(name = "bcrypt")]
+    extern "system" {
+        fn BCryptCreateHash(
+            alg: BCRYPT_HANDLE,
+            hash: *mut BCRYPT_HANDLE,
+            object: *mut u8,
+            object_len: u32,
+            secret: *mut u8,
+            secret_len: u32,
+            flags: u32,
+        ) -> NTSTATUS;
+        fn BCryptHashData(hash: BCRYPT_HANDLE, input: *const u8, input_len: u32, flags: u32) -> NTSTATUS;
+        fn BCryptFinishHash(hash: BCRYPT_HANDLE, output: *mut u8, output_len: u32, flags: u32) -> NTSTATUS;
+        fn BCryptDestroyHash(hash: BCRYPT_HANDLE) -> NTSTATUS;
+    }
+
+    pub(super) struct Context(BCRYPT_HANDLE);
+
+    impl Context {
+        pub(super) fn new() -> Option<Self> {
+            let mut hash = ptr::null_mut();
+            // a null object buffer lets CNG allocate the hash state itself
+            let status = unsafe { BCryptCreateHash(BCRYPT_SHA256_ALG_HANDLE, &mut hash, ptr::null_mut(), 0, ptr::null_mut(), 0, 0) };
+            (status >= 0).then_some(Self(hash))
+        }
+
+        pub(super) fn update(&mut self, data: *const c_void, len: u32) {
+            let status = unsafe { BCryptHashData(self.0, data as *const u8, len, 0) };
+            assert!(status >= 0, "BCryptHashData failed: {:#x}", status);
+        }
+
+        pub(super) fn finalize(self) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            let status = unsafe { BCryptFinishHash(self.0, out.as_mut_ptr(), out.len() as u32, 0) };
+            assert!(status >= 0, "BCryptFinishHash failed: {:#x}", status);
+            out
+        }
+    }
+
+    impl Drop for Context {
+        fn drop(&mut self) {
+            unsafe { BCryptDestroyHash(self.0) };
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+mod imp {
+    use core::ffi::c_void;
+    use core::mem::MaybeUninit;
+
+    #[repr(C)]
+    struct CC_SHA256_CTX {
+        count: [u32; 2],
+        hash: [u32; 8],
+        wbuf: [u32; 16],
+    }
+
+    // part of libSystem, so no extra linking is required
+    extern "C" {
+        fn CC_SHA256_Init(c: *mut CC_SHA256_CTX) -> i32;
+        fn CC_SHA256_Update(c: *mut CC_SHA256_CTX, data: *const c_void, len: u32) -> i32;
+        fn CC_SHA256_Final(md: *mut u8, c: *mut CC_SHA256_CTX) -> i32;
+    }
+
+    pub(super) struct Context(CC_SHA256_CTX);
+
+    impl Context {
+        pub(super) fn new() -> Option<Self> {
+            let mut ctx = MaybeUninit::<CC_SHA256_CTX>::uninit();
+            unsafe {
+                // always returns 1
+                CC_SHA256_Init(ctx.as_mut_ptr());
+                Some(Self(ctx.assume_init()))
+            }
+        }
+
+        pub(super) fn update(&mut self, data: *const c_void, len: u32) {
+            unsafe { CC_SHA256_Update(&mut self.0, data, len) };
+        }
+
+        pub(super) fn finalize(mut self) -> [u8; 32] {
+            let mut out = [0u8; 32];
+            unsafe { CC_SHA256_Final(out.as_mut_ptr(), &mut self.0) };
+            out
+        }
+    }
+}
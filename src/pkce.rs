@@ -0,0 +1,81 @@
+//! OAuth 2.0 PKCE (RFC 7636) with the `S256` method: the code challenge is the unpadded base64url encoding of the SHA-256 of the code verifier.
+
+use alloc::string::String;
+
+use crate::Sha256;
+
+const BASE64URL: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Computes the `S256` code challenge a client sends with its authorization request.
+///
+/// ```
+/// // RFC 7636 appendix B
+/// let challenge = sha_256::pkce_s256("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk");
+/// assert_eq!(challenge, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+/// ```
+///
+/// # Arguments
+/// * `code_verifier` - The client's secret verifier, 43 to 128 characters from `A-Z a-z 0-9 - . _ ~`. Not checked here, see `pkce_verify_s256`.
+///
+/// # Returns
+/// `BASE64URL(SHA256(code_verifier))` without padding, always 43 characters.
+pub fn pkce_s256(code_verifier: &str) -> String {
+    challenge(code_verifier).iter().map(|&c| char::from(c)).collect()
+}
+
+/// Checks a code verifier against the challenge received with the authorization request, as the authorization server does before issuing a token.
+///
+/// The comparison takes the same time wherever the challenges differ.
+///
+/// # Arguments
+/// * `code_verifier` - The verifier sent with the token request.
+/// * `code_challenge` - The `S256` challenge sent with the authorization request.
+///
+/// # Returns
+/// `true` if the verifier is well formed (RFC 7636 section 4.1) and hashes to the challenge.
+pub fn pkce_verify_s256(code_verifier: &str, code_challenge: &str) -> bool {
+    let well_formed = (43..=128).contains(&code_verifier.len())
+        && code_verifier.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'));
+    let code_challenge = code_challenge.as_bytes();
+    if !well_formed || code_challenge.len() != 43 {
+        return false;
+    }
+    let mut diff = 0u8;
+    for (a, b) in challenge(code_verifier).iter().zip(code_challenge) {
+        diff |= a ^ b;
+    }
+    core::hint::black_box(diff) == 0
+}
+
+/// Encodes the digest of the verifier as 43 base64url characters, 32 bytes being 42 full sextets and 2 bits.
+fn challenge(code_verifier: &str) -> [u8; 43] {
+    let digest = Sha256::new().digest(code_verifier.as_bytes());
+    let mut out = [0u8; 43];
+    for (i, chunk) in digest.chunks(3).enumerate() {
+        let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+        for (j, c) in out[i * 4..].iter_mut().take(chunk.len() + 1).enumerate() {
+            *c = BASE64URL[(n >> (18 - 6 * j)) as usize & 63];
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rfc_7636_example() {
+        let verifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        let challenge = "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM";
+        assert_eq!(pkce_s256(verifier), challenge);
+        assert!(pkce_verify_s256(verifier, challenge));
+
+        assert!(!pkce_verify_s256(verifier, "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cN"));
+        assert!(!pkce_verify_s256(verifier, &alloc::format!("{}=", challenge)));
+        // too short, and a character outside the unreserved set
+        assert!(!pkce_verify_s256("abc", &pkce_s256("abc")));
+        let plus = "dBjftJeZ4CVP+mB92K27uhbUJU1p1r_wW1gFWFOEjXk";
+        assert!(!pkce_verify_s256(plus, &pkce_s256(plus)));
+    }
+}
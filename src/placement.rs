@@ -0,0 +1,188 @@
+//! Placing content-addressed keys on shards or nodes from their digests.
+//!
+//! SHA-256 output is uniformly distributed, so its first 8 bytes make a good key for the placement algorithms, and unlike
+//! `std::hash::Hasher` they are the same on every machine, build and release, so every service agrees where a key lives.
+
+use crate::{Hash256, Sha256};
+
+/// Picks the bucket a digest belongs in with jump consistent hashing (Lamping and Veach, 2014).
+///
+/// Buckets are numbered `0..n_buckets`. Growing from `n` to `n + 1` buckets only moves about `1 / (n + 1)` of the keys,
+/// all of them into the new bucket, so shards can be added at the end without reshuffling the rest. Removing a bucket other than the last isn't supported.
+///
+/// ```
+/// let digest = sha_256::Hash256::new(sha_256::Sha256::new().digest(b"some/object"));
+/// let shard = sha_256::shard_for(&digest, 16);
+/// assert!(shard < 16);
+/// ```
+///
+/// # Arguments
+/// * `digest` - The digest of the key.
+/// * `n_buckets` - The number of buckets, at least 1.
+///
+/// # Returns
+/// The bucket, less than `n_buckets`.
+///
+/// # Panics
+/// If `n_buckets` is 0.
+pub fn shard_for(digest: &Hash256, n_buckets: u32) -> u32 {
+    assert!(n_buckets > 0, "shard_for needs at least 1 bucket");
+    jump(key(digest), n_buckets)
+}
+
+/// Picks the node a digest belongs on with rendezvous, or highest random weight, hashing (Thaler and Ravishankar, 1998).
+///
+/// Every node is scored for the key with `rendezvous_score` and the highest wins. Removing any node only moves the keys which were on it,
+/// spread evenly over the others, and adding one only takes keys which now score highest on it. Costs one hash per node, so suits
+/// up to a few hundred nodes. Nodes are identified by their bytes, the order of `nodes` doesn't matter.
+///
+/// ```
+/// let digest = sha_256::Hash256::new(sha_256::Sha256::new().digest(b"some/object"));
+/// let node = sha_256::rendezvous(&digest, &["cache-a", "cache-b", "cache-c"]);
+/// assert!(node.is_some());
+/// ```
+///
+/// # Arguments
+/// * `digest` - The digest of the key.
+/// * `nodes` - The identifiers of the nodes, e.g. host names.
+///
+/// # Returns
+/// The chosen node, or `None` if `nodes` is empty.
+pub fn rendezvous<'a, N: AsRef<[u8]>>(digest: &Hash256, nodes: &'a [N]) -> Option<&'a N> {
+    // ties, as good as impossible with 64-bit scores, go to the greater id so the order of nodes doesn't matter
+    nodes
+        .iter()
+        .map(|node| (rendezvous_score(node.as_ref(), digest), node))
+        .max_by(|(a, a_node), (b, b_node)| a.cmp(b).then_with(|| a_node.as_ref().cmp(b_node.as_ref())))
+        .map(|(_, node)| node)
+}
+
+/// Picks a node like `rendezvous`, with each node getting a share of the keys in proportion to its weight.
+///
+/// Uses the logarithmic method (Schindelhauer and Schomaker, 2005), scoring each node `-weight / ln(score / 2^64)`, which keeps
+/// rendezvous hashing's property that changing one node's weight only moves keys to or from that node. Needs `std` for `f64::ln`.
+///
+/// # Arguments
+/// * `digest` - The digest of the key.
+/// * `nodes` - The identifiers of the nodes and their weights, e.g. their capacities. Nodes with a weight which isn't positive are never picked.
+///
+/// # Returns
+/// The chosen node, or `None` if no node has a positive weight.
+#[cfg(feature = "std")]
+pub fn rendezvous_weighted<'a, N: AsRef<[u8]>>(digest: &Hash256, nodes: &'a [(N, f64)]) -> Option<&'a N> {
+    nodes
+        .iter()
+        .filter(|(_, weight)| *weight > 0.0)
+        .map(|(node, weight)| {
+            // uniform in (0, 1), never exactly 0 or 1 so the log is finite and negative
+            let uniform = (rendezvous_score(node.as_ref(), digest) >> 11) as f64 / (1u64 << 53) as f64 + 0.5 / (1u64 << 53) as f64;
+            (-weight / uniform.ln(), node)
+        })
+        .max_by(|(a, a_node), (b, b_node)| a.total_cmp(b).then_with(|| a_node.as_ref().cmp(b_node.as_ref())))
+        .map(|(_, node)| node)
+}
+
+/// The score of a node for a key in rendezvous hashing, the first 8 bytes of `SHA-256(len(node) || node || digest)` as a big-endian `u64`.
+///
+/// The length prefix is the node's `Hashable` encoding (a big-endian `u64`), so the node and digest can't run into each other.
+///
+/// # Arguments
+/// * `node` - The identifier of the node.
+/// * `digest` - The digest of the key.
+pub fn rendezvous_score(node: &[u8], digest: &Hash256) -> u64 {
+    let mut sha256 = Sha256::new();
+    sha256.absorb(node);
+    sha256.update(digest.as_bytes());
+    key(&Hash256::new(sha256.finalize()))
+}
+
+/// The first 8 bytes of a digest as a big-endian `u64`.
+fn key(digest: &Hash256) -> u64 {
+    let bytes = digest.as_bytes();
+    u64::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]])
+}
+
+/// The jump hash of the paper, line for line.
+fn jump(mut key: u64, n_buckets: u32) -> u32 {
+    let (mut b, mut j) = (-1i64, 0i64);
+    while j < n_buckets as i64 {
+        b = j;
+        key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+        j = ((b + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    b as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+
+    #[test]
+    fn matches_the_paper() {
+        // values from other implementations of the paper's code
+        assert_eq!(jump(1, 1), 0);
+        assert_eq!(jump(42, 57), 43);
+        assert_eq!(jump(0xdead10cc, 666), 361);
+        assert_eq!(jump(256, 1024), 520);
+    }
+
+    #[test]
+    fn spreads_and_moves_few_keys() {
+        let mut counts = [0u32; 10];
+        let mut moved = 0;
+        for i in 0..10_000u32 {
+            let digest = Hash256::new(Sha256::new().digest(&i.to_le_bytes()));
+            let before = shard_for(&digest, 10);
+            let after = shard_for(&digest, 11);
+            counts[before as usize] += 1;
+            if before != after {
+                // keys only ever move to the new bucket
+                assert_eq!(after, 10);
+                moved += 1;
+            }
+        }
+        assert!(counts.iter().all(|&count| (900..1100).contains(&count)), "{:?}", counts);
+        // about 1/11 of the keys
+        assert!((800..1000).contains(&moved), "{}", moved);
+    }
+
+    #[test]
+    fn rendezvous_moves_only_the_removed_nodes_keys() {
+        let nodes = ["a", "b", "c", "d", "e"];
+        let without_c = ["e", "d", "b", "a"];
+        let mut counts = [0u32; 5];
+        for i in 0..10_000u32 {
+            let digest = Hash256::new(Sha256::new().digest(&i.to_le_bytes()));
+            let node = rendezvous(&digest, &nodes).unwrap();
+            counts[nodes.iter().position(|n| n == node).unwrap()] += 1;
+            let after = rendezvous(&digest, &without_c).unwrap();
+            if *node != "c" {
+                assert_eq!(after, node);
+            }
+        }
+        assert!(counts.iter().all(|&count| (1800..2200).contains(&count)), "{:?}", counts);
+        assert_eq!(rendezvous::<&str>(&Hash256::new([0; 32]), &[]), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn rendezvous_weighted_follows_the_weights() {
+        let nodes = [("small", 1.0), ("big", 3.0), ("off", 0.0)];
+        let mut big = 0;
+        for i in 0..10_000u32 {
+            let digest = Hash256::new(Sha256::new().digest(&i.to_le_bytes()));
+            let node = *rendezvous_weighted(&digest, &nodes).unwrap();
+            assert_ne!(node, "off");
+            if node == "big" {
+                big += 1;
+                // giving small more weight only ever moves keys to it
+                assert!(matches!(rendezvous_weighted(&digest, &[("small", 2.0), ("big", 3.0)]), Some(&"big") | Some(&"small")));
+            } else {
+                assert_eq!(rendezvous_weighted(&digest, &[("small", 2.0), ("big", 3.0)]), Some(&"small"));
+            }
+        }
+        assert!((7300..7700).contains(&big), "{}", big);
+        assert_eq!(rendezvous_weighted(&Hash256::new([0; 32]), &[("off", 0.0), ("nan", f64::NAN)]), None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Proof-of-work checks, treating a digest as a 256-bit big-endian integer which must not exceed a target.
+//!
+//! `Hash256` orders by its bytes, which is the same as ordering the big-endian integers, so no bigint arithmetic is needed.
+//! Note that Bitcoin displays and compares block hashes byte reversed (as little-endian integers), reverse the digest first for those.
+
+use crate::{Error, Hash256};
+
+/// A proof-of-work target, the largest digest, as a 256-bit big-endian integer, which counts as a solution.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Target([u8; 32]);
+
+impl Target {
+    /// The largest target, met by every digest.
+    pub const MAX: Target = Target([0xff; 32]);
+
+    /// Wraps a target given as 32 big-endian bytes.
+    ///
+    /// # Arguments
+    /// * `bytes` - The target, most significant byte first.
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the target as 32 big-endian bytes.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// The target met by digests with at least `bits` leading zero bits, i.e. `2^(256 - bits) - 1`, for simple rate limiting puzzles.
+    ///
+    /// # Arguments
+    /// * `bits` - The number of leading zero bits, at most 256.
+    pub fn from_leading_zero_bits(bits: u32) -> Self {
+        let mut target = [0xff; 32];
+        let bits = bits.min(256) as usize;
+        target[..bits / 8].fill(0);
+        if bits < 256 {
+            target[bits / 8] = 0xff >> (bits % 8);
+        }
+        Self(target)
+    }
+
+    /// Decodes a target in the compact form used in Bitcoin block headers (`nBits`).
+    ///
+    /// The top byte is the length of the target in bytes and the low 3 bytes its most significant bytes, e.g. `0x1d00ffff` is `0xffff << 208`.
+    ///
+    /// # Arguments
+    /// * `bits` - The compact target.
+    ///
+    /// # Returns
+    /// The target, or `Error::Parse` if the sign bit (`0x00800000`) is set on a non-zero target or the target doesn't fit in 256 bits.
+    pub fn from_compact(bits: u32) -> Result<Self, Error> {
+        let size = (bits >> 24) as i32;
+        let mantissa = bits & 0x007f_ffff;
+        if mantissa != 0 && bits & 0x0080_0000 != 0 {
+            return Err(Error::Parse { reason: "compact target is negative" });
+        }
+        let mut target = [0u8; 32];
+        for (k, byte) in mantissa.to_be_bytes()[1..].iter().enumerate() {
+            // the mantissa's most significant byte is byte `size` counting from the least significant end
+            let index = 32 - size + k as i32;
+            if index < 0 {
+                if *byte != 0 {
+                    return Err(Error::Parse { reason: "compact target is more than 256 bits" });
+                }
+            } else if index < 32 {
+                target[index as usize] = *byte;
+            }
+        }
+        Ok(Self(target))
+    }
+
+    /// Encodes the target in the compact form used in Bitcoin block headers, dropping all but its 3 most significant bytes (or 2, see below).
+    ///
+    /// # Returns
+    /// The compact target. The mantissa is shifted down a byte when its top bit is set, so the result is never negative.
+    pub fn to_compact(&self) -> u32 {
+        let start = self.0.iter().position(|&b| b != 0).unwrap_or(32);
+        let mut size = (32 - start) as u32;
+        let byte = |i: usize| self.0.get(i).copied().unwrap_or(0) as u32;
+        let mut mantissa = byte(start) << 16 | byte(start + 1) << 8 | byte(start + 2);
+        if mantissa & 0x0080_0000 != 0 {
+            mantissa >>= 8;
+            size += 1;
+        }
+        size << 24 | mantissa
+    }
+
+    /// Checks whether a digest solves the puzzle.
+    ///
+    /// # Arguments
+    /// * `digest` - The digest, as a big-endian integer.
+    ///
+    /// # Returns
+    /// `true` if `digest <= target`.
+    pub fn is_met_by(&self, digest: &Hash256) -> bool {
+        digest.as_bytes() <= &self.0
+    }
+}
+
+/// Counts the leading zero bits of a digest, the usual measure of work for hashcash style puzzles.
+///
+/// # Arguments
+/// * `digest` - The digest.
+///
+/// # Returns
+/// 0 to 256.
+pub fn leading_zero_bits(digest: &Hash256) -> u32 {
+    let bytes = digest.as_bytes();
+    match bytes.iter().position(|&b| b != 0) {
+        Some(i) => i as u32 * 8 + bytes[i].leading_zeros(),
+        None => 256,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(hex: &str) -> Target {
+        let mut bytes = [0u8; 32];
+        let start = 32 - hex.len() / 2;
+        for (i, byte) in bytes[start..].iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Target::new(bytes)
+    }
+
+    #[test]
+    fn compact_targets() {
+        // the cases of Bitcoin Core's arith_uint256 SetCompact/GetCompact tests
+        let genesis = Target::from_compact(0x1d00ffff).unwrap();
+        assert_eq!(genesis, target("00000000ffff0000000000000000000000000000000000000000000000000000"));
+        assert_eq!(genesis.to_compact(), 0x1d00ffff);
+        for (bits, value, compact) in [
+            (0x00123456, "", 0),
+            (0x01003456, "", 0),
+            (0x01123456, "12", 0x01120000),
+            (0x02123456, "1234", 0x02123456 & 0xffff_ff00),
+            (0x03123456, "123456", 0x03123456),
+            (0x04123456, "12345600", 0x04123456),
+            (0x05009234, "92340000", 0x05009234),
+            (0x20123456, "1234560000000000000000000000000000000000000000000000000000000000", 0x20123456),
+        ] {
+            let decoded = Target::from_compact(bits).unwrap();
+            assert_eq!(decoded, target(value), "{:08x}", bits);
+            assert_eq!(decoded.to_compact(), compact, "{:08x}", bits);
+        }
+        assert!(Target::from_compact(0x04923456).is_err());
+        assert!(Target::from_compact(0x01fedcba).is_err());
+        assert!(Target::from_compact(0xff123456).is_err());
+        assert!(Target::from_compact(0x21010000).is_err());
+        // too big only if the bytes which don't fit aren't 0
+        assert!(Target::from_compact(0x21000001).is_ok());
+        // the sign bit is shifted out of the mantissa
+        assert_eq!(target("80").to_compact(), 0x02008000);
+    }
+
+    #[test]
+    fn leading_zeros_and_targets() {
+        let mut bytes = [0xffu8; 32];
+        assert_eq!(leading_zero_bits(&Hash256::new(bytes)), 0);
+        bytes[0] = 0;
+        bytes[1] = 0x1f;
+        assert_eq!(leading_zero_bits(&Hash256::new(bytes)), 11);
+        assert_eq!(leading_zero_bits(&Hash256::new([0; 32])), 256);
+
+        let target = Target::from_leading_zero_bits(11);
+        assert!(target.is_met_by(&Hash256::new(bytes)));
+        bytes[1] = 0x20;
+        assert!(!target.is_met_by(&Hash256::new(bytes)));
+        assert_eq!(Target::from_leading_zero_bits(0), Target::MAX);
+        assert_eq!(Target::from_leading_zero_bits(256), Target::new([0; 32]));
+        for bits in 0..=256 {
+            let target = Target::from_leading_zero_bits(bits);
+            assert_eq!(leading_zero_bits(&Hash256::new(*target.as_bytes())), bits);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Python bindings via PyO3, mirroring `hashlib.sha256`.
+//!
+//! Build the extension module with `maturin build --release` (the feature is enabled by `pyproject.toml`), then:
+//! ```python
+//! import sha_256
+//!
+//! h = sha_256.sha256(b"hel")
+//! h.update(b"lo")
+//! h.hexdigest()
+//! ```
+
+use alloc::string::String;
+use pyo3::buffer::PyBuffer;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+use crate::{hex, Sha256};
+
+/// A hashlib compatible SHA-256 hash object.
+#[pyclass(name = "sha256", module = "sha_256")]
+#[derive(Clone)]
+pub struct PySha256 {
+    inner: Sha256,
+}
+
+#[pymethods]
+impl PySha256 {
+    /// Creates a new hash object, optionally absorbing some initial data.
+    #[new]
+    #[pyo3(signature = (data = None, *, usedforsecurity = true))]
+    fn new(data: Option<PyBuffer<u8>>, usedforsecurity: bool) -> PyResult<Self> {
+        // accepted for hashlib compatibility, sha256 is always fit for security purposes
+        let _ = usedforsecurity;
+        let mut hasher = Self { inner: Sha256::new() };
+        if let Some(data) = data {
+            hasher.update(data)?;
+        }
+        Ok(hasher)
+    }
+
+    /// Absorbs more data. Accepts any object supporting the buffer protocol (bytes, bytearray, memoryview, ...).
+    fn update(&mut self, data: PyBuffer<u8>) -> PyResult<()> {
+        Python::with_gil(|py| {
+            if data.is_c_contiguous() {
+                // safe to view the buffer as bytes as we hold the GIL for the duration of the update
+                let bytes = unsafe { core::slice::from_raw_parts(data.buf_ptr() as *const u8, data.len_bytes()) };
+                self.inner.update(bytes);
+            } else {
+                self.inner.update(&data.to_vec(py)?);
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns the digest of the data absorbed so far. The hash object can continue to be updated afterwards.
+    fn digest<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.clone().finalize())
+    }
+
+    /// Returns the digest of the data absorbed so far as lowercase hex.
+    fn hexdigest(&self) -> String {
+        hex::encode(&self.inner.clone().finalize()).iter().map(|&c| c as char).collect()
+    }
+
+    /// Returns an independent copy of the hash object.
+    fn copy(&self) -> Self {
+        self.clone()
+    }
+
+    #[getter]
+    fn name(&self) -> &'static str {
+        "sha256"
+    }
+
+    #[getter]
+    fn digest_size(&self) -> usize {
+        32
+    }
+
+    #[getter]
+    fn block_size(&self) -> usize {
+        64
+    }
+}
+
+/// The `sha_256` python module.
+#[pymodule]
+fn sha_256(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PySha256>()
+}
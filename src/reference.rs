@@ -0,0 +1,148 @@
+//! A deliberately plain SHA-256, written to be read side by side with FIPS 180-4.
+//!
+//! Each step is commented with the section of the standard it implements. Nothing is unrolled or reordered,
+//! so this is much slower than `Sha256` but easy to audit, which makes it the oracle for differential tests of the optimised code.
+
+use crate::primitives::{big_sigma0, big_sigma1, ch, maj, small_sigma0, small_sigma1};
+use crate::{Backend, H, K};
+
+/// SHA-256 implemented as literally as possible from FIPS 180-4. Use `Sha256` for anything other than cross-checking.
+#[derive(Clone)]
+pub struct ReferenceSha256 {
+    /// H(i), the intermediate hash value.
+    hash: [u32; 8],
+    /// The part of the next 512-bit block received so far.
+    block: [u8; 64],
+    block_len: usize,
+    /// l, the length of the message in bits.
+    l: u64,
+}
+
+impl ReferenceSha256 {
+    /// Creates a hasher set to H(0).
+    pub fn new() -> Self {
+        // 5.3.3: the initial hash value H(0)
+        Self { hash: H, block: [0; 64], block_len: 0, l: 0 }
+    }
+
+    /// 6.2.2: the SHA-256 hash computation for one message block M(i).
+    // indexing by t keeps the subscripts of the standard
+    #[allow(clippy::needless_range_loop)]
+    fn compute_block(&mut self) {
+        // 1. prepare the message schedule {W_t}
+        let mut w = [0u32; 64];
+        for t in 0..16 {
+            // 5.2.1: the block is parsed as sixteen 32-bit words, big-endian
+            w[t] = u32::from_be_bytes([self.block[4 * t], self.block[4 * t + 1], self.block[4 * t + 2], self.block[4 * t + 3]]);
+        }
+        for t in 16..64 {
+            w[t] = small_sigma1(w[t - 2]).wrapping_add(w[t - 7]).wrapping_add(small_sigma0(w[t - 15])).wrapping_add(w[t - 16]);
+        }
+
+        // 2. initialize the eight working variables with the (i-1)st hash value
+        let mut a = self.hash[0];
+        let mut b = self.hash[1];
+        let mut c = self.hash[2];
+        let mut d = self.hash[3];
+        let mut e = self.hash[4];
+        let mut f = self.hash[5];
+        let mut g = self.hash[6];
+        let mut h = self.hash[7];
+
+        // 3. for t = 0 to 63
+        for t in 0..64 {
+            let t1 = h.wrapping_add(big_sigma1(e)).wrapping_add(ch(e, f, g)).wrapping_add(K[t]).wrapping_add(w[t]);
+            let t2 = big_sigma0(a).wrapping_add(maj(a, b, c));
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+
+        // 4. compute the ith intermediate hash value H(i)
+        self.hash[0] = a.wrapping_add(self.hash[0]);
+        self.hash[1] = b.wrapping_add(self.hash[1]);
+        self.hash[2] = c.wrapping_add(self.hash[2]);
+        self.hash[3] = d.wrapping_add(self.hash[3]);
+        self.hash[4] = e.wrapping_add(self.hash[4]);
+        self.hash[5] = f.wrapping_add(self.hash[5]);
+        self.hash[6] = g.wrapping_add(self.hash[6]);
+        self.hash[7] = h.wrapping_add(self.hash[7]);
+    }
+
+    /// Appends one byte to the message, computing each block as it fills.
+    fn push(&mut self, byte: u8) {
+        self.block[self.block_len] = byte;
+        self.block_len += 1;
+        if self.block_len == 64 {
+            self.compute_block();
+            self.block_len = 0;
+        }
+    }
+}
+
+impl Default for ReferenceSha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Backend for ReferenceSha256 {
+    fn new() -> Self {
+        ReferenceSha256::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.push(byte);
+            self.l = self.l.wrapping_add(8);
+        }
+    }
+
+    fn finalize(mut self) -> [u8; 32] {
+        // 5.1.1: append the bit "1" then k zero bits, where l + 1 + k ≡ 448 mod 512
+        let l = self.l;
+        self.push(0x80);
+        while self.block_len != 56 {
+            self.push(0);
+        }
+        // then the 64-bit block equal to l
+        for byte in l.to_be_bytes() {
+            self.push(byte);
+        }
+
+        // 6.2.2: the digest is H(N)
+        let mut digest = [0u8; 32];
+        for (i, word) in self.hash.iter().enumerate() {
+            digest[4 * i..4 * i + 4].copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+
+    #[test]
+    fn agrees_with_sha256() {
+        // the FIPS 180-4 example "abc"
+        assert_eq!(
+            <ReferenceSha256 as Backend>::compute(b"abc").as_bytes(),
+            &[
+                0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff,
+                0x61, 0xf2, 0x00, 0x15, 0xad
+            ]
+        );
+        // every padding case, differentially against the optimised implementation
+        let msg: [u8; 300] = core::array::from_fn(|i| (i * 31 + 7) as u8);
+        for len in 0..msg.len() {
+            assert_eq!(<ReferenceSha256 as Backend>::compute(&msg[..len]), <Sha256 as Backend>::compute(&msg[..len]), "len {}", len);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Hashing long runs of a repeated byte without materializing them.
+
+use crate::{Hash256, Sha256};
+
+/// Hashes `count` copies of `byte`, e.g. a zero filled region, without allocating it.
+///
+/// # Arguments
+/// * `byte` - The repeated byte.
+/// * `count` - How many times it repeats.
+///
+/// # Returns
+/// The hash of the run, the same as hashing `vec![byte; count]`.
+pub fn digest_repeated(byte: u8, count: u64) -> Hash256 {
+    digest_runs(&[(byte, count)])
+}
+
+/// Hashes a run-length encoded message without expanding it.
+///
+/// # Arguments
+/// * `runs` - `(byte, count)` pairs, each standing for `count` copies of `byte`, in message order.
+///
+/// # Returns
+/// The hash of the expanded message.
+pub fn digest_runs(runs: &[(u8, u64)]) -> Hash256 {
+    let mut sha256 = Sha256::new();
+    for &(byte, count) in runs {
+        sha256.update_repeated(byte, count);
+    }
+    Hash256::new(sha256.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn matches_materialized_input() {
+        for count in [0, 1, 63, 64, 65, 1000] {
+            assert_eq!(digest_repeated(0, count), Hash256::new(Sha256::new().digest(&vec![0; count as usize])), "count {}", count);
+        }
+        let runs = [(1u8, 10u64), (0, 300), (0xff, 64), (7, 3)];
+        let expanded: Vec<u8> = runs.iter().flat_map(|&(byte, count)| vec![byte; count as usize]).collect();
+        assert_eq!(digest_runs(&runs), Hash256::new(Sha256::new().digest(&expanded)));
+    }
+}
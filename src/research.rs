@@ -0,0 +1,156 @@
+//! Round-reduced SHA-256 for cryptanalysis experiments and generating test vectors.
+//!
+//! **Not secure.** SHA-256 with fewer than 64 rounds has known practical collisions and preimage attacks, and its output is not SHA-256.
+//! Nothing here should be used to protect data. The module only exists behind the off-by-default `research` feature for that reason.
+//!
+//! Everything is as in FIPS 180-4 except the compression function stops after the requested number of rounds (and only expands the schedule that far),
+//! then adds the working variables into the hash values as usual. The padding and initial hash values are unchanged, so 64 rounds gives the real SHA-256.
+
+use crate::primitives::{big_sigma0, big_sigma1, ch, maj, small_sigma0, small_sigma1};
+use crate::{H, K};
+
+/// Compresses one block with a reduced number of rounds.
+///
+/// # Arguments
+/// * `state` - The 8 hash values, updated in place.
+/// * `block` - The 64 byte message block.
+/// * `rounds` - How many of the 64 rounds to run.
+///
+/// # Panics
+/// If `rounds` is more than 64.
+pub fn compress_reduced(state: &mut [u32; 8], block: &[u8; 64], rounds: usize) {
+    assert!(rounds <= 64, "SHA-256 has at most 64 rounds");
+    let mut w = [0u32; 64];
+    for (w, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *w = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for t in 16..rounds {
+        w[t] = w[t - 16].wrapping_add(small_sigma0(w[t - 15])).wrapping_add(w[t - 7]).wrapping_add(small_sigma1(w[t - 2]));
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+    for t in 0..rounds {
+        let temp1 = h.wrapping_add(big_sigma1(e)).wrapping_add(ch(e, f, g)).wrapping_add(K[t]).wrapping_add(w[t]);
+        let temp2 = big_sigma0(a).wrapping_add(maj(a, b, c));
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(temp1);
+        d = c;
+        c = b;
+        b = a;
+        a = temp1.wrapping_add(temp2);
+    }
+    for (h, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *h = h.wrapping_add(v);
+    }
+}
+
+/// A streaming hasher for SHA-256 reduced to a chosen number of rounds. **Not secure**, see the module docs.
+#[derive(Clone)]
+pub struct ReducedSha256 {
+    state: [u32; 8],
+    rounds: usize,
+    buf: [u8; 64],
+    buf_len: usize,
+    len: u64,
+}
+
+impl ReducedSha256 {
+    /// Creates a hasher which runs `rounds` rounds per block.
+    ///
+    /// # Arguments
+    /// * `rounds` - How many of the 64 rounds to run, 64 being standard SHA-256.
+    ///
+    /// # Panics
+    /// If `rounds` is more than 64.
+    pub fn new(rounds: usize) -> Self {
+        assert!(rounds <= 64, "SHA-256 has at most 64 rounds");
+        Self { state: H, rounds, buf: [0; 64], buf_len: 0, len: 0 }
+    }
+
+    /// Returns the number of rounds run per block.
+    pub fn rounds(&self) -> usize {
+        self.rounds
+    }
+
+    /// Absorbs more of the message.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len = self.len.wrapping_add(data.len() as u64);
+        while !data.is_empty() {
+            let n = (64 - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len == 64 {
+                compress_reduced(&mut self.state, &self.buf, self.rounds);
+                self.buf_len = 0;
+            }
+        }
+    }
+
+    /// Pads the message as SHA-256 does and returns the digest.
+    ///
+    /// # Returns
+    /// The 32 byte digest.
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bits = self.len.wrapping_mul(8);
+        self.update(&[0x80]);
+        while self.buf_len != 56 {
+            self.update(&[0]);
+        }
+        self.update(&bits.to_be_bytes());
+        let mut out = [0u8; 32];
+        for (chunk, h) in out.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&h.to_be_bytes());
+        }
+        out
+    }
+
+    /// Hashes a whole message.
+    ///
+    /// # Arguments
+    /// * `rounds` - How many of the 64 rounds to run.
+    /// * `msg` - The message.
+    ///
+    /// # Returns
+    /// The 32 byte digest.
+    pub fn digest(rounds: usize, msg: &[u8]) -> [u8; 32] {
+        let mut hasher = Self::new(rounds);
+        hasher.update(msg);
+        hasher.finalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Sha256;
+
+    #[test]
+    fn full_rounds_is_sha256() {
+        let msg: [u8; 200] = core::array::from_fn(|i| i as u8);
+        for len in [0, 55, 56, 64, 200] {
+            assert_eq!(ReducedSha256::digest(64, &msg[..len]), Sha256::new().digest(&msg[..len]));
+        }
+        let mut hasher = ReducedSha256::new(64);
+        hasher.update(&msg[..70]);
+        hasher.update(&msg[70..]);
+        assert_eq!(hasher.finalize(), Sha256::new().digest(&msg));
+    }
+
+    #[test]
+    fn fewer_rounds_differ() {
+        let digests: [[u8; 32]; 65] = core::array::from_fn(|rounds| ReducedSha256::digest(rounds, b"abc"));
+        for (i, a) in digests.iter().enumerate() {
+            assert!(digests[i + 1..].iter().all(|b| a != b));
+        }
+        // 0 rounds only adds the initial values to themselves
+        let mut state = H;
+        compress_reduced(&mut state, &[0; 64], 0);
+        assert_eq!(state, H.map(|h| h.wrapping_add(h)));
+    }
+}
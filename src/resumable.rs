@@ -0,0 +1,155 @@
+//! Hashing large files across interruptions.
+
+use std::io::{self, Read, Seek, SeekFrom};
+
+use crate::{Error, Hash256, Sha256};
+
+/// The progress of hashing a file, which can be persisted and resumed later without rehashing the bytes already done.
+///
+/// ```no_run
+/// # fn main() -> Result<(), sha_256::Error> {
+/// use sha_256::ResumableFileHash;
+/// let mut file = std::fs::File::open("disk.img")?;
+/// let mut progress = ResumableFileHash::new();
+/// // hash in 1GiB steps, saving progress after each one
+/// while progress.advance(&mut file, 1 << 30)? > 0 {
+///     std::fs::write("disk.img.progress", progress.to_bytes())?;
+/// }
+/// let hash = progress.finalize();
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Only the hashing state and offset are saved, so the file must not change between sessions.
+#[derive(Clone)]
+pub struct ResumableFileHash {
+    // the offset is the number of bytes the hasher has absorbed, so the two can't disagree after a failed read
+    sha256: Sha256,
+}
+
+impl Default for ResumableFileHash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResumableFileHash {
+    /// The length of the encoding produced by `to_bytes`.
+    pub const LEN: usize = Sha256::STATE_LEN;
+
+    /// Starts hashing a file from the beginning.
+    pub fn new() -> Self {
+        Self { sha256: Sha256::new() }
+    }
+
+    /// Returns how many bytes of the file have been hashed, i.e. where the next `advance` starts reading.
+    pub fn offset(&self) -> u64 {
+        self.sha256.len
+    }
+
+    /// Hashes up to `limit` more bytes of the file, starting at `offset()`.
+    ///
+    /// # Arguments
+    /// * `file` - The file being hashed, its current position is irrelevant.
+    /// * `limit` - The maximum number of bytes to hash in this call.
+    ///
+    /// # Returns
+    /// The number of bytes hashed, 0 once the end of the file has been reached.
+    /// On error the bytes read before it stay hashed and `offset()` includes them, so the next call carries on after them.
+    pub fn advance<R: Read + Seek>(&mut self, file: &mut R, limit: u64) -> io::Result<u64> {
+        file.seek(SeekFrom::Start(self.offset()))?;
+        io::copy(&mut file.take(limit), &mut self.sha256)
+    }
+
+    /// Encodes the progress for persisting, see `Sha256::export_state`.
+    ///
+    /// # Returns
+    /// The `LEN` byte encoding.
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        // the offset is the number of bytes absorbed, which the hasher state already includes
+        self.sha256.export_state()
+    }
+
+    /// Restores progress saved with `to_bytes`.
+    ///
+    /// # Arguments
+    /// * `bytes` - The saved progress.
+    ///
+    /// # Returns
+    /// The progress, `Error::Parse` if `bytes` is not a valid encoding, or `Error::LengthOverflow` if it claims more than `Sha256::MAX_LEN` bytes hashed.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let state: &[u8; Self::LEN] = bytes.try_into().map_err(|_| Error::Parse { reason: "saved progress has the wrong length" })?;
+        Ok(Self { sha256: Sha256::import_state(state)? })
+    }
+
+    /// Finishes hashing. Call once `advance` returns 0, otherwise only the bytes hashed so far are included.
+    ///
+    /// # Returns
+    /// The hash of the file.
+    pub fn finalize(self) -> Hash256 {
+        Hash256::new(self.sha256.finalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::vec::Vec;
+
+    #[test]
+    fn resumes_where_it_left_off() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+        let mut file = Cursor::new(&data);
+        let mut progress = ResumableFileHash::new();
+        loop {
+            // persist and restore between every step, as if interrupted
+            let saved = progress.to_bytes();
+            progress = ResumableFileHash::from_bytes(&saved).unwrap();
+            if progress.advance(&mut file, 999).unwrap() == 0 {
+                break;
+            }
+        }
+        assert_eq!(progress.offset(), 10_000);
+        assert_eq!(progress.finalize(), Hash256::new(Sha256::new().digest(&data)));
+        assert!(ResumableFileHash::from_bytes(&[0u8; 3]).is_err());
+    }
+
+    // fails once after handing out `fail_at` bytes
+    struct Flaky<'a> {
+        data: Cursor<&'a [u8]>,
+        fail_at: Option<u64>,
+    }
+
+    impl Read for Flaky<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if let Some(fail_at) = self.fail_at {
+                let remaining = fail_at - self.data.position();
+                if remaining == 0 {
+                    self.fail_at = None;
+                    return Err(io::Error::other("disk went away"));
+                }
+                let n = buf.len().min(remaining as usize);
+                return self.data.read(&mut buf[..n]);
+            }
+            self.data.read(buf)
+        }
+    }
+
+    impl Seek for Flaky<'_> {
+        fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+            self.data.seek(pos)
+        }
+    }
+
+    #[test]
+    fn carries_on_after_a_failed_read() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 7) as u8).collect();
+        let mut file = Flaky { data: Cursor::new(&data), fail_at: Some(1234) };
+        let mut progress = ResumableFileHash::new();
+        assert!(progress.advance(&mut file, 5000).is_err());
+        assert_eq!(progress.offset(), 1234);
+        while progress.advance(&mut file, 5000).unwrap() > 0 {}
+        assert_eq!(progress.finalize(), Hash256::new(Sha256::new().digest(&data)));
+    }
+}
@@ -0,0 +1,156 @@
+//! A deterministic random number generator for reproducible test data and simulations.
+//!
+//! The output is SHA-256 in counter mode, block `i` of the stream being `SHA-256(seed || i)` with `i` a big-endian `u64`, so the same seed
+//! gives the same bytes on every platform and in every release, and any part of the stream can be reached without generating what comes before.
+
+use rand_core::{RngCore, SeedableRng};
+
+use crate::{Hash256, Sha256};
+
+/// A deterministic byte stream, `SHA-256(seed || 0) || SHA-256(seed || 1) || ...`.
+///
+/// Only as unpredictable as its seed, and it isn't reseeded, so it isn't a `CryptoRng`. Use the OS generator for keys and nonces.
+///
+/// ```
+/// use rand_core::{RngCore, SeedableRng};
+/// let mut rng = sha_256::Sha256Rng::from_seed_bytes(b"test case 7");
+/// let mut data = [0u8; 1000];
+/// rng.fill_bytes(&mut data);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sha256Rng {
+    seed: [u8; 32],
+    /// The index of the next block to generate.
+    counter: u64,
+    block: [u8; 32],
+    /// How much of `block` has been used, 32 when it needs refilling.
+    pos: usize,
+}
+
+impl Sha256Rng {
+    /// Creates a generator seeded with a digest, e.g. of a test's name.
+    ///
+    /// # Arguments
+    /// * `seed` - The seed.
+    pub fn from_digest(seed: Hash256) -> Self {
+        Self::from_seed(*seed.as_bytes())
+    }
+
+    /// Creates a generator seeded with the SHA-256 of some bytes, so any length of seed can be used.
+    ///
+    /// # Arguments
+    /// * `seed` - The seed.
+    pub fn from_seed_bytes(seed: &[u8]) -> Self {
+        Self::from_seed(Sha256::new().digest(seed))
+    }
+
+    /// Returns how many bytes of the stream have been generated.
+    ///
+    /// The stream is longer than a `u64` can count, so past the first 2^64 bytes this wraps, the same as `seek` only reaches the first 2^64.
+    pub fn position(&self) -> u64 {
+        // blocks up to counter have been generated, minus what's left of the last one. after seek(u64::MAX) counter is 2^59,
+        // so the multiply wraps to 0 before the subtraction brings it back
+        self.counter.wrapping_mul(32).wrapping_sub((32 - self.pos) as u64)
+    }
+
+    /// Moves to any byte of the stream, so the next byte generated is the one at `position`.
+    ///
+    /// # Arguments
+    /// * `position` - The offset into the stream, in bytes.
+    pub fn seek(&mut self, position: u64) {
+        self.counter = position / 32;
+        self.pos = 32;
+        let skip = (position % 32) as usize;
+        if skip > 0 {
+            self.refill();
+            self.pos = skip;
+        }
+    }
+
+    fn refill(&mut self) {
+        let mut sha256 = Sha256::new();
+        sha256.update(&self.seed);
+        sha256.update(&self.counter.to_be_bytes());
+        self.block = sha256.finalize();
+        self.counter = self.counter.wrapping_add(1);
+        self.pos = 0;
+    }
+}
+
+impl SeedableRng for Sha256Rng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: [u8; 32]) -> Self {
+        Self { seed, counter: 0, block: [0; 32], pos: 32 }
+    }
+}
+
+impl RngCore for Sha256Rng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, mut dst: &mut [u8]) {
+        while !dst.is_empty() {
+            if self.pos == 32 {
+                self.refill();
+            }
+            let n = dst.len().min(32 - self.pos);
+            dst[..n].copy_from_slice(&self.block[self.pos..self.pos + n]);
+            self.pos += n;
+            dst = &mut dst[n..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_is_counter_mode_sha256() {
+        let seed = [7u8; 32];
+        let mut rng = Sha256Rng::from_seed(seed);
+        let mut stream = [0u8; 100];
+        // uneven pieces across the block boundaries
+        for piece in stream.chunks_mut(13) {
+            rng.fill_bytes(piece);
+        }
+        assert_eq!(rng.position(), 100);
+        for (i, block) in stream.chunks(32).enumerate() {
+            let mut input = [0u8; 40];
+            input[..32].copy_from_slice(&seed);
+            input[32..].copy_from_slice(&(i as u64).to_be_bytes());
+            assert_eq!(block, &Sha256::new().digest(&input)[..block.len()]);
+        }
+
+        for position in [0, 5, 32, 70, 99] {
+            let mut rng = Sha256Rng::from_seed(seed);
+            rng.seek(position);
+            assert_eq!(rng.position(), position);
+            let mut byte = [0u8];
+            rng.fill_bytes(&mut byte);
+            assert_eq!(byte[0], stream[position as usize], "position {}", position);
+        }
+
+        // the last byte seek can reach, from block 2^59 - 1
+        let mut rng = Sha256Rng::from_seed(seed);
+        rng.seek(u64::MAX);
+        assert_eq!(rng.position(), u64::MAX);
+        let mut input = [0u8; 40];
+        input[..32].copy_from_slice(&seed);
+        input[32..].copy_from_slice(&((1u64 << 59) - 1).to_be_bytes());
+        let mut byte = [0u8];
+        rng.fill_bytes(&mut byte);
+        assert_eq!(byte[0], Sha256::new().digest(&input)[31]);
+        assert_eq!(rng.position(), 0);
+
+        let mut rng = Sha256Rng::from_seed(seed);
+        assert_eq!(rng.next_u32(), u32::from_le_bytes([stream[0], stream[1], stream[2], stream[3]]));
+        assert_eq!(Sha256Rng::from_seed_bytes(b"seed").next_u64(), Sha256Rng::from_digest(Hash256::new(Sha256::new().digest(b"seed"))).next_u64());
+    }
+}
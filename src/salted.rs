@@ -0,0 +1,109 @@
+//! Salted hashing for simple per-record fingerprints.
+//!
+//! The salt is absorbed first using its `Hashable` encoding, i.e. its length as a big-endian `u64` followed by the salt bytes, then the message bytes follow as-is:
+//! `sha256(len(salt) as u64 BE || salt || msg)`.
+//! The length prefix means a salt/message pair cannot be confused with a different split of the same bytes.
+//!
+//! This is not a password hash. Use a dedicated KDF (argon2, scrypt, PBKDF2) for passwords.
+
+use crate::{Hash256, Sha256};
+
+/// The length in bytes of a generated `Salt`.
+pub const SALT_LEN: usize = 16;
+
+/// A randomly generated salt.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Salt([u8; SALT_LEN]);
+
+impl Salt {
+    /// Wraps existing salt bytes, e.g. ones loaded alongside a stored hash.
+    ///
+    /// # Arguments
+    /// * `bytes` - The bytes of the salt.
+    pub const fn new(bytes: [u8; SALT_LEN]) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the raw bytes of the salt.
+    pub const fn as_bytes(&self) -> &[u8; SALT_LEN] {
+        &self.0
+    }
+}
+
+/// Hashes a message with a salt.
+///
+/// # Arguments
+/// * `msg` - A byte slice representing the message to be hashed.
+/// * `salt` - The salt to prefix the message with.
+///
+/// # Returns
+/// The SHA-256 hash of the length-prefixed salt followed by the message.
+pub fn hash_salted(msg: &[u8], salt: &[u8]) -> Hash256 {
+    let mut sha256 = Sha256::new();
+    sha256.absorb(salt);
+    sha256.update(msg);
+    Hash256::new(sha256.finalize())
+}
+
+/// Generates a fresh salt and hashes the message with it.
+///
+/// # Arguments
+/// * `msg` - A byte slice representing the message to be hashed.
+/// * `rng` - A cryptographically secure random number generator to draw the salt from.
+///
+/// # Returns
+/// The generated salt, which must be stored to recompute the hash, and the hash itself.
+#[cfg(feature = "rand_core")]
+pub fn hash_with_generated_salt<R: rand_core::CryptoRng + ?Sized>(msg: &[u8], rng: &mut R) -> (Salt, Hash256) {
+    let mut bytes = [0u8; SALT_LEN];
+    rng.fill_bytes(&mut bytes);
+    let salt = Salt::new(bytes);
+    let hash = hash_salted(msg, salt.as_bytes());
+    (salt, hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn salt_is_length_prefixed() {
+        let mut sha256 = Sha256::new();
+        let expected = sha256.digest(&[0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 3]);
+        assert_eq!(hash_salted(&[3], &[1, 2]).as_bytes(), &expected);
+        assert_ne!(hash_salted(&[3], &[1, 2]), hash_salted(&[2, 3], &[1]));
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn generated_salt_reproduces_hash() {
+        struct Counter(u8);
+
+        impl rand_core::RngCore for Counter {
+            fn next_u32(&mut self) -> u32 {
+                rand_core::impls::next_u32_via_fill(self)
+            }
+
+            fn next_u64(&mut self) -> u64 {
+                rand_core::impls::next_u64_via_fill(self)
+            }
+
+            fn fill_bytes(&mut self, dst: &mut [u8]) {
+                for byte in dst {
+                    self.0 = self.0.wrapping_add(1);
+                    *byte = self.0;
+                }
+            }
+        }
+
+        impl rand_core::CryptoRng for Counter {}
+
+        let mut rng = Counter(0);
+        let (salt, hash) = hash_with_generated_salt(b"hello", &mut rng);
+        assert_eq!(salt.as_bytes()[0], 1);
+        assert_eq!(hash, hash_salted(b"hello", salt.as_bytes()));
+        let (salt2, hash2) = hash_with_generated_salt(b"hello", &mut rng);
+        assert_ne!(salt, salt2);
+        assert_ne!(hash, hash2);
+    }
+}
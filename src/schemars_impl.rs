@@ -0,0 +1,41 @@
+//! `JsonSchema` for `Hash256`, describing its serde representation in JSON.
+
+use alloc::borrow::Cow;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::Hash256;
+
+impl JsonSchema for Hash256 {
+    fn schema_name() -> Cow<'static, str> {
+        "Hash256".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        concat!(module_path!(), "::Hash256").into()
+    }
+
+    fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+        // JSON is human readable, so digests are always the hex form
+        json_schema!({
+            "type": "string",
+            "description": "A SHA-256 digest as 64 lowercase hex characters.",
+            "pattern": "^[0-9a-f]{64}$",
+            "minLength": 64,
+            "maxLength": 64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use schemars::schema_for;
+
+    #[test]
+    fn schema_is_a_hex_string() {
+        let schema = schema_for!(Hash256);
+        assert_eq!(schema.get("type").and_then(|v| v.as_str()), Some("string"));
+        assert_eq!(schema.get("pattern").and_then(|v| v.as_str()), Some("^[0-9a-f]{64}$"));
+    }
+}
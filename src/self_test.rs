@@ -0,0 +1,221 @@
+//! Known-answer tests, run on demand (e.g. at start up) to check the hashing code produces the expected output on this machine.
+//!
+//! `self_test()` runs the built-in vectors from FIPS 180-4 and RFC 4231, then any vectors the application has registered,
+//! such as those mandated by its certification body.
+
+use crate::{Error, Hash256, HmacSha256, Sha256};
+
+/// A message and the digest it must hash to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KnownAnswer {
+    /// Identifies the vector when it fails.
+    pub name: &'static str,
+    /// The HMAC key, or `None` for a plain SHA-256 vector.
+    pub key: Option<&'static [u8]>,
+    /// The message to hash.
+    pub message: &'static [u8],
+    /// The expected digest or MAC.
+    pub expected: [u8; 32],
+}
+
+impl KnownAnswer {
+    /// Creates a SHA-256 vector.
+    ///
+    /// # Arguments
+    /// * `name` - Identifies the vector when it fails.
+    /// * `message` - The message to hash.
+    /// * `expected` - The digest of `message`.
+    pub const fn sha256(name: &'static str, message: &'static [u8], expected: [u8; 32]) -> Self {
+        Self { name, key: None, message, expected }
+    }
+
+    /// Creates an HMAC-SHA256 vector.
+    ///
+    /// # Arguments
+    /// * `name` - Identifies the vector when it fails.
+    /// * `key` - The HMAC key.
+    /// * `message` - The message to authenticate.
+    /// * `expected` - The MAC of `message` under `key`.
+    pub const fn hmac_sha256(name: &'static str, key: &'static [u8], message: &'static [u8], expected: [u8; 32]) -> Self {
+        Self { name, key: Some(key), message, expected }
+    }
+
+    /// Hashes the message and compares the result with the expected digest.
+    ///
+    /// # Returns
+    /// `Error::Mismatch` if the digest is wrong.
+    pub fn check(&self) -> Result<(), Error> {
+        let actual = match self.key {
+            Some(key) => {
+                let mut hmac = HmacSha256::new(key);
+                hmac.update(self.message);
+                hmac.finalize()
+            }
+            None => Sha256::new().digest(self.message),
+        };
+        if actual != self.expected {
+            return Err(Error::Mismatch { expected: Hash256::new(self.expected), actual: Hash256::new(actual), bytes_read: self.message.len() as u64 });
+        }
+        Ok(())
+    }
+}
+
+/// The vectors `self_test()` always runs.
+pub const BUILTIN_KNOWN_ANSWERS: &[KnownAnswer] = &[
+    // FIPS 180-4 examples
+    KnownAnswer::sha256(
+        "SHA-256 empty message",
+        b"",
+        [
+            0xe3, 0xb0, 0xc4, 0x42, 0x98, 0xfc, 0x1c, 0x14, 0x9a, 0xfb, 0xf4, 0xc8, 0x99, 0x6f, 0xb9, 0x24, 0x27, 0xae, 0x41, 0xe4, 0x64, 0x9b, 0x93, 0x4c, 0xa4, 0x95, 0x99,
+            0x1b, 0x78, 0x52, 0xb8, 0x55,
+        ],
+    ),
+    KnownAnswer::sha256(
+        "SHA-256 one block",
+        b"abc",
+        [
+            0xba, 0x78, 0x16, 0xbf, 0x8f, 0x01, 0xcf, 0xea, 0x41, 0x41, 0x40, 0xde, 0x5d, 0xae, 0x22, 0x23, 0xb0, 0x03, 0x61, 0xa3, 0x96, 0x17, 0x7a, 0x9c, 0xb4, 0x10, 0xff,
+            0x61, 0xf2, 0x00, 0x15, 0xad,
+        ],
+    ),
+    KnownAnswer::sha256(
+        "SHA-256 two blocks",
+        b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+        [
+            0x24, 0x8d, 0x6a, 0x61, 0xd2, 0x06, 0x38, 0xb8, 0xe5, 0xc0, 0x26, 0x93, 0x0c, 0x3e, 0x60, 0x39, 0xa3, 0x3c, 0xe4, 0x59, 0x64, 0xff, 0x21, 0x67, 0xf6, 0xec, 0xed,
+            0xd4, 0x19, 0xdb, 0x06, 0xc1,
+        ],
+    ),
+    // RFC 4231 test cases 1, 2 and 6
+    KnownAnswer::hmac_sha256(
+        "HMAC-SHA256 RFC 4231 case 1",
+        &[0x0b; 20],
+        b"Hi There",
+        [
+            0xb0, 0x34, 0x4c, 0x61, 0xd8, 0xdb, 0x38, 0x53, 0x5c, 0xa8, 0xaf, 0xce, 0xaf, 0x0b, 0xf1, 0x2b, 0x88, 0x1d, 0xc2, 0x00, 0xc9, 0x83, 0x3d, 0xa7, 0x26, 0xe9, 0x37,
+            0x6c, 0x2e, 0x32, 0xcf, 0xf7,
+        ],
+    ),
+    KnownAnswer::hmac_sha256(
+        "HMAC-SHA256 RFC 4231 case 2",
+        b"Jefe",
+        b"what do ya want for nothing?",
+        [
+            0x5b, 0xdc, 0xc1, 0x46, 0xbf, 0x60, 0x75, 0x4e, 0x6a, 0x04, 0x24, 0x26, 0x08, 0x95, 0x75, 0xc7, 0x5a, 0x00, 0x3f, 0x08, 0x9d, 0x27, 0x39, 0x83, 0x9d, 0xec, 0x58,
+            0xb9, 0x64, 0xec, 0x38, 0x43,
+        ],
+    ),
+    KnownAnswer::hmac_sha256(
+        "HMAC-SHA256 RFC 4231 case 6",
+        &[0xaa; 131],
+        b"Test Using Larger Than Block-Size Key - Hash Key First",
+        [
+            0x60, 0xe4, 0x31, 0x59, 0x1e, 0xe0, 0xb6, 0x7f, 0x0d, 0x8a, 0x26, 0xaa, 0xcb, 0xf5, 0xb7, 0x7f, 0x8e, 0x0b, 0xc6, 0x21, 0x37, 0x28, 0xc5, 0x14, 0x05, 0x46, 0x04,
+            0x0f, 0x0e, 0xe3, 0x7f, 0x54,
+        ],
+    ),
+];
+
+#[cfg(feature = "std")]
+type Registry = std::sync::Mutex<alloc::vec::Vec<&'static [KnownAnswer]>>;
+
+#[cfg(feature = "std")]
+static REGISTERED: Registry = Registry::new(alloc::vec::Vec::new());
+
+/// Adds vectors for every later `self_test()` to run after the built-in ones.
+///
+/// Vectors loaded at run time can be given a `'static` lifetime with `Box::leak`.
+///
+/// # Arguments
+/// * `answers` - The vectors to add.
+#[cfg(feature = "std")]
+pub fn register_known_answers(answers: &'static [KnownAnswer]) {
+    register_in(&REGISTERED, answers);
+}
+
+#[cfg(feature = "std")]
+fn register_in(registry: &Registry, answers: &'static [KnownAnswer]) {
+    registry.lock().unwrap_or_else(|e| e.into_inner()).push(answers);
+}
+
+/// Runs the built-in known-answer tests, then any added with `register_known_answers`.
+///
+/// # Returns
+/// The name of the first vector which failed and why, see `Error::SelfTest`.
+pub fn self_test() -> Result<(), Error> {
+    #[cfg(feature = "std")]
+    return self_test_registry(&REGISTERED);
+    #[cfg(not(feature = "std"))]
+    self_test_with(&[])
+}
+
+/// `self_test` with the vectors registered in `registry`, so tests can use a registry of their own rather than the global one.
+#[cfg(feature = "std")]
+fn self_test_registry(registry: &Registry) -> Result<(), Error> {
+    self_test_with(&[])?;
+    for answers in registry.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+        run(answers)?;
+    }
+    Ok(())
+}
+
+/// Runs the built-in known-answer tests then `extra`, for `no_std` applications which can't use the registry.
+///
+/// # Arguments
+/// * `extra` - Vectors to run after the built-in ones.
+///
+/// # Returns
+/// The name of the first vector which failed and why, see `Error::SelfTest`.
+pub fn self_test_with(extra: &[KnownAnswer]) -> Result<(), Error> {
+    run(BUILTIN_KNOWN_ANSWERS)?;
+    run(extra)
+}
+
+fn run(answers: &[KnownAnswer]) -> Result<(), Error> {
+    for answer in answers {
+        if let Err(Error::Mismatch { expected, actual, .. }) = answer.check() {
+            return Err(Error::SelfTest { name: answer.name, expected, actual });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_builtin_and_registered_vectors() {
+        self_test().unwrap();
+        let wrong = [KnownAnswer::sha256("wrong", b"abc", [0; 32])];
+        match self_test_with(&wrong) {
+            Err(Error::SelfTest { name, actual, .. }) => {
+                assert_eq!(name, "wrong");
+                assert_eq!(actual.as_bytes(), &BUILTIN_KNOWN_ANSWERS[1].expected);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        #[cfg(feature = "std")]
+        {
+            // a failing vector goes in a registry of its own, the global one is shared with every other test
+            static WRONG: [KnownAnswer; 1] = [KnownAnswer::hmac_sha256("empty key", b"", b"", [0; 32])];
+            let registry = Registry::new(alloc::vec::Vec::new());
+            self_test_registry(&registry).unwrap();
+            register_in(&registry, &WRONG);
+            assert!(matches!(self_test_registry(&registry), Err(Error::SelfTest { name: "empty key", .. })));
+
+            static EXTRA: [KnownAnswer; 1] = [KnownAnswer::hmac_sha256(
+                "empty key",
+                b"",
+                b"",
+                [
+                    0xb6, 0x13, 0x67, 0x9a, 0x08, 0x14, 0xd9, 0xec, 0x77, 0x2f, 0x95, 0xd7, 0x78, 0xc3, 0x5f, 0xc5, 0xff, 0x16, 0x97, 0xc4, 0x93, 0x71, 0x56, 0x53, 0xc6, 0xc7, 0x12,
+                    0x14, 0x42, 0x92, 0xc5, 0xad,
+                ],
+            )];
+            register_known_answers(&EXTRA);
+            self_test().unwrap();
+        }
+    }
+}
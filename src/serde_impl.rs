@@ -0,0 +1,335 @@
+//! serde support for `Hash256` and `SavedState`.
+//!
+//! By default the representation follows the format: a lowercase hex string in human readable formats (JSON, TOML, YAML, ...) and the raw 32 bytes in binary formats (bincode, postcard, ...).
+//! Wrap a digest in `HexHash256` or `BytesHash256` to force one representation regardless of the format.
+
+use core::fmt;
+use core::str;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::{hex, Hash256, SavedState};
+
+/// A `Hash256` which always serializes as a 64 character lowercase hex string.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct HexHash256(pub Hash256);
+
+/// A `Hash256` which always serializes as its raw 32 bytes.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct BytesHash256(pub Hash256);
+
+impl Serialize for Hash256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            HexHash256(*self).serialize(serializer)
+        } else {
+            BytesHash256(*self).serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Hash256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            HexHash256::deserialize(deserializer).map(|hash| hash.0)
+        } else {
+            BytesHash256::deserialize(deserializer).map(|hash| hash.0)
+        }
+    }
+}
+
+impl Serialize for HexHash256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hex = hex::encode(self.0.as_bytes());
+        // hex digits are always ASCII
+        serializer.serialize_str(str::from_utf8(&hex).unwrap())
+    }
+}
+
+impl<'de> Deserialize<'de> for HexHash256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct HexVisitor;
+
+        impl Visitor<'_> for HexVisitor {
+            type Value = HexHash256;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a 64 character hex string")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                hex::decode(value.as_bytes())
+                    .map(|bytes| HexHash256(Hash256::new(bytes)))
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_str(HexVisitor)
+    }
+}
+
+impl Serialize for BytesHash256 {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // a fixed size array is a tuple to serde, so binary formats write the bytes without a length prefix
+        self.0.as_bytes().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BytesHash256 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = BytesHash256;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("32 bytes")
+            }
+
+            // self-describing formats such as CBOR and MessagePack may hold the digest as a byte string, e.g. when written by another language
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                value.try_into().map(|bytes| BytesHash256(Hash256::new(bytes))).map_err(|_| E::invalid_length(value.len(), &self))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut bytes = [0u8; 32];
+                for (i, byte) in bytes.iter_mut().enumerate() {
+                    *byte = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(i, &self))?;
+                }
+                Ok(BytesHash256(Hash256::new(bytes)))
+            }
+        }
+
+        deserializer.deserialize_tuple(32, BytesVisitor)
+    }
+}
+
+impl Serialize for SavedState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("SavedState", 4)?;
+        state.serialize_field("h", &self.h)?;
+        state.serialize_field("len", &self.len)?;
+        state.serialize_field("buffered", &Buffered::new(self.buffered()))?;
+        state.serialize_field("iv", &self.iv)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SavedState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const FIELDS: &[&str] = &["h", "len", "buffered", "iv"];
+
+        enum Field {
+            H,
+            Len,
+            Buffered,
+            Iv,
+        }
+
+        impl<'de> Deserialize<'de> for Field {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct FieldVisitor;
+
+                impl Visitor<'_> for FieldVisitor {
+                    type Value = Field;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("`h`, `len`, `buffered` or `iv`")
+                    }
+
+                    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                        match value {
+                            "h" => Ok(Field::H),
+                            "len" => Ok(Field::Len),
+                            "buffered" => Ok(Field::Buffered),
+                            "iv" => Ok(Field::Iv),
+                            _ => Err(E::unknown_field(value, FIELDS)),
+                        }
+                    }
+                }
+
+                deserializer.deserialize_identifier(FieldVisitor)
+            }
+        }
+
+        struct StateVisitor;
+
+        impl<'de> Visitor<'de> for StateVisitor {
+            type Value = SavedState;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a saved SHA-256 state")
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let h = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let len = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let buffered = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                saved_state(h, len, buffered, seq.next_element()?)
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let (mut h, mut len, mut buffered, mut iv) = (None, None, None, None);
+                while let Some(field) = map.next_key()? {
+                    match field {
+                        Field::H if h.is_none() => h = Some(map.next_value()?),
+                        Field::Len if len.is_none() => len = Some(map.next_value()?),
+                        Field::Buffered if buffered.is_none() => buffered = Some(map.next_value()?),
+                        Field::Iv if iv.is_none() => iv = Some(map.next_value()?),
+                        Field::H => return Err(de::Error::duplicate_field("h")),
+                        Field::Len => return Err(de::Error::duplicate_field("len")),
+                        Field::Buffered => return Err(de::Error::duplicate_field("buffered")),
+                        Field::Iv => return Err(de::Error::duplicate_field("iv")),
+                    }
+                }
+                saved_state(
+                    h.ok_or_else(|| de::Error::missing_field("h"))?,
+                    len.ok_or_else(|| de::Error::missing_field("len"))?,
+                    buffered.ok_or_else(|| de::Error::missing_field("buffered"))?,
+                    iv,
+                )
+            }
+        }
+
+        // states saved before the IV was included are plain SHA-256
+        fn saved_state<E: de::Error>(h: [u32; 8], len: u64, buffered: Buffered, iv: Option<[u32; 8]>) -> Result<SavedState, E> {
+            if buffered.len as u64 != len % 64 {
+                return Err(E::custom("the number of buffered bytes doesn't match len"));
+            }
+            Ok(SavedState { h, len, buffer: buffered.bytes, iv: iv.unwrap_or_else(|| crate::Sha256::new().iv()) })
+        }
+
+        deserializer.deserialize_struct("SavedState", FIELDS, StateVisitor)
+    }
+}
+
+/// The bytes of a partial chunk, serialized as a byte string without the unused part of the buffer.
+struct Buffered {
+    bytes: [u8; 64],
+    len: usize,
+}
+
+impl Buffered {
+    fn new(buffered: &[u8]) -> Self {
+        let mut bytes = [0u8; 64];
+        bytes[..buffered.len()].copy_from_slice(buffered);
+        Self { bytes, len: buffered.len() }
+    }
+}
+
+impl Serialize for Buffered {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.bytes[..self.len])
+    }
+}
+
+impl<'de> Deserialize<'de> for Buffered {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BufferedVisitor;
+
+        impl<'de> Visitor<'de> for BufferedVisitor {
+            type Value = Buffered;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("at most 63 bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+                if value.len() > 63 {
+                    return Err(E::invalid_length(value.len(), &self));
+                }
+                Ok(Buffered::new(value))
+            }
+
+            // formats without byte strings, such as JSON, write them as sequences
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut buffered = Buffered { bytes: [0; 64], len: 0 };
+                while let Some(byte) = seq.next_element()? {
+                    if buffered.len == 63 {
+                        return Err(de::Error::invalid_length(64, &self));
+                    }
+                    buffered.bytes[buffered.len] = byte;
+                    buffered.len += 1;
+                }
+                Ok(buffered)
+            }
+        }
+
+        deserializer.deserialize_bytes(BufferedVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Configure, Token};
+    use std::vec::Vec;
+
+    const HEX: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    fn hello() -> Hash256 {
+        Hash256::new(crate::Sha256::new().digest(b"hello"))
+    }
+
+    fn byte_tokens() -> Vec<Token> {
+        let mut tokens = std::vec![Token::Tuple { len: 32 }];
+        tokens.extend(hello().as_bytes().iter().map(|&b| Token::U8(b)));
+        tokens.push(Token::TupleEnd);
+        tokens
+    }
+
+    #[test]
+    fn representation_follows_format() {
+        assert_tokens(&hello().readable(), &[Token::Str(HEX)]);
+        assert_tokens(&hello().compact(), &byte_tokens());
+    }
+
+    #[test]
+    fn wrappers_force_representation() {
+        assert_tokens(&HexHash256(hello()).compact(), &[Token::Str(HEX)]);
+        assert_tokens(&BytesHash256(hello()).readable(), &byte_tokens());
+    }
+
+    #[test]
+    fn accepts_byte_strings() {
+        // tokens borrow for 'static
+        let bytes: &'static [u8; 32] = std::boxed::Box::leak(std::boxed::Box::new(*hello().as_bytes()));
+        assert_de_tokens(&hello().compact(), &[Token::Bytes(bytes)]);
+        assert_de_tokens(&BytesHash256(hello()), &[Token::BorrowedBytes(bytes)]);
+        assert_de_tokens_error::<BytesHash256>(&[Token::Bytes(&[0; 31])], "invalid length 31, expected 32 bytes");
+        assert_de_tokens_error::<BytesHash256>(&[Token::Seq { len: Some(1) }, Token::U8(0), Token::SeqEnd], "invalid length 1, expected 32 bytes");
+    }
+
+    #[test]
+    fn saved_state_round_trips() {
+        let saved = crate::Sha256::new().chain(&[7; 66]).save_state();
+        let mut tokens = std::vec![Token::Struct { name: "SavedState", len: 4 }, Token::Str("h"), Token::Tuple { len: 8 }];
+        tokens.extend(saved.h.iter().map(|&h| Token::U32(h)));
+        tokens.extend([Token::TupleEnd, Token::Str("len"), Token::U64(66), Token::Str("buffered"), Token::Bytes(&[7, 7])]);
+        let iv_at = tokens.len();
+        tokens.extend([Token::Str("iv"), Token::Tuple { len: 8 }]);
+        tokens.extend(saved.iv.iter().map(|&iv| Token::U32(iv)));
+        tokens.extend([Token::TupleEnd, Token::StructEnd]);
+        assert_tokens(&saved, &tokens);
+
+        // saved before the IV was included, which means SHA-256's
+        tokens.drain(iv_at..tokens.len() - 1);
+        assert_de_tokens(&saved, &tokens);
+
+        // as JSON writes it, the buffered bytes as a sequence
+        let at = tokens.len() - 2;
+        tokens.splice(at..at + 1, [Token::Seq { len: Some(2) }, Token::U8(7), Token::U8(7), Token::SeqEnd]);
+        assert_de_tokens(&saved, &tokens);
+
+        tokens.splice(at..at + 4, [Token::Bytes(&[7])]);
+        assert_de_tokens_error::<SavedState>(&tokens, "the number of buffered bytes doesn't match len");
+    }
+
+    #[test]
+    fn rejects_bad_hex() {
+        assert_de_tokens_error::<HexHash256>(&[Token::Str("2cf2")], "invalid value: string \"2cf2\", expected a 64 character hex string");
+    }
+}
@@ -0,0 +1,125 @@
+//! SHA-224, which is SHA-256 started from a different IV with the output truncated to 28 bytes (FIPS 180-4 section 6.3).
+
+use crate::Sha256;
+
+// the SHA-224 initial hash values, the second 32 bits of the fractional parts of the square roots of the 9th to 16th primes
+const H224: [u32; 8] = [
+    0xc1059ed8, 0x367cd507, 0x3070dd17, 0xf70e5939, 0xffc00b31, 0x68581511, 0x64f98fa7, 0xbefa4fa4,
+];
+
+/// A structure representing the SHA-224 hash algorithm, for protocols which still call for it.
+///
+/// It has the same compression function and streaming API as `Sha256`, so it's as fast.
+#[derive(Clone)]
+pub struct Sha224 {
+    sha256: Sha256,
+}
+
+impl Default for Sha224 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha224 {
+    /// Creates a new instance of the SHA-224 hash algorithm.
+    pub fn new() -> Self {
+        Self { sha256: Sha256::with_iv(H224) }
+    }
+
+    /// Resets the hasher to its initial state, discarding any data passed to `update`.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Absorbs more of the message into the hasher.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, data: &[u8]) {
+        self.sha256.update(data);
+    }
+
+    /// Absorbs more of the message and returns the hasher, so a message made of a few pieces can be hashed in one expression.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn chain(mut self, data: &[u8]) -> Self {
+        self.update(data);
+        self
+    }
+
+    /// Pads the message absorbed via `update` and returns its SHA-224 digest.
+    ///
+    /// # Returns
+    /// A 28-byte array representing the SHA-224 hash of the message.
+    pub fn finalize(mut self) -> [u8; 28] {
+        self.finalize_reset()
+    }
+
+    /// Pads the message absorbed via `update`, returns its SHA-224 digest and resets the hasher, ready for the next message.
+    ///
+    /// # Returns
+    /// A 28-byte array representing the SHA-224 hash of the message.
+    pub fn finalize_reset(&mut self) -> [u8; 28] {
+        let hash = self.sha256.finalize_with(|_| {});
+        self.reset();
+        // SHA-224 drops the last of the 8 hash values
+        hash[..28].try_into().unwrap()
+    }
+
+    /// Computes the SHA-224 digest of the given message. Any data previously passed to `update` is discarded.
+    ///
+    /// # Arguments
+    /// * `msg` - A byte slice representing the message to be hashed.
+    ///
+    /// # Returns
+    /// A 28-byte array representing the SHA-224 hash of the message.
+    pub fn digest(&mut self, msg: &[u8]) -> [u8; 28] {
+        self.reset();
+        self.update(msg);
+        self.finalize_reset()
+    }
+}
+
+/// Computes the SHA-224 digest of a message in one call.
+///
+/// # Arguments
+/// * `msg` - A byte slice representing the message to be hashed.
+///
+/// # Returns
+/// A 28-byte array representing the SHA-224 hash of the message.
+pub fn sha224(msg: &[u8]) -> [u8; 28] {
+    Sha224::new().digest(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use sha2::Digest;
+    use std::vec::Vec;
+
+    #[test]
+    fn fips_180_4_examples() {
+        let hex = |hash: [u8; 28]| hash.iter().map(|b| std::format!("{:02x}", b)).collect::<std::string::String>();
+        assert_eq!(hex(sha224(b"abc")), "23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7");
+        assert_eq!(hex(sha224(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")), "75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525");
+        assert_eq!(hex(sha224(b"")), "d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f");
+    }
+
+    #[test]
+    fn matches_sha2_when_streamed() {
+        let msg: Vec<u8> = (0..300u32).map(|i| (i * 13) as u8).collect();
+        let mut sha224 = Sha224::new();
+        for len in [0, 1, 55, 56, 63, 64, 65, 300] {
+            let expected = sha2::Sha224::digest(&msg[..len]);
+            for piece in msg[..len].chunks(17) {
+                sha224.update(piece);
+            }
+            assert_eq!(&sha224.finalize_reset()[..], &expected[..], "len {}", len);
+            assert_eq!(&Sha224::new().chain(&msg[..len]).finalize()[..], &expected[..], "len {}", len);
+        }
+    }
+}
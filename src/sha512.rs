@@ -0,0 +1,393 @@
+//! SHA-512, SHA-384, SHA-512/256 and SHA-512/224 (FIPS 180-4 sections 6.4 to 6.7), the 64-bit word members of the SHA-2 family.
+//!
+//! The structure is SHA-256's with 64-bit words, 128 byte blocks, 80 rounds and a 128-bit length field. The others are SHA-512
+//! started from a different IV with the output truncated to 48, 32 or 28 bytes. On 64-bit CPUs SHA-512 processes twice the data per round, so it's
+//! usually faster than SHA-256 for long messages.
+
+// the SHA-512 initial hash values, the first 64 bits of the fractional parts of the square roots of the first 8 primes
+const H512: [u64; 8] = [
+    0x6a09e667f3bcc908, 0xbb67ae8584caa73b, 0x3c6ef372fe94f82b, 0xa54ff53a5f1d36f1,
+    0x510e527fade682d1, 0x9b05688c2b3e6c1f, 0x1f83d9abfb41bd6b, 0x5be0cd19137e2179,
+];
+
+// the SHA-384 initial hash values, from the square roots of the 9th to 16th primes
+const H384: [u64; 8] = [
+    0xcbbb9d5dc1059ed8, 0x629a292a367cd507, 0x9159015a3070dd17, 0x152fecd8f70e5939,
+    0x67332667ffc00b31, 0x8eb44a8768581511, 0xdb0c2e0d64f98fa7, 0x47b5481dbefa4fa4,
+];
+
+// the SHA-512/t initial hash values for t = 256 and 224, generated as in FIPS 180-4 section 5.3.6: SHA-512 of "SHA-512/t"
+// starting from the SHA-512 IV with every word XORed with 0xa5a5a5a5a5a5a5a5
+const H512_256: [u64; 8] = [
+    0x22312194fc2bf72c, 0x9f555fa3c84c64c2, 0x2393b86b6f53b151, 0x963877195940eabd,
+    0x96283ee2a88effe3, 0xbe5e1e2553863992, 0x2b0199fc2c85b8aa, 0x0eb72ddc81c52ca2,
+];
+const H512_224: [u64; 8] = [
+    0x8c3d37c819544da2, 0x73e1996689dcd4d6, 0x1dfab7ae32ff9c82, 0x679dd514582f9fcf,
+    0x0f6d2b697bd44da8, 0x77e36f7304c48942, 0x3f9d85a86a1d36c8, 0x1112e6ad91d692a1,
+];
+
+// the first 64 bits of the fractional parts of the cube roots of the first 80 primes
+const K512: [u64; 80] = [
+    0x428a2f98d728ae22, 0x7137449123ef65cd, 0xb5c0fbcfec4d3b2f, 0xe9b5dba58189dbbc,
+    0x3956c25bf348b538, 0x59f111f1b605d019, 0x923f82a4af194f9b, 0xab1c5ed5da6d8118,
+    0xd807aa98a3030242, 0x12835b0145706fbe, 0x243185be4ee4b28c, 0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f, 0x80deb1fe3b1696b1, 0x9bdc06a725c71235, 0xc19bf174cf692694,
+    0xe49b69c19ef14ad2, 0xefbe4786384f25e3, 0x0fc19dc68b8cd5b5, 0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275, 0x4a7484aa6ea6e483, 0x5cb0a9dcbd41fbd4, 0x76f988da831153b5,
+    0x983e5152ee66dfab, 0xa831c66d2db43210, 0xb00327c898fb213f, 0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2, 0xd5a79147930aa725, 0x06ca6351e003826f, 0x142929670a0e6e70,
+    0x27b70a8546d22ffc, 0x2e1b21385c26c926, 0x4d2c6dfc5ac42aed, 0x53380d139d95b3df,
+    0x650a73548baf63de, 0x766a0abb3c77b2a8, 0x81c2c92e47edaee6, 0x92722c851482353b,
+    0xa2bfe8a14cf10364, 0xa81a664bbc423001, 0xc24b8b70d0f89791, 0xc76c51a30654be30,
+    0xd192e819d6ef5218, 0xd69906245565a910, 0xf40e35855771202a, 0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8, 0x1e376c085141ab53, 0x2748774cdf8eeb99, 0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63, 0x4ed8aa4ae3418acb, 0x5b9cca4f7763e373, 0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc, 0x78a5636f43172f60, 0x84c87814a1f0ab72, 0x8cc702081a6439ec,
+    0x90befffa23631e28, 0xa4506cebde82bde9, 0xbef9a3f7b2c67915, 0xc67178f2e372532b,
+    0xca273eceea26619c, 0xd186b8c721c0c207, 0xeada7dd6cde0eb1e, 0xf57d4f7fee6ed178,
+    0x06f067aa72176fba, 0x0a637dc5a2c898a6, 0x113f9804bef90dae, 0x1b710b35131c471b,
+    0x28db77f523047d84, 0x32caab7b40c72493, 0x3c9ebe0a15c9bebc, 0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6, 0x597f299cfc657e2a, 0x5fcb6fab3ad6faec, 0x6c44198c4a475817,
+];
+
+/// A structure representing the SHA-512 hash algorithm.
+#[derive(Clone)]
+pub struct Sha512 {
+    h: [u64; 8], // the 8 hash values
+    // streaming state
+    buf: [u8; 128], // bytes of a partial block which have not been processed yet
+    buf_len: usize, // how many bytes of buf are in use
+    len: u128, // total number of message bytes absorbed so far
+}
+
+impl Default for Sha512 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha512 {
+    /// Creates a new instance of the SHA-512 hash algorithm.
+    pub fn new() -> Self {
+        Self::with_iv(H512)
+    }
+
+    /// Creates a hasher starting from other initial hash values, for the variants which share SHA-512's compression function.
+    pub(crate) const fn with_iv(iv: [u64; 8]) -> Self {
+        Self { h: iv, buf: [0; 128], buf_len: 0, len: 0 }
+    }
+
+    /// Resets the hasher to its initial state, discarding any data passed to `update`.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Absorbs more of the message into the hasher.
+    ///
+    /// Can be called any number of times. Full blocks are processed immediately, any remainder is buffered until the next call or `finalize`.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len = self.len.wrapping_add(data.len() as u128);
+        if self.buf_len > 0 {
+            // top up the partial block first
+            let n = data.len().min(128 - self.buf_len);
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < 128 {
+                return;
+            }
+            let buf = self.buf;
+            compress(&mut self.h, &buf);
+            self.buf_len = 0;
+        }
+        let mut blocks = data.chunks_exact(128);
+        for block in &mut blocks {
+            compress(&mut self.h, block.try_into().unwrap());
+        }
+        let rest = blocks.remainder();
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.buf_len = rest.len();
+    }
+
+    /// Absorbs more of the message and returns the hasher, so a message made of a few pieces can be hashed in one expression.
+    ///
+    /// # Arguments
+    /// * `data` - The next bytes of the message.
+    pub fn chain(mut self, data: &[u8]) -> Self {
+        self.update(data);
+        self
+    }
+
+    /// Pads the message absorbed via `update` and returns its SHA-512 digest.
+    ///
+    /// # Returns
+    /// A 64-byte array representing the SHA-512 hash of the message.
+    pub fn finalize(mut self) -> [u8; 64] {
+        self.pad()
+    }
+
+    /// Pads the message absorbed via `update`, returns its SHA-512 digest and resets the hasher, ready for the next message.
+    ///
+    /// # Returns
+    /// A 64-byte array representing the SHA-512 hash of the message.
+    pub fn finalize_reset(&mut self) -> [u8; 64] {
+        let hash = self.pad();
+        self.reset();
+        hash
+    }
+
+    /// Computes the SHA-512 digest of the given message. Any data previously passed to `update` is discarded.
+    ///
+    /// # Arguments
+    /// * `msg` - A byte slice representing the message to be hashed.
+    ///
+    /// # Returns
+    /// A 64-byte array representing the SHA-512 hash of the message.
+    pub fn digest(&mut self, msg: &[u8]) -> [u8; 64] {
+        self.reset();
+        self.update(msg);
+        self.finalize_reset()
+    }
+
+    /// Pads the message and returns the 8 hash values as bytes, leaving the hasher in an unspecified state.
+    pub(crate) fn pad(&mut self) -> [u8; 64] {
+        let n = self.buf_len;
+        // after the msg ends, we pad with a 0b10000000 byte followed by 0's
+        self.buf[n] = 0b10000000;
+        self.buf[n + 1..].fill(0);
+        if n > 111 {
+            // no space for the length field, so it goes at the end of an extra block of 0's
+            let buf = self.buf;
+            compress(&mut self.h, &buf);
+            self.buf = [0; 128];
+        }
+        self.buf[112..].copy_from_slice(&self.len.wrapping_mul(8).to_be_bytes());
+        let buf = self.buf;
+        compress(&mut self.h, &buf);
+
+        let mut hash = [0u8; 64];
+        for (bytes, h) in hash.chunks_exact_mut(8).zip(self.h) {
+            bytes.copy_from_slice(&h.to_be_bytes());
+        }
+        hash
+    }
+}
+
+/// Defines a variant which is SHA-512 from a different IV, truncated to `$len` bytes, with the same API as `Sha512` and a one-shot function.
+macro_rules! truncated_sha512 {
+    ($(#[$doc:meta])* $name:ident, $fn_name:ident, $alg:literal, $iv:expr, $len:literal) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            sha512: Sha512,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl $name {
+            #[doc = concat!("Creates a new instance of the ", $alg, " hash algorithm.")]
+            pub fn new() -> Self {
+                Self { sha512: Sha512::with_iv($iv) }
+            }
+
+            /// Resets the hasher to its initial state, discarding any data passed to `update`.
+            pub fn reset(&mut self) {
+                *self = Self::new();
+            }
+
+            /// Absorbs more of the message into the hasher.
+            ///
+            /// # Arguments
+            /// * `data` - The next bytes of the message.
+            pub fn update(&mut self, data: &[u8]) {
+                self.sha512.update(data);
+            }
+
+            /// Absorbs more of the message and returns the hasher, so a message made of a few pieces can be hashed in one expression.
+            ///
+            /// # Arguments
+            /// * `data` - The next bytes of the message.
+            pub fn chain(mut self, data: &[u8]) -> Self {
+                self.update(data);
+                self
+            }
+
+            #[doc = concat!("Pads the message absorbed via `update` and returns its ", $alg, " digest.")]
+            ///
+            /// # Returns
+            #[doc = concat!("A ", $len, "-byte array representing the ", $alg, " hash of the message.")]
+            pub fn finalize(mut self) -> [u8; $len] {
+                self.finalize_reset()
+            }
+
+            #[doc = concat!("Pads the message absorbed via `update`, returns its ", $alg, " digest and resets the hasher, ready for the next message.")]
+            ///
+            /// # Returns
+            #[doc = concat!("A ", $len, "-byte array representing the ", $alg, " hash of the message.")]
+            pub fn finalize_reset(&mut self) -> [u8; $len] {
+                let hash = self.sha512.pad();
+                self.reset();
+                hash[..$len].try_into().unwrap()
+            }
+
+            #[doc = concat!("Computes the ", $alg, " digest of the given message. Any data previously passed to `update` is discarded.")]
+            ///
+            /// # Arguments
+            /// * `msg` - A byte slice representing the message to be hashed.
+            ///
+            /// # Returns
+            #[doc = concat!("A ", $len, "-byte array representing the ", $alg, " hash of the message.")]
+            pub fn digest(&mut self, msg: &[u8]) -> [u8; $len] {
+                self.reset();
+                self.update(msg);
+                self.finalize_reset()
+            }
+        }
+
+        #[doc = concat!("Computes the ", $alg, " digest of a message in one call.")]
+        ///
+        /// # Arguments
+        /// * `msg` - A byte slice representing the message to be hashed.
+        ///
+        /// # Returns
+        #[doc = concat!("A ", $len, "-byte array representing the ", $alg, " hash of the message.")]
+        pub fn $fn_name(msg: &[u8]) -> [u8; $len] {
+            $name::new().digest(msg)
+        }
+    };
+}
+
+truncated_sha512!(
+    /// A structure representing the SHA-384 hash algorithm, SHA-512 with a different IV truncated to 48 bytes.
+    Sha384, sha384, "SHA-384", H384, 48
+);
+
+truncated_sha512!(
+    /// A structure representing the SHA-512/256 hash algorithm, SHA-512 with an IV generated for a 256-bit output, truncated to 32 bytes.
+    ///
+    /// The same size digest as SHA-256 but usually faster on 64-bit CPUs, and unlike SHA-256 not open to length extension.
+    /// It's a different hash though, so only use it where both sides agree on it.
+    Sha512_256, sha512_256, "SHA-512/256", H512_256, 32
+);
+
+truncated_sha512!(
+    /// A structure representing the SHA-512/224 hash algorithm, SHA-512 with an IV generated for a 224-bit output, truncated to 28 bytes.
+    Sha512_224, sha512_224, "SHA-512/224", H512_224, 28
+);
+
+/// Computes the SHA-512 digest of a message in one call.
+///
+/// # Arguments
+/// * `msg` - A byte slice representing the message to be hashed.
+///
+/// # Returns
+/// A 64-byte array representing the SHA-512 hash of the message.
+pub fn sha512(msg: &[u8]) -> [u8; 64] {
+    Sha512::new().digest(msg)
+}
+
+/// Processes a single 128 byte block, updating the hash values.
+fn compress(h: &mut [u64; 8], block: &[u8; 128]) {
+    // the message schedule, 16 words from the block and 64 derived from them
+    let mut w = [0u64; 80];
+    for (word, bytes) in w.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_be_bytes(bytes.try_into().unwrap());
+    }
+    for i in 16..80 {
+        let s0 = w[i - 15].rotate_right(1) ^ w[i - 15].rotate_right(8) ^ (w[i - 15] >> 7);
+        let s1 = w[i - 2].rotate_right(19) ^ w[i - 2].rotate_right(61) ^ (w[i - 2] >> 6);
+        w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut hh] = *h;
+    for i in 0..80 {
+        let big_s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = hh.wrapping_add(big_s1).wrapping_add(ch).wrapping_add(K512[i]).wrapping_add(w[i]);
+        let big_s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = big_s0.wrapping_add(maj);
+        hh = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+    for (h, x) in h.iter_mut().zip([a, b, c, d, e, f, g, hh]) {
+        *h = h.wrapping_add(x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use sha2::Digest;
+    use std::string::String;
+    use std::vec::Vec;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| std::format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn fips_180_4_examples() {
+        let two_blocks = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+        assert_eq!(hex(&sha512(b"abc")), "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f");
+        assert_eq!(hex(&sha512(two_blocks)), "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909");
+        assert_eq!(hex(&sha384(b"abc")), "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7");
+        assert_eq!(hex(&sha384(two_blocks)), "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039");
+    }
+
+    #[test]
+    fn sha512_t() {
+        // the IV generation function of FIPS 180-4 section 5.3.6
+        let iv = |t: &str| {
+            let mut sha512 = Sha512::with_iv(H512.map(|h| h ^ 0xa5a5a5a5a5a5a5a5));
+            sha512.update(t.as_bytes());
+            let hash = sha512.pad();
+            core::array::from_fn(|i| u64::from_be_bytes(hash[i * 8..i * 8 + 8].try_into().unwrap()))
+        };
+        assert_eq!(iv("SHA-512/256"), H512_256);
+        assert_eq!(iv("SHA-512/224"), H512_224);
+
+        // examples from the NIST cryptographic standards and guidelines site
+        assert_eq!(hex(&sha512_256(b"abc")), "53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23");
+        assert_eq!(hex(&sha512_224(b"abc")), "4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa");
+        let msg = [0x5cu8; 300];
+        let mut sha512_256 = Sha512_256::new();
+        sha512_256.update(&msg[..100]);
+        sha512_256.update(&msg[100..]);
+        assert_eq!(&sha512_256.finalize()[..], &sha2::Sha512_256::digest(msg)[..]);
+        assert_eq!(&Sha512_224::new().chain(&msg).finalize()[..], &sha2::Sha512_224::digest(msg)[..]);
+    }
+
+    #[test]
+    fn matches_sha2_when_streamed() {
+        let msg: Vec<u8> = (0..600u32).map(|i| (i * 13) as u8).collect();
+        let mut sha512 = Sha512::new();
+        let mut sha384 = Sha384::new();
+        // across the 111/112 byte padding boundary and whole blocks
+        for len in [0, 1, 111, 112, 127, 128, 129, 239, 240, 600] {
+            for piece in msg[..len].chunks(37) {
+                sha512.update(piece);
+                sha384.update(piece);
+            }
+            assert_eq!(&sha512.finalize_reset()[..], &sha2::Sha512::digest(&msg[..len])[..], "len {}", len);
+            assert_eq!(&sha384.finalize_reset()[..], &sha2::Sha384::digest(&msg[..len])[..], "len {}", len);
+            assert_eq!(&Sha512::new().chain(&msg[..len]).finalize()[..], &sha2::Sha512::digest(&msg[..len])[..], "len {}", len);
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! sqlx `Type`/`Encode`/`Decode` for `Hash256`, storing digests as their raw 32 bytes (`BYTEA`/`BLOB`/`VARBINARY`).
+
+use std::format;
+use std::vec::Vec;
+
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Decode, Encode, Type};
+
+use crate::Hash256;
+
+impl<DB: Database> Type<DB> for Hash256
+where
+    [u8]: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <[u8] as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <[u8] as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for Hash256
+where
+    Vec<u8>: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut <DB as Database>::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        // the argument buffer may outlive the digest, so the bytes have to be copied
+        self.as_bytes().to_vec().encode_by_ref(buf)
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for Hash256
+where
+    &'r [u8]: Decode<'r, DB>,
+{
+    fn decode(value: <DB as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let bytes = <&[u8] as Decode<DB>>::decode(value)?;
+        let bytes: [u8; 32] = bytes.try_into().map_err(|_| format!("expected a 32 byte SHA-256 digest, got {} bytes", bytes.len()))?;
+        Ok(Hash256::new(bytes))
+    }
+}
+
@@ -0,0 +1,104 @@
+//! Subresource Integrity (SRI) metadata, the `sha256-<base64>` values of `integrity` attributes on `<script>` and `<link>` elements.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+use crate::{Error, Hash256};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl Hash256 {
+    /// Formats the digest as SRI metadata, `sha256-` followed by the padded base64 of the digest.
+    ///
+    /// ```
+    /// let hash = sha_256::Hash256::from(sha_256::sha256(b""));
+    /// assert_eq!(hash.to_sri(), "sha256-47DEQpj8HBSa+/TImW+5JCeuQeRkm5NMpJWZG3hSuFU=");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn to_sri(&self) -> String {
+        let mut sri = String::with_capacity(7 + 44);
+        sri.push_str("sha256-");
+        for chunk in self.as_bytes().chunks(3) {
+            let n = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+            for i in 0..4 {
+                sri.push(if i <= chunk.len() { char::from(BASE64[(n >> (18 - 6 * i)) as usize & 63]) } else { '=' });
+            }
+        }
+        sri
+    }
+
+    /// Parses SRI metadata, such as the value of an `integrity` attribute.
+    ///
+    /// The metadata may list several hashes separated by whitespace, e.g. `sha384-... sha256-...`, and each may be followed by `?options`,
+    /// which are ignored as the spec says. The first `sha256` hash is returned.
+    ///
+    /// # Arguments
+    /// * `metadata` - The SRI metadata.
+    ///
+    /// # Returns
+    /// The hash, or `Error::Parse` if there is no `sha256` hash or it isn't 44 characters of padded base64.
+    pub fn from_sri(metadata: &str) -> Result<Self, Error> {
+        let encoded = metadata
+            .split_ascii_whitespace()
+            .find_map(|token| token.strip_prefix("sha256-"))
+            .ok_or(Error::Parse { reason: "SRI metadata has no sha256 hash" })?;
+        let encoded = encoded.split_once('?').map_or(encoded, |(encoded, _)| encoded).as_bytes();
+        let malformed = || Error::Parse { reason: "SRI hash is not 44 characters of base64" };
+        if encoded.len() != 44 || encoded[43] != b'=' {
+            return Err(malformed());
+        }
+
+        let mut bytes = [0u8; 32];
+        let mut sextets = encoded[..43].iter().map(|&c| BASE64.iter().position(|&b| b == c).map(|v| v as u32));
+        for chunk in bytes.chunks_mut(3) {
+            let mut n = 0;
+            for i in 0..4 {
+                // the last chunk is 2 bytes, encoded in 3 characters
+                let sextet = if i <= chunk.len() { sextets.next().unwrap().ok_or_else(malformed)? } else { 0 };
+                n |= sextet << (18 - 6 * i);
+            }
+            for (i, byte) in chunk.iter_mut().enumerate() {
+                *byte = (n >> (16 - 8 * i)) as u8;
+            }
+            if chunk.len() < 3 && n & 0xff != 0 {
+                return Err(Error::Parse { reason: "SRI hash has non-zero padding bits" });
+            }
+        }
+        Ok(Hash256::new(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_metadata() {
+        let hash = Hash256::new(crate::sha256(b"alert('Hello, world.');"));
+        let base64 = "qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng=";
+        assert_eq!(Hash256::from_sri(&["sha256-", base64].concat()).unwrap(), hash);
+        assert_eq!(Hash256::from_sri(&["sha384-abc\n  sha256-", base64, "?ct=application/javascript sha256-x"].concat()).unwrap(), hash);
+        #[cfg(feature = "alloc")]
+        assert_eq!(hash.to_sri(), ["sha256-", base64].concat());
+
+        for bad in [
+            "",
+            "sha384-H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO",
+            "sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tng",
+            "sha256-qznLcsROx4GACP2dm0UCKCzCG-HiZ1guq6ZZDob_Tng=",
+            "sha256-qznLcsROx4GACP2dm0UCKCzCG+HiZ1guq6ZZDob/Tnh=",
+        ] {
+            assert!(matches!(Hash256::from_sri(bad), Err(Error::Parse { .. })), "{}", bad);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn round_trips() {
+        let mut rng = crate::tests::Rng::new(3);
+        for _ in 0..100 {
+            let hash = Hash256::new(core::array::from_fn(|_| rng.next() as u8));
+            assert_eq!(Hash256::from_sri(&hash.to_sri()).unwrap(), hash);
+        }
+    }
+}
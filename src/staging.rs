@@ -0,0 +1,135 @@
+//! Collects a message into runs of whole blocks for accelerators which hash many blocks per operation, e.g. over DMA.
+
+/// A buffer of `N` bytes, a multiple of 64 and at least two blocks, which hands out whole blocks and pads the end of the message.
+pub(crate) struct Staging<const N: usize> {
+    buffer: [u8; N],
+    len: usize,
+    total: u64,
+}
+
+impl<const N: usize> Staging<N> {
+    pub(crate) fn new() -> Self {
+        Self { buffer: [0; N], len: 0, total: 0 }
+    }
+
+    /// Buffers data, passing the buffer to `sink` each time it fills.
+    ///
+    /// # Arguments
+    /// * `data` - The next part of the message.
+    /// * `sink` - Hashes whole blocks, `N` bytes at a time.
+    pub(crate) fn update(&mut self, mut data: &[u8], mut sink: impl FnMut(&[u8])) {
+        self.total += data.len() as u64;
+        while !data.is_empty() {
+            let take = data.len().min(N - self.len);
+            self.buffer[self.len..self.len + take].copy_from_slice(&data[..take]);
+            self.len += take;
+            data = &data[take..];
+            if self.len == N {
+                sink(&self.buffer);
+                self.len = 0;
+            }
+        }
+    }
+
+    /// Pads the message and passes the remaining blocks to `sink`, leaving the buffer ready for the next message.
+    ///
+    /// # Arguments
+    /// * `sink` - Hashes whole blocks; called once or twice, with at most `N` bytes.
+    pub(crate) fn finish(&mut self, mut sink: impl FnMut(&[u8])) {
+        // flush the whole blocks first so the padding always fits
+        let whole = self.len / 64 * 64;
+        if whole > 0 {
+            sink(&self.buffer[..whole]);
+            self.buffer.copy_within(whole..self.len, 0);
+            self.len -= whole;
+        }
+        let padded = if self.len < 56 { 64 } else { 128 };
+        self.buffer[self.len] = 0x80;
+        self.buffer[self.len + 1..padded - 8].fill(0);
+        self.buffer[padded - 8..padded].copy_from_slice(&(self.total * 8).to_be_bytes());
+        sink(&self.buffer[..padded]);
+        self.len = 0;
+        self.total = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::tests::Rng;
+    use crate::{compress, sha256, H};
+    use std::vec::Vec;
+
+    // hashes what the staging buffer hands out in software, recording the run lengths
+    fn digest<const N: usize>(parts: &[&[u8]]) -> ([u8; 32], Vec<usize>) {
+        let mut staging = Staging::<N>::new();
+        let mut state = H;
+        let mut runs = Vec::new();
+        let mut sink = |blocks: &[u8]| {
+            assert_eq!(blocks.len() % 64, 0);
+            assert!(blocks.len() <= N);
+            runs.push(blocks.len());
+            for block in blocks.chunks_exact(64) {
+                compress(&mut state, block.try_into().unwrap());
+            }
+        };
+        for part in parts {
+            staging.update(part, &mut sink);
+        }
+        staging.finish(&mut sink);
+        let mut hash = [0u8; 32];
+        for (out, h) in hash.chunks_exact_mut(4).zip(state) {
+            out.copy_from_slice(&h.to_be_bytes());
+        }
+        (hash, runs)
+    }
+
+    #[test]
+    fn pads_every_tail_length() {
+        let msg: Vec<u8> = (0..600u32).map(|i| (i * 7) as u8).collect();
+        for len in 0..msg.len() {
+            assert_eq!(digest::<256>(&[&msg[..len]]).0, sha256(&msg[..len]), "len {}", len);
+        }
+    }
+
+    #[test]
+    fn hands_out_full_buffers() {
+        let msg = [0x5a; 4032 * 2 + 100];
+        let (hash, runs) = digest::<4032>(&[&msg]);
+        assert_eq!(hash, sha256(&msg));
+        assert_eq!(runs, [4032, 4032, 64, 64]);
+        // a tail that leaves room for the length needs a single padding block
+        let (_, runs) = digest::<4032>(&[&msg[..4032 + 55]]);
+        assert_eq!(runs, [4032, 64]);
+    }
+
+    #[test]
+    fn matches_across_updates() {
+        let mut rng = Rng::new(7);
+        let msg: Vec<u8> = (0..3000).map(|_| rng.next() as u8).collect();
+        for _ in 0..50 {
+            let mut parts = Vec::new();
+            let mut rest = &msg[..];
+            while !rest.is_empty() {
+                let (part, tail) = rest.split_at((rng.next() as usize % 300).min(rest.len()));
+                parts.push(part);
+                rest = tail;
+            }
+            assert_eq!(digest::<128>(&parts).0, sha256(&msg));
+        }
+    }
+
+    #[test]
+    fn is_ready_for_the_next_message() {
+        let mut staging = Staging::<128>::new();
+        let mut first = Vec::new();
+        staging.update(&[1; 200], |blocks| first.extend_from_slice(blocks));
+        staging.finish(|blocks| first.extend_from_slice(blocks));
+        let mut second = Vec::new();
+        staging.update(&[1; 200], |blocks| second.extend_from_slice(blocks));
+        staging.finish(|blocks| second.extend_from_slice(blocks));
+        assert_eq!(first, second);
+    }
+}
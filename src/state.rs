@@ -0,0 +1,92 @@
+//! The progress of a hasher as a plain struct, for suspending a long running hash and resuming it in another process.
+
+use crate::{Error, Sha256};
+
+/// The progress of a `Sha256`: its hash values, the number of bytes absorbed, the bytes of the partial chunk not yet processed and the IV it resets to.
+///
+/// With the `serde` feature it serializes as a struct of `h`, `len`, `buffered` (only the `len % 64` bytes in use) and `iv`, so it can be
+/// stored alongside other job state in JSON or bincode. `Sha256::export_state` is the fixed size binary equivalent.
+///
+/// ```
+/// use sha_256::{SavedState, Sha256};
+/// let mut sha256 = Sha256::new();
+/// sha256.update(b"the first ");
+/// let saved: SavedState = sha256.save_state();
+/// // ... persist, exit, restart, load ...
+/// let mut sha256 = Sha256::restore_state(&saved)?;
+/// sha256.update(b"half");
+/// assert_eq!(sha256.finalize(), sha_256::sha256(b"the first half"));
+/// # Ok::<(), sha_256::Error>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SavedState {
+    /// The 8 hash values after the whole chunks absorbed so far.
+    pub h: [u32; 8],
+    /// The number of bytes absorbed.
+    pub len: u64,
+    /// The bytes absorbed after the last whole chunk, the first `len % 64` are used and the rest must be 0.
+    pub buffer: [u8; 64],
+    /// The initial hash values the hasher started from and resets to, SHA-256's unless it was created with `Sha256::with_iv`.
+    pub iv: [u32; 8],
+}
+
+impl SavedState {
+    /// Returns the bytes absorbed after the last whole chunk.
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffer[..(self.len % 64) as usize]
+    }
+}
+
+impl Sha256 {
+    /// Saves the hasher's progress, see `SavedState`.
+    pub fn save_state(&self) -> SavedState {
+        let mut buffer = [0u8; 64];
+        buffer[..self.buf_len].copy_from_slice(&self.buf[..self.buf_len]);
+        SavedState { h: self.state(), len: self.len, buffer, iv: self.iv }
+    }
+
+    /// Recreates a hasher from progress saved with `save_state`.
+    ///
+    /// # Arguments
+    /// * `state` - The saved progress.
+    ///
+    /// # Returns
+    /// The hasher, `Error::Parse` if the unused part of the buffer is not all 0's, or `Error::LengthOverflow` if the length is over `Sha256::MAX_LEN`.
+    pub fn restore_state(state: &SavedState) -> Result<Self, Error> {
+        Sha256::check_len(state.len)?;
+        let buffered = state.buffered().len();
+        if state.buffer[buffered..].iter().any(|&b| b != 0) {
+            return Err(Error::Parse { reason: "non-zero padding in saved state" });
+        }
+        let mut sha256 = Sha256::from_state_with_iv(state.iv, state.h, state.len);
+        sha256.buf_len = buffered;
+        sha256.buf[..buffered].copy_from_slice(state.buffered());
+        Ok(sha256)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resumes_where_it_left_off() {
+        let msg: [u8; 300] = core::array::from_fn(|i| (i * 7) as u8);
+        for split in [0, 1, 63, 64, 65, 200, 300] {
+            let mut sha256 = Sha256::new();
+            sha256.update(&msg[..split]);
+            let saved = sha256.save_state();
+            assert_eq!(saved.len, split as u64);
+            assert_eq!(saved.buffered(), &msg[split / 64 * 64..split]);
+            let mut resumed = Sha256::restore_state(&saved).unwrap();
+            resumed.update(&msg[split..]);
+            assert_eq!(resumed.finalize(), Sha256::new().digest(&msg), "split {}", split);
+        }
+
+        let mut saved = Sha256::new().chain(b"abc").save_state();
+        saved.buffer[3] = 1;
+        assert!(matches!(Sha256::restore_state(&saved), Err(Error::Parse { .. })));
+        let saved = SavedState { len: Sha256::MAX_LEN + 1, ..Sha256::new().save_state() };
+        assert!(matches!(Sha256::restore_state(&saved), Err(Error::LengthOverflow)));
+    }
+}
@@ -0,0 +1,57 @@
+//! Hashing a `futures` `Stream` of byte chunks, e.g. the frames of a streaming HTTP or gRPC body, as they arrive.
+
+use core::future::poll_fn;
+use core::pin::pin;
+
+use futures_core::Stream;
+
+use crate::{Hash256, Sha256};
+
+/// Hashes each chunk of a stream as it arrives, without collecting the stream into memory.
+///
+/// ```
+/// # let stream = futures_util::stream::iter([Ok::<_, ()>(bytes::Bytes::from_static(b"hel")), Ok(bytes::Bytes::from_static(b"lo"))]);
+/// # let _ = async {
+/// let hash = sha_256::hash_stream(stream).await?;
+/// assert_eq!(hash, sha_256::Hash256::new(sha_256::sha256(b"hello")));
+/// # Ok::<_, ()>(())
+/// # };
+/// ```
+///
+/// # Arguments
+/// * `stream` - The stream to be hashed, its items can be any byte container such as `Bytes` or `Vec<u8>`.
+///
+/// # Returns
+/// The hash of the concatenated chunks, or the first error returned by the stream, at which point hashing stops.
+pub async fn hash_stream<S, B, E>(stream: S) -> Result<Hash256, E>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+{
+    let mut stream = pin!(stream);
+    let mut sha256 = Sha256::new();
+    while let Some(chunk) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+        sha256.update(chunk?.as_ref());
+    }
+    Ok(Hash256::new(sha256.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::tests::block_on;
+    use std::vec::Vec;
+
+    #[test]
+    fn hashes_chunks_until_an_error() {
+        let msg: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+        let chunks = msg.chunks(77).map(|c| Ok::<_, &str>(c.to_vec()));
+        let expected = Hash256::new(Sha256::new().digest(&msg));
+        assert_eq!(block_on(hash_stream(futures_util::stream::iter(chunks))), Ok(expected));
+
+        let chunks = [Ok(&b"frame"[..]), Err("reset"), Ok(b"never read")];
+        assert_eq!(block_on(hash_stream(futures_util::stream::iter(chunks))), Err("reset"));
+    }
+}
@@ -0,0 +1,55 @@
+//! Hashing formatted text without allocating a `String` for it first.
+
+use core::fmt;
+
+use crate::Sha256;
+
+/// Feeds text written with `write!` straight into a hasher, for hashing structured text like cache keys or canonical request lines.
+///
+/// The text is hashed as its UTF-8 bytes, the same as hashing the `String` `format!` would have built. An adapter rather than
+/// a `fmt::Write` impl on `Sha256` itself, as that would make `write!` on a hasher ambiguous wherever `io::Write` is also in scope.
+///
+/// ```
+/// use core::fmt::Write;
+/// use sha_256::{FmtHasher, Sha256};
+/// let mut sha256 = Sha256::new();
+/// write!(FmtHasher::new(&mut sha256), "{}:{}", "user", 42).unwrap();
+/// assert_eq!(sha256.finalize(), Sha256::new().digest(b"user:42"));
+/// ```
+pub struct FmtHasher<'a> {
+    sha256: &'a mut Sha256,
+}
+
+impl<'a> FmtHasher<'a> {
+    /// Wraps a hasher, which can be in the middle of a message.
+    ///
+    /// # Arguments
+    /// * `sha256` - The hasher the text is absorbed into.
+    pub fn new(sha256: &'a mut Sha256) -> Self {
+        Self { sha256 }
+    }
+}
+
+impl fmt::Write for FmtHasher<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.sha256.update(s.as_bytes());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn hashes_the_formatted_text() {
+        let mut sha256 = Sha256::new();
+        sha256.update(b"GET ");
+        let path = "items";
+        let mut writer = FmtHasher::new(&mut sha256);
+        write!(writer, "/{}/{:04}?q={:?}", path, 7, 'é').unwrap();
+        writer.write_char('\n').unwrap();
+        assert_eq!(sha256.finalize(), Sha256::new().digest("GET /items/0007?q='é'\n".as_bytes()));
+    }
+}
@@ -0,0 +1,177 @@
+//! A dudect style harness for checking whether an operation's run time depends on secret inputs.
+//!
+//! Following "dude, is my code constant time?" (Reparaz, Balasch and Verbauwhede, 2017): inputs are drawn from two classes, usually one
+//! fixed value and random values, the operation is timed on a random interleaving of both, and Welch's t-test compares the two distributions
+//! of timings. A large |t| means the timings differ between the classes, i.e. the run time leaks something about the input.
+//! Measurements are also repeated with the slowest ones discarded, as interrupts and cache misses only ever add time.
+//!
+//! Timings are only meaningful on the hardware and build being checked, so run this there, in release mode, e.g. with
+//! `cargo test --release --features timing-tests -- --ignored timing`. A pass is evidence, not proof, more samples find smaller leaks.
+
+use core::hint::black_box;
+use std::time::Instant;
+use std::vec::Vec;
+
+use crate::{HmacSha256, Sha256};
+
+/// |t| above which the timings are considered to leak, the threshold used by dudect.
+pub const LEAK_THRESHOLD: f64 = 4.5;
+
+/// The result of a timing test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LeakReport {
+    /// The largest |t| over the uncropped and cropped measurements.
+    pub t: f64,
+    /// The number of timings taken.
+    pub samples: usize,
+}
+
+impl LeakReport {
+    /// Returns whether |t| is above `LEAK_THRESHOLD`.
+    pub fn leaks(&self) -> bool {
+        self.t > LEAK_THRESHOLD
+    }
+}
+
+/// Welch's t statistic for two sets of measurements.
+///
+/// # Arguments
+/// * `a` - The measurements of one class, at least 2.
+/// * `b` - The measurements of the other class, at least 2.
+///
+/// # Returns
+/// t, positive if `a` has the greater mean. 0 if either class has too few measurements or both have no variance.
+pub fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+    let stats = |xs: &[f64]| {
+        let n = xs.len() as f64;
+        let mean = xs.iter().sum::<f64>() / n;
+        let var = xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (n - 1.0);
+        (mean, var / n)
+    };
+    let (mean_a, se_a) = stats(a);
+    let (mean_b, se_b) = stats(b);
+    if se_a + se_b == 0.0 {
+        return 0.0;
+    }
+    (mean_a - mean_b) / (se_a + se_b).sqrt()
+}
+
+/// Times an operation on inputs from two classes and tests whether the timings differ.
+///
+/// # Arguments
+/// * `samples` - How many timings to take, split randomly between the classes. Tens of thousands or more for small leaks.
+/// * `input` - Makes an input of the given class (`false` for the first, `true` for the second) from a random `u64`. Not timed.
+/// * `op` - The operation being checked.
+///
+/// # Returns
+/// The largest |t| seen, see `LeakReport`.
+pub fn measure<I, G: FnMut(bool, u64) -> I, F: FnMut(&I)>(samples: usize, mut input: G, mut op: F) -> LeakReport {
+    let mut rng = Rng::new();
+    let classes: Vec<bool> = (0..samples).map(|_| rng.next() & 1 == 1).collect();
+    let inputs: Vec<I> = classes.iter().map(|&class| input(class, rng.next())).collect();
+    let mut timings = Vec::with_capacity(samples);
+    for input in &inputs {
+        let start = Instant::now();
+        op(black_box(input));
+        timings.push(start.elapsed().as_nanos() as f64);
+    }
+
+    let mut sorted = timings.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mut t = 0.0f64;
+    // all of the timings, then only those under a few percentiles
+    for percentile in [1.0, 0.99, 0.9, 0.5] {
+        let cutoff = sorted.get(((samples as f64 * percentile) as usize).min(samples.saturating_sub(1))).copied().unwrap_or(0.0);
+        let (mut a, mut b) = (Vec::new(), Vec::new());
+        for (&class, &timing) in classes.iter().zip(&timings) {
+            if timing <= cutoff {
+                if class { b.push(timing) } else { a.push(timing) }
+            }
+        }
+        t = t.max(welch_t(&a, &b).abs());
+    }
+    LeakReport { t, samples }
+}
+
+/// Checks whether `Sha256::digest` takes longer for some 64 byte messages than others, comparing all zero messages with random ones.
+///
+/// # Arguments
+/// * `samples` - How many timings to take.
+pub fn digest_leakage(samples: usize) -> LeakReport {
+    let mut sha256 = Sha256::new();
+    measure(samples, |class, random| if class { random_bytes::<64>(random) } else { [0; 64] }, |msg| {
+        black_box(sha256.digest(msg));
+    })
+}
+
+/// Checks whether `HmacSha256::verify` takes longer depending on how much of the tag is right, comparing the correct tag with random ones.
+///
+/// An early exit on the first differing byte would show up as the correct tags taking longer.
+///
+/// # Arguments
+/// * `samples` - How many timings to take.
+pub fn hmac_verify_leakage(samples: usize) -> LeakReport {
+    let hmac = HmacSha256::new(b"timing test key");
+    let mut correct = hmac.clone();
+    correct.update(b"message");
+    let correct = correct.finalize();
+    measure(samples, |class, random| if class { random_bytes::<32>(random) } else { correct }, |tag| {
+        let mut hmac = hmac.clone();
+        hmac.update(b"message");
+        black_box(hmac.verify(tag));
+    })
+}
+
+fn random_bytes<const N: usize>(seed: u64) -> [u8; N] {
+    let mut rng = Rng(seed | 1);
+    core::array::from_fn(|_| rng.next() as u8)
+}
+
+/// xorshift64, only used to pick the classes and inputs so it needn't be cryptographic.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        let seed = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0);
+        Self(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn welch_t_and_detection() {
+        assert_eq!(welch_t(&[1.0, 2.0, 3.0], &[1.0, 2.0, 3.0]), 0.0);
+        // means 2 and 4, variances 1 and 1, 3 samples each: 2 / sqrt(1/3 + 1/3)
+        assert!((welch_t(&[1.0, 2.0, 3.0], &[3.0, 4.0, 5.0]) + 2.0 / (2.0f64 / 3.0).sqrt()).abs() < 1e-9);
+        // an operation doing far more work for one class is caught
+        let report = measure(2000, |class, _| if class { 20_000u32 } else { 0 }, |&n| {
+            for i in 0..n {
+                black_box(i);
+            }
+        });
+        assert!(report.leaks(), "{:?}", report);
+    }
+
+    // run on the hardware being checked, see the module docs
+    #[test]
+    #[ignore]
+    fn timing() {
+        let digest = digest_leakage(200_000);
+        let verify = hmac_verify_leakage(200_000);
+        assert!(!digest.leaks(), "digest {:?}", digest);
+        assert!(!verify.leaks(), "verify {:?}", verify);
+    }
+}
@@ -0,0 +1,157 @@
+//! Tower middleware which hashes request bodies, for services that sign or audit payloads.
+//!
+//! ```ignore
+//! let service = ServiceBuilder::new()
+//!     .layer(Sha256Layer::new().max_body_size(16 * 1024 * 1024))
+//!     .service(handler);
+//! ```
+//! The handler receives the buffered body along with a `ContentSha256` extension and an `x-content-sha256` header holding the lowercase hex digest.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+use std::boxed::Box;
+
+use bytes::Bytes;
+use http::header::{HeaderName, HeaderValue};
+use http::Request;
+use http_body::Body;
+use http_body_util::Full;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::http::collect_and_hash;
+use crate::{hex, Hash256};
+
+/// The header the digest is written to.
+pub const CONTENT_SHA256_HEADER: HeaderName = HeaderName::from_static("x-content-sha256");
+
+/// The type erased error returned by `Sha256Service`.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Request extension holding the hash of the request body.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ContentSha256(pub Hash256);
+
+/// The body size limit of a new `Sha256Layer`, 2MiB as in axum.
+pub const DEFAULT_MAX_BODY_SIZE: u64 = 2 * 1024 * 1024;
+
+/// Layer applying `Sha256Service`.
+#[derive(Clone, Copy, Debug)]
+pub struct Sha256Layer {
+    max_body_size: Option<u64>,
+}
+
+impl Default for Sha256Layer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sha256Layer {
+    /// Creates a layer which rejects bodies larger than `DEFAULT_MAX_BODY_SIZE`, so a client can't make the service buffer without limit.
+    pub fn new() -> Self {
+        Self { max_body_size: Some(DEFAULT_MAX_BODY_SIZE) }
+    }
+
+    /// Rejects requests with bodies larger than the given number of bytes, rather than buffering them.
+    ///
+    /// # Arguments
+    /// * `limit` - The maximum body size in bytes.
+    pub fn max_body_size(mut self, limit: u64) -> Self {
+        self.max_body_size = Some(limit);
+        self
+    }
+
+    /// Accepts bodies of any size. Only for services whose bodies are already limited, e.g. by a proxy in front, as every body is buffered in memory.
+    pub fn unlimited(mut self) -> Self {
+        self.max_body_size = None;
+        self
+    }
+}
+
+impl<S> Layer<S> for Sha256Layer {
+    type Service = Sha256Service<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Sha256Service { inner, max_body_size: self.max_body_size }
+    }
+}
+
+/// Middleware which buffers the request body, hashes it and passes the digest on to the inner service.
+#[derive(Clone, Debug)]
+pub struct Sha256Service<S> {
+    inner: S,
+    max_body_size: Option<u64>,
+}
+
+impl<S, B> Service<Request<B>> for Sha256Service<S>
+where
+    S: Service<Request<Full<Bytes>>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: std::error::Error + Send + Sync + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request<B>) -> Self::Future {
+        // the clone may not be ready, so keep the one poll_ready was called on
+        let clone = self.inner.clone();
+        let mut inner = core::mem::replace(&mut self.inner, clone);
+        let max_body_size = self.max_body_size;
+
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            let (data, hash) = collect_and_hash(body, max_body_size).await?;
+            let hex = hex::encode(hash.as_bytes());
+            parts.headers.insert(CONTENT_SHA256_HEADER, HeaderValue::from_bytes(&hex)?);
+            parts.extensions.insert(ContentSha256(hash));
+            inner.call(Request::from_parts(parts, Full::new(data))).await.map_err(Into::into)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::block_on;
+    use crate::Sha256;
+    use tower::{service_fn, ServiceExt};
+
+    #[test]
+    fn injects_digest() {
+        let handler = service_fn(|request: Request<Full<Bytes>>| async move {
+            let hash = request.extensions().get::<ContentSha256>().unwrap().0;
+            let header = request.headers()[&CONTENT_SHA256_HEADER].clone();
+            Ok::<_, core::convert::Infallible>((hash, header))
+        });
+        let service = Sha256Layer::new().layer(handler);
+        let request = Request::new(Full::new(Bytes::from_static(b"hello")));
+        let (hash, header) = block_on(service.oneshot(request)).unwrap();
+        assert_eq!(hash, Hash256::new(Sha256::new().digest(b"hello")));
+        assert_eq!(header, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+    }
+
+    #[test]
+    fn rejects_large_bodies() {
+        let handler = service_fn(|_: Request<Full<Bytes>>| async { Ok::<_, core::convert::Infallible>(()) });
+        let service = Sha256Layer::new().max_body_size(4).layer(handler);
+        let request = Request::new(Full::new(Bytes::from_static(b"hello")));
+        assert!(block_on(service.oneshot(request)).is_err());
+
+        // limited by default, unlimited only on request
+        let big = Bytes::from(std::vec![0u8; DEFAULT_MAX_BODY_SIZE as usize + 1]);
+        let service = Sha256Layer::new().layer(handler);
+        assert!(block_on(service.oneshot(Request::new(Full::new(big.clone())))).is_err());
+        let service = Sha256Layer::new().unlimited().layer(handler);
+        assert!(block_on(service.oneshot(Request::new(Full::new(big)))).is_ok());
+    }
+}
@@ -0,0 +1,160 @@
+//! Tree hashing, so a single large buffer can be hashed on every core.
+//!
+//! SHA-256 is inherently serial, so this is a *different hash* from plain SHA-256 and the two are not interchangeable. It is defined as:
+//! * the input is split into chunks of `TREE_CHUNK_SIZE` bytes, the last chunk may be shorter. An empty input is a single empty chunk.
+//! * each chunk is a leaf: `leaf = SHA-256(0x00 || chunk)`.
+//! * leaves are combined with internal nodes: `node = SHA-256(0x01 || left || right)`.
+//! * the tree shape is that of RFC 6962 (Certificate Transparency): for `n > 1` leaves, the left subtree holds the largest power of two of leaves less than `n` and the right subtree the rest.
+//!
+//! The `0x00`/`0x01` domain tags stop a leaf ever being mistaken for an internal node and vice versa.
+
+use crate::{Hash256, Sha256};
+
+/// The size of each leaf chunk in tree hashing, 1MiB.
+pub const TREE_CHUNK_SIZE: usize = 1 << 20;
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+pub(crate) fn leaf(chunk: &[u8]) -> [u8; 32] {
+    let mut sha256 = Sha256::new();
+    sha256.update(&[LEAF_TAG]);
+    sha256.update(chunk);
+    sha256.finalize()
+}
+
+pub(crate) fn node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut sha256 = Sha256::new();
+    sha256.update(&[NODE_TAG]);
+    sha256.update(left);
+    sha256.update(right);
+    sha256.finalize()
+}
+
+/// Folds leaf hashes, in order, into the root of the tree.
+fn root<I: Iterator<Item = [u8; 32]>>(leaves: I) -> [u8; 32] {
+    // stack of complete subtrees (hash, number of leaves), their sizes are strictly decreasing powers of two
+    let mut stack = [([0u8; 32], 0u64); 64];
+    let mut depth = 0;
+    for leaf in leaves {
+        let mut hash = leaf;
+        let mut size = 1;
+        while depth > 0 && stack[depth - 1].1 == size {
+            depth -= 1;
+            hash = node(&stack[depth].0, &hash);
+            size *= 2;
+        }
+        stack[depth] = (hash, size);
+        depth += 1;
+    }
+    // incomplete subtrees on the right are joined from the smallest up
+    let mut hash = stack[depth - 1].0;
+    for (left, _) in stack[..depth - 1].iter().rev() {
+        hash = node(left, &hash);
+    }
+    hash
+}
+
+pub(crate) fn tree_hash_with(data: &[u8], chunk_size: usize) -> Hash256 {
+    if data.is_empty() {
+        return Hash256::new(leaf(&[]));
+    }
+    Hash256::new(root(data.chunks(chunk_size).map(leaf)))
+}
+
+/// Computes the tree hash (see the module docs) of a buffer on the current thread.
+///
+/// # Arguments
+/// * `data` - The buffer to be hashed.
+///
+/// # Returns
+/// The root of the tree. This is not the SHA-256 hash of `data`.
+pub fn tree_hash(data: &[u8]) -> Hash256 {
+    tree_hash_with(data, TREE_CHUNK_SIZE)
+}
+
+#[cfg(feature = "std")]
+fn tree_hash_parallel_with(data: &[u8], chunk_size: usize) -> Hash256 {
+    use std::thread;
+    use std::vec;
+
+    if data.len() <= chunk_size {
+        return tree_hash_with(data, chunk_size);
+    }
+    let chunks = data.len().div_ceil(chunk_size);
+    let threads = thread::available_parallelism().map_or(1, |n| n.get()).min(chunks);
+    // give each thread a contiguous run of whole chunks
+    let per_thread = chunks.div_ceil(threads);
+    let mut leaves = vec![[0u8; 32]; chunks];
+    thread::scope(|scope| {
+        for (data, leaves) in data.chunks(per_thread * chunk_size).zip(leaves.chunks_mut(per_thread)) {
+            scope.spawn(move || {
+                for (chunk, leaf_hash) in data.chunks(chunk_size).zip(leaves.iter_mut()) {
+                    *leaf_hash = leaf(chunk);
+                }
+            });
+        }
+    });
+    Hash256::new(root(leaves.into_iter()))
+}
+
+/// Computes the tree hash (see the module docs) of a buffer, hashing the leaves on all available cores.
+///
+/// # Arguments
+/// * `data` - The buffer to be hashed.
+///
+/// # Returns
+/// The root of the tree, identical to `tree_hash(data)`. This is not the SHA-256 hash of `data`.
+#[cfg(feature = "std")]
+pub fn tree_hash_parallel(data: &[u8]) -> Hash256 {
+    tree_hash_parallel_with(data, TREE_CHUNK_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn small_trees() {
+        let a = b"a";
+        let b = b"b";
+        let c = b"c";
+        assert_eq!(tree_hash_with(b"", 1), Hash256::new(leaf(b"")));
+        assert_eq!(tree_hash_with(b"a", 1), Hash256::new(leaf(a)));
+        assert_eq!(tree_hash_with(b"ab", 1), Hash256::new(node(&leaf(a), &leaf(b))));
+        // three leaves split 2 + 1
+        assert_eq!(tree_hash_with(b"abc", 1), Hash256::new(node(&node(&leaf(a), &leaf(b)), &leaf(c))));
+        // a single chunk is not plain SHA-256
+        assert_ne!(tree_hash(b"abc"), Hash256::new(Sha256::new().digest(b"abc")));
+    }
+
+    // the recursive definition from RFC 6962
+    fn reference(leaves: &[[u8; 32]]) -> [u8; 32] {
+        if leaves.len() == 1 {
+            return leaves[0];
+        }
+        let k = leaves.len().next_power_of_two() / 2;
+        node(&reference(&leaves[..k]), &reference(&leaves[k..]))
+    }
+
+    #[test]
+    fn matches_recursive_definition() {
+        let data: Vec<u8> = (0..40u8).collect();
+        for len in 1..=data.len() {
+            let leaves: Vec<[u8; 32]> = data[..len].chunks(1).map(leaf).collect();
+            assert_eq!(tree_hash_with(&data[..len], 1), Hash256::new(reference(&leaves)), "len {}", len);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn parallel_matches_serial() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i % 251) as u8).collect();
+        for len in [0, 1, 63, 64, 65, 640, 1000, 4097, 10_000] {
+            assert_eq!(tree_hash_parallel_with(&data[..len], 64), tree_hash_with(&data[..len], 64), "len {}", len);
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! File hashing on Linux using io_uring, keeping several reads in flight so the device queue stays busy while the CPU hashes.
+
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::vec;
+use std::vec::Vec;
+
+use io_uring::{opcode, types, IoUring};
+
+use crate::{hash_file, hash_file_with, FileHashOptions, Hash256, Sha256};
+
+/// Number of reads kept in flight at once, unless set by `FileHashOptions::read_ahead`.
+const QUEUE_DEPTH: usize = 8;
+/// Size of each read, unless set by `FileHashOptions::chunk_size`.
+const CHUNK_SIZE: usize = 128 * 1024;
+
+/// A buffer and the region of the file it is being filled from.
+struct Slot {
+    buf: Vec<u8>,
+    offset: u64,
+    len: usize,
+    filled: usize,
+    busy: bool, // a read into `buf` has been submitted and not yet completed
+    done: bool, // `buf[..filled]` holds the final contents of the region
+}
+
+/// Hashes the contents of a file, reading it through io_uring with up to 8 reads of 128KiB in flight.
+///
+/// Reads complete out of order but are hashed in file order, so the result is identical to `hash_file`.
+/// The file's length is taken when it is opened, data appended while hashing is not included.
+/// Falls back to `hash_file` if io_uring is not available (older kernels, or disabled by seccomp/sysctl).
+///
+/// # Arguments
+/// * `path` - The file to be hashed.
+///
+/// # Returns
+/// The hash of the file's contents, or the first IO error encountered.
+pub fn hash_file_uring<P: AsRef<Path>>(path: P) -> io::Result<Hash256> {
+    let path = path.as_ref();
+    match hash(path, FileHashOptions::new().chunk_size(CHUNK_SIZE).read_ahead(QUEUE_DEPTH))? {
+        Some(hash) => Ok(hash),
+        None => hash_file(path),
+    }
+}
+
+/// Hashes the contents of a file through io_uring like `hash_file_uring`, with the size and number of reads in flight set by `options`.
+///
+/// The chunk size is picked as for `hash_file_with` if not set, and the number of reads in flight is `options`' read ahead, at least 1
+/// and 8 if not set. `FileHashOptions::direct` is ignored. Falls back to `hash_file_with` if io_uring is not available.
+///
+/// # Arguments
+/// * `path` - The file to be hashed.
+/// * `options` - How the file is read.
+///
+/// # Returns
+/// The hash of the file's contents, or the first IO error encountered.
+pub fn hash_file_uring_with<P: AsRef<Path>>(path: P, options: FileHashOptions) -> io::Result<Hash256> {
+    let path = path.as_ref();
+    match hash(path, options)? {
+        Some(hash) => Ok(hash),
+        None => hash_file_with(path, options),
+    }
+}
+
+/// Hashes a file through io_uring, `None` if io_uring is not available.
+fn hash(path: &Path, options: FileHashOptions) -> io::Result<Option<Hash256>> {
+    let file = File::open(path)?;
+    let metadata = file.metadata()?;
+    let size = metadata.len();
+    let chunk_size = options.resolve_chunk_size(&metadata);
+    let depth = options.read_ahead_or(QUEUE_DEPTH).max(1);
+    let mut ring = match IoUring::new(depth as u32) {
+        Ok(ring) => ring,
+        Err(e) if matches!(e.kind(), io::ErrorKind::Unsupported | io::ErrorKind::PermissionDenied) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mut slots: Vec<Slot> = (0..depth).map(|_| Slot { buf: vec![0u8; chunk_size], offset: 0, len: 0, filled: 0, busy: false, done: false }).collect();
+    let mut uring = Uring { ring: &mut ring, fd: types::Fd(file.as_raw_fd()), slots: &mut slots, in_flight: 0, chunk_size };
+    let result = uring.hash(size);
+    // the kernel may still be writing into the buffers, so they must outlive every submitted read
+    let drained = uring.drain();
+    result.and_then(|hash| drained.map(|_| Some(hash)))
+}
+
+struct Uring<'a> {
+    ring: &'a mut IoUring,
+    fd: types::Fd,
+    slots: &'a mut [Slot],
+    in_flight: usize,
+    chunk_size: usize,
+}
+
+impl Uring<'_> {
+    fn hash(&mut self, size: u64) -> io::Result<Hash256> {
+        let mut sha256 = Sha256::new();
+        let mut next_offset = 0u64;
+        // slots are handed regions round robin, so visiting them in the same order hashes the file in order
+        for i in 0..self.slots.len() {
+            if next_offset >= size {
+                break;
+            }
+            next_offset = self.start(i, next_offset, size)?;
+        }
+        let mut current = 0;
+        while self.slots[current].len > 0 {
+            while !self.slots[current].done {
+                self.complete_one()?;
+            }
+            let slot = &mut self.slots[current];
+            sha256.update(&slot.buf[..slot.filled]);
+            slot.len = 0;
+            if next_offset < size {
+                next_offset = self.start(current, next_offset, size)?;
+            }
+            current = (current + 1) % self.slots.len();
+        }
+        Ok(Hash256::new(sha256.finalize()))
+    }
+
+    /// Assigns the region starting at `offset` to a slot and submits a read for it, returning the offset of the following region.
+    fn start(&mut self, index: usize, offset: u64, size: u64) -> io::Result<u64> {
+        let slot = &mut self.slots[index];
+        slot.offset = offset;
+        slot.len = (size - offset).min(self.chunk_size as u64) as usize;
+        slot.filled = 0;
+        slot.done = false;
+        self.submit(index)?;
+        Ok(offset + self.slots[index].len as u64)
+    }
+
+    /// Submits a read for the unfilled remainder of a slot's region.
+    fn submit(&mut self, index: usize) -> io::Result<()> {
+        let slot = &mut self.slots[index];
+        let remaining = &mut slot.buf[slot.filled..slot.len];
+        let entry = opcode::Read::new(self.fd, remaining.as_mut_ptr(), remaining.len() as u32)
+            .offset(slot.offset + slot.filled as u64)
+            .build()
+            .user_data(index as u64);
+        // SAFETY: the buffer is owned by the slot, which is not touched until the read completes (see `drain`)
+        unsafe {
+            // never more entries than slots, so the queue cannot be full
+            self.ring.submission().push(&entry).expect("submission queue is full");
+        }
+        slot.busy = true;
+        self.in_flight += 1;
+        self.ring.submit()?;
+        Ok(())
+    }
+
+    /// Waits for a read to complete, resubmitting it if it came back short.
+    fn complete_one(&mut self) -> io::Result<()> {
+        let (index, result) = loop {
+            if let Some(cqe) = self.ring.completion().next() {
+                break (cqe.user_data() as usize, cqe.result());
+            }
+            match self.ring.submit_and_wait(1) {
+                Ok(_) => {}
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        };
+        self.in_flight -= 1;
+        let slot = &mut self.slots[index];
+        slot.busy = false;
+        if result < 0 {
+            let e = io::Error::from_raw_os_error(-result);
+            return match e.kind() {
+                io::ErrorKind::Interrupted | io::ErrorKind::WouldBlock => self.submit(index),
+                _ => Err(e),
+            };
+        }
+        slot.filled += result as usize;
+        if result == 0 || slot.filled == slot.len {
+            // a read of 0 means the file was truncated while hashing, hash whatever is left
+            slot.done = true;
+            Ok(())
+        } else {
+            self.submit(index)
+        }
+    }
+
+    /// Waits for every submitted read to complete, so the buffers can be freed.
+    fn drain(&mut self) -> io::Result<()> {
+        while self.in_flight > 0 {
+            if let Some(cqe) = self.ring.completion().next() {
+                self.slots[cqe.user_data() as usize].busy = false;
+                self.in_flight -= 1;
+                continue;
+            }
+            if let Err(e) = self.ring.submit_and_wait(1) {
+                if e.kind() != io::ErrorKind::Interrupted {
+                    // nothing sensible can be done if the ring is broken, the buffers are leaked rather than freed under the kernel
+                    for slot in self.slots.iter_mut().filter(|slot| slot.busy) {
+                        std::mem::forget(std::mem::take(&mut slot.buf));
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_hash_file() {
+        let path = std::env::temp_dir().join(std::format!("sha_256-uring-{}", std::process::id()));
+        // cover empty, sub-chunk, exactly one chunk, and more chunks than the queue depth with a partial last chunk
+        for len in [0, 1000, CHUNK_SIZE, CHUNK_SIZE * QUEUE_DEPTH * 2 + 12345] {
+            let msg: Vec<u8> = (0..len).map(|i| (i * 31 % 251) as u8).collect();
+            std::fs::write(&path, &msg).unwrap();
+            let hash = hash_file_uring(&path);
+            assert_eq!(hash.unwrap(), Hash256::new(Sha256::new().digest(&msg)), "len {}", len);
+            let hash = hash_file_uring_with(&path, FileHashOptions::new().chunk_size(4000).read_ahead(3));
+            assert_eq!(hash.unwrap(), Hash256::new(Sha256::new().digest(&msg)), "len {} with options", len);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,129 @@
+//! JS/TS bindings via wasm-bindgen.
+//!
+//! ```js
+//! import { sha256, sha256Hex, Sha256 } from "sha_256";
+//!
+//! sha256Hex(new TextEncoder().encode("hello"));
+//! const hasher = new Sha256();
+//! hasher.update(chunk1);
+//! hasher.update(chunk2);
+//! hasher.hexDigest();
+//!
+//! // a File from <input type="file">, read a slice at a time
+//! await sha256BlobHex(input.files[0]);
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use js_sys::{ArrayBuffer, Uint8Array};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::Blob;
+
+use crate::{Hash256, Sha256};
+
+/// Hashes the data in one go, returning the 32 byte digest.
+#[wasm_bindgen]
+pub fn sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::new().digest(data).to_vec()
+}
+
+/// Hashes the data in one go, returning the digest as lowercase hex.
+#[wasm_bindgen(js_name = sha256Hex)]
+pub fn sha256_hex(data: &[u8]) -> String {
+    Hash256::new(Sha256::new().digest(data)).to_string()
+}
+
+/// How much of a `Blob` is read into memory at a time.
+const BLOB_CHUNK_SIZE: usize = 1 << 20;
+
+/// Hashes a `Blob` (or `File`, which is a `Blob`) a slice at a time, so large files are never in memory all at once.
+///
+/// Resolves to the 32 byte digest, or rejects with the browser's error if the blob can't be read (e.g. the file was changed).
+#[wasm_bindgen(js_name = sha256Blob)]
+pub async fn sha256_blob(blob: Blob) -> Result<Vec<u8>, JsValue> {
+    hash_blob(&blob).await.map(|hash| hash.to_vec())
+}
+
+/// Hashes a `Blob` (or `File`) like `sha256Blob`, resolving to the digest as lowercase hex.
+#[wasm_bindgen(js_name = sha256BlobHex)]
+pub async fn sha256_blob_hex(blob: Blob) -> Result<String, JsValue> {
+    hash_blob(&blob).await.map(|hash| Hash256::new(hash).to_string())
+}
+
+async fn hash_blob(blob: &Blob) -> Result<[u8; 32], JsValue> {
+    let mut sha256 = Sha256::new();
+    // copied out of JS into one reused buffer, rather than a new allocation per slice
+    let mut buf = vec![0u8; BLOB_CHUNK_SIZE];
+    // blob sizes are doubles in JS
+    let size = blob.size();
+    let mut offset = 0.0;
+    while offset < size {
+        let end = (offset + BLOB_CHUNK_SIZE as f64).min(size);
+        let slice = blob.slice_with_f64_and_f64(offset, end)?;
+        let bytes = Uint8Array::new(&JsFuture::from(slice.array_buffer()).await?.unchecked_into::<ArrayBuffer>());
+        let n = bytes.length() as usize;
+        bytes.copy_to(&mut buf[..n]);
+        sha256.update(&buf[..n]);
+        offset = end;
+    }
+    Ok(sha256.finalize())
+}
+
+/// A streaming hasher for data arriving in chunks.
+#[wasm_bindgen(js_name = Sha256)]
+pub struct WasmSha256 {
+    inner: Sha256,
+}
+
+impl Default for WasmSha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[wasm_bindgen(js_class = Sha256)]
+impl WasmSha256 {
+    /// Creates a new streaming hasher.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self { inner: Sha256::new() }
+    }
+
+    /// Absorbs the next chunk of data.
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    /// Returns the 32 byte digest of the data absorbed so far and resets the hasher.
+    pub fn digest(&mut self) -> Vec<u8> {
+        self.inner.finalize_reset().to_vec()
+    }
+
+    /// Returns the digest of the data absorbed so far as lowercase hex and resets the hasher.
+    #[wasm_bindgen(js_name = hexDigest)]
+    pub fn hex_digest(&mut self) -> String {
+        Hash256::new(self.inner.finalize_reset()).to_string()
+    }
+
+    /// Discards the data absorbed so far.
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let mut hasher = WasmSha256::new();
+        hasher.update(b"hel");
+        hasher.update(b"lo");
+        assert_eq!(hasher.hex_digest(), "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+        assert_eq!(hasher.digest(), sha256(b""));
+        assert_eq!(sha256_hex(b"hello"), "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824");
+    }
+}
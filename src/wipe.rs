@@ -0,0 +1,82 @@
+//! Overwriting secrets once they are no longer needed, so they don't linger in freed memory.
+
+#[cfg(feature = "alloc")]
+use core::mem::MaybeUninit;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::Hash256;
+use crate::Sha256;
+
+/// Overwrites secret values with 0's in a way the compiler can't remove as a dead store.
+pub(crate) fn zeroize<T: Copy + Default>(values: &mut [T]) {
+    for value in values.iter_mut() {
+        // SAFETY: the pointer comes from a mutable reference, so is valid and aligned
+        unsafe { core::ptr::write_volatile(value, T::default()) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+/// Wipes the copies of the message a hasher keeps, its buffered bytes and the message schedule derived from the last block.
+pub(crate) fn zeroize_hasher(sha256: &mut Sha256) {
+    zeroize(&mut sha256.w);
+    zeroize(&mut sha256.buf);
+}
+
+/// Wipes everything a hasher keeps, the hash values too, for hashers started from a secret state such as HMAC's keyed ones.
+pub(crate) fn zeroize_keyed_hasher(sha256: &mut Sha256) {
+    zeroize_hasher(sha256);
+    for h in [&mut sha256.h0, &mut sha256.h1, &mut sha256.h2, &mut sha256.h3, &mut sha256.h4, &mut sha256.h5, &mut sha256.h6, &mut sha256.h7] {
+        zeroize(core::slice::from_mut(h));
+    }
+}
+
+/// Hashes a buffer then overwrites it with 0's before freeing it, so secret plaintext passed in by value doesn't linger after hashing.
+///
+/// The whole allocation is wiped, including any spare capacity, which may hold earlier contents from before the `Vec` was truncated.
+/// Copies made before the buffer was passed in (e.g. when a `Vec` grew and moved) are out of reach.
+///
+/// # Arguments
+/// * `buffer` - The message, consumed.
+///
+/// # Returns
+/// The hash of the message.
+#[cfg(feature = "alloc")]
+pub fn digest_consuming(mut buffer: Vec<u8>) -> Hash256 {
+    let mut sha256 = Sha256::new();
+    sha256.update(&buffer);
+    let hash = Hash256::new(sha256.finalize_reset());
+    zeroize_hasher(&mut sha256);
+    zeroize(&mut buffer);
+    // the spare capacity may never have been written, so it's wiped as uninitialized memory rather than as a slice of bytes
+    for byte in buffer.spare_capacity_mut() {
+        // SAFETY: the pointer comes from a mutable reference, so is valid and aligned
+        unsafe { core::ptr::write_volatile(byte, MaybeUninit::new(0)) };
+    }
+    compiler_fence(Ordering::SeqCst);
+    hash
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_consuming_matches_digest() {
+        let mut buffer = alloc::vec![0xabu8; 1000];
+        buffer.truncate(100);
+        assert_eq!(digest_consuming(buffer), Hash256::new(Sha256::new().digest(&[0xab; 100])));
+        // spare capacity which was never initialized
+        let mut buffer = Vec::with_capacity(1000);
+        buffer.extend_from_slice(b"secret");
+        assert_eq!(digest_consuming(buffer), Hash256::new(Sha256::new().digest(b"secret")));
+
+        let mut sha256 = Sha256::new();
+        sha256.update(b"secret");
+        zeroize_hasher(&mut sha256);
+        assert_eq!(sha256.buf, [0; 64]);
+    }
+}
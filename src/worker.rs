@@ -0,0 +1,43 @@
+//! Hashing on a background thread.
+
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::{Hash256, Sha256};
+
+/// Starts a thread which hashes the chunks sent to it, so an IO bound producer can overlap reading with hashing.
+///
+/// Chunks can be anything which derefs to bytes, e.g. `Vec<u8>` or `bytes::Bytes`. The message is the chunks in the order they were sent.
+/// Drop the sender (and any clones of it) to mark the end of the message, then join the handle for the hash.
+///
+/// # Returns
+/// The sending half of the channel to the worker and the worker's handle.
+pub fn spawn_hasher<T: AsRef<[u8]> + Send + 'static>() -> (Sender<T>, JoinHandle<Hash256>) {
+    let (sender, receiver) = mpsc::channel::<T>();
+    let handle = thread::spawn(move || {
+        let mut sha256 = Sha256::new();
+        // ends once every sender has been dropped
+        for chunk in receiver {
+            sha256.update(chunk.as_ref());
+        }
+        Hash256::new(sha256.finalize())
+    });
+    (sender, handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    #[test]
+    fn hashes_chunks_in_order() {
+        let msg: Vec<u8> = (0..10_000u32).map(|i| i as u8).collect();
+        let (sender, handle) = spawn_hasher();
+        for chunk in msg.chunks(777) {
+            sender.send(chunk.to_vec()).unwrap();
+        }
+        drop(sender);
+        assert_eq!(handle.join().unwrap(), Hash256::new(Sha256::new().digest(&msg)));
+    }
+}